@@ -10,3 +10,75 @@ void alloc4(int **p, int a, int b, int c, int d) {
     (*p)[2] = c;
     (*p)[3] = d;
 }
+
+struct va_mix { long l; double d; };
+struct va_big { long a, b, c; };
+struct va_flt { float x, y, z; };
+struct va_pair { long a, b; };
+long call_va_longs(long (*f)(int, ...)) { return f(10, 1L, 2L, 3L, 4L, 5L, 6L, 7L, 8L, 9L, 10L); }
+double call_va_doubles(double (*f)(int, ...)) {
+    return f(10, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.5);
+}
+long call_va_structs(long (*f)(int, ...)) {
+    struct va_mix m = {3, 1.5};
+    struct va_big b = {10, 20, 30};
+    struct va_flt v = {1.5f, 2.5f, 4.0f};
+    struct va_pair p = {100, 200};
+    return f(4, m, b, v, p);
+}
+long call_va_spill(long (*f)(int, ...)) {
+    struct va_pair p = {100, 200};
+    return f(5, 1L, 2L, 3L, 4L, p, 5L);
+}
+long sum_many(long a, long b, long c, long d, long e, long f, long g, long h, int i) {
+    return a + b * 2 + c * 3 + d * 4 + e * 5 + f * 6 + g * 7 + h * 8 + i * 9;
+}
+double fsum_many(double a, double b, double c, double d, double e, double f, double g, double h,
+                 double i, float j, int k) {
+    return a + b + c + d + e + f + g + h + i * 10 + j * 100 + k * 1000;
+}
+long call_many(long (*f)(long, long, long, long, long, long, long, char, double, double, double,
+                         double, double, double, double, double, float, double)) {
+    return f(1, 2, 3, 4, 5, 6, 7, 8, 0.5, 1, 2, 3, 4, 5, 6, 7, 8.5f, 9.25);
+}
+
+struct s_ll { long a, b; };
+struct s_dd { double x, y; };
+struct s_mix { int i; float f; double d; };
+struct s_big { long a, b, c; };
+struct s_chars { char c[3]; };
+struct s_fff { float x, y, z; };
+struct s_ld { long double v; };
+struct s_ll make_ll(long a, long b) { struct s_ll s = {a, b}; return s; }
+struct s_dd make_dd(double x, double y) { struct s_dd s = {x, y}; return s; }
+struct s_mix make_mix(int i, float f, double d) { struct s_mix s = {i, f, d}; return s; }
+struct s_big make_big(long a, long b, long c) { struct s_big s = {a, b, c}; return s; }
+struct s_chars make_chars(char a, char b, char c) { struct s_chars s = {{a, b, c}}; return s; }
+struct s_fff make_fff(float x, float y, float z) { struct s_fff s = {x, y, z}; return s; }
+struct s_ld make_ld(long double v) { struct s_ld s = {v}; return s; }
+double sum_structs(struct s_ll ll, struct s_dd dd, struct s_mix mix, struct s_big big,
+                   struct s_chars ch, struct s_fff fff, struct s_ld ld, struct s_ll ll2) {
+    return ll.a + ll.b * 2 + dd.x * 3 + dd.y * 4 + mix.i * 5 + mix.f * 6 + mix.d * 7 +
+           big.a * 8 + big.b * 9 + big.c * 10 + ch.c[0] * 11 + ch.c[1] * 12 + ch.c[2] * 13 +
+           fff.x * 14 + fff.y * 15 + fff.z * 16 + (double)ld.v * 17 + ll2.a * 18 + ll2.b * 19;
+}
+double call_struct_funcs(struct s_ll (*ll)(struct s_ll, long), struct s_big (*big)(struct s_big, int),
+                         struct s_mix (*mix)(struct s_fff, struct s_chars, struct s_dd)) {
+    struct s_ll a = {1, 2};
+    struct s_big b = {3, 4, 5};
+    struct s_fff f = {1.5f, 2.5f, 3.5f};
+    struct s_chars c = {{6, 7, 8}};
+    struct s_dd d = {0.25, 0.5};
+    struct s_ll r1 = ll(a, 10);
+    struct s_big r2 = big(b, 100);
+    struct s_mix r3 = mix(f, c, d);
+    return r1.a + r1.b * 2 + r2.a + r2.b * 2 + r2.c * 3 + r3.i + r3.f * 2 + r3.d * 4;
+}
+struct s_int3 { int a, b, c; };
+struct __attribute__((packed)) s_odd { char c; int i; short s; };
+struct s_int3 twice_int3(struct s_int3 s) { struct s_int3 r = {s.a * 2, s.b * 2, s.c * 2}; return r; }
+struct s_odd twice_odd(struct s_odd s) { struct s_odd r = {s.c * 2, s.i * 2, s.s * 2}; return r; }
+long spill_struct(long a, long b, long c, long d, long e, struct s_ll s, long f) {
+    return a + b + c + d + e + s.a * 10 + s.b * 100 + f * 1000;
+}
+long double call_ld_ret(struct s_ld (*f)(long double)) { return f(1.25L).v; }
@@ -2,6 +2,7 @@
 struct Row {
     indent: bool,
     elements: Vec<String>,
+    volatile: bool, // 最適化で削除・統合してはならない行
 }
 
 #[derive(Debug)]
@@ -25,7 +26,19 @@ impl AsmBuilder {
             .split_whitespace()
             .map(String::from)
             .collect::<Vec<String>>();
-        self.rows.push(Row { indent, elements });
+        self.rows.push(Row {
+            indent,
+            elements,
+            volatile: false,
+        });
+    }
+
+    // volatileなオブジェクトへのアクセスなど、最適化の対象にしない行を追加する
+    pub fn add_volatile_row(&mut self, row: &str, indent: bool) {
+        self.add_row(row, indent);
+        if let Some(row) = self.rows.last_mut() {
+            row.volatile = true;
+        }
     }
 
     pub fn build(&self) -> String {
@@ -46,7 +59,9 @@ impl AsmBuilder {
         let mut i = self.rows.len();
         while i > 1 {
             i -= 1;
-            if self.rows[i - 1].elements.len() == 2
+            if !self.rows[i - 1].volatile
+                && !self.rows[i].volatile
+                && self.rows[i - 1].elements.len() == 2
                 && self.rows[i].elements.len() == 2
                 && self.rows[i - 1].elements[0] == "push"
                 && self.rows[i].elements[0] == "pop"
@@ -63,7 +78,9 @@ impl AsmBuilder {
         // mov rax, [rbp-X] に最適化する
         let mut i = 0;
         while i + 1 < self.rows.len() {
-            if self.rows[i].elements.len() == 3
+            if !self.rows[i].volatile
+                && !self.rows[i + 1].volatile
+                && self.rows[i].elements.len() == 3
                 && self.rows[i].elements[0] == "lea"
                 && self.rows[i].elements[1] == "rax,"
                 && self.rows[i + 1].elements.len() == 3
@@ -7,14 +7,33 @@ mod statement;
 use crate::errors::CompileError;
 use crate::node::{Node, NodeKind};
 use crate::token::{Token, TokenKind};
-use crate::types::{AlignUp, Type, TypeKind};
+use crate::types::{AlignUp, BitField, Type, TypeKind};
+
+// 初期化子を構成する要素
+// offsetは初期化するオブジェクトの先頭からのバイトオフセット
+// ビットフィールドの場合、offsetはそれを含む記憶単位の位置を表す
+#[derive(Clone, PartialEq, Eq)]
+pub struct InitElem {
+    pub offset: usize,
+    pub bit_field: Option<BitField>,
+    pub ty: Type,
+    pub expr: Box<Node>,
+}
+
+impl fmt::Debug for InitElem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {:?}", self.offset, self.expr)
+    }
+}
 
 #[derive(Clone, PartialEq, Eq)]
 pub struct Var {
     pub name: String,
     pub offset: usize,
     pub ty: Box<Type>,
-    pub init: Vec<Option<Box<Node>>>,
+    pub init: Vec<InitElem>,
+    pub bit_field: Option<BitField>, // ビットフィールドのメンバーの場合に設定される
+    pub is_extern: bool,             // 定義を伴わないextern宣言の場合に真
 }
 
 impl Var {
@@ -24,32 +43,26 @@ impl Var {
             offset: 0,
             ty: Box::new(ty),
             init: Vec::new(),
+            bit_field: None,
+            is_extern: false,
         }
     }
+
+    // 初期化子がオブジェクト全体を1つの式で初期化しているかどうか
+    // そうでない場合、初期化子で指定されていない部分は0で初期化される
+    pub fn is_init_whole(&self) -> bool {
+        self.init.len() == 1 && self.init[0].ty.kind == self.ty.kind
+    }
 }
 
 impl fmt::Debug for Var {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}: {:?} (offset: {})", self.name, self.ty, self.offset)?;
+        if let Some(bit_field) = &self.bit_field {
+            write!(f, " : {:?}", bit_field)?;
+        }
         if !self.init.is_empty() {
-            if self.init.len() == 1 {
-                write!(f, " = ")?;
-            } else {
-                write!(f, " = {{ ")?;
-            }
-            for (i, init_node) in self.init.iter().enumerate() {
-                if let Some(node) = init_node {
-                    write!(f, "{:?}", node)?;
-                } else {
-                    write!(f, "None")?;
-                }
-                if i != self.init.len() - 1 {
-                    write!(f, ", ")?;
-                }
-            }
-            if self.init.len() > 1 {
-                write!(f, " }}")?;
-            }
+            write!(f, " = {:?}", self.init)?;
         }
         Ok(())
     }
@@ -58,8 +71,11 @@ impl fmt::Debug for Var {
 pub struct Function {
     pub name: String,
     pub body: Vec<Box<Node>>,
-    pub locals: Vec<Var>,
+    pub locals: Vec<Var>, // 先頭のparam_count個は仮引数
+    pub param_count: usize,
     pub return_ty: Type,
+    pub va_area: Option<usize>, // 可変長引数の関数でレジスタの値を保存する領域のオフセット
+    pub sret: Option<usize>, // メモリで返す戻り値の書き込み先のアドレスを保持するローカル変数のオフセット
 }
 
 impl Function {
@@ -68,14 +84,18 @@ impl Function {
             name: name.to_string(),
             body: Vec::new(),
             locals: Vec::new(),
+            param_count: 0,
             return_ty: Type::from(&TypeKind::Void, false),
+            va_area: None,
+            sret: None,
         }
     }
 }
 
 impl Function {
     fn gen_lvar(&mut self, mut var: Var) -> Result<(), CompileError> {
-        if self.find_lvar(&var.name).is_some() {
+        // 名前のない仮引数は重複してもよい
+        if !var.name.is_empty() && self.find_lvar(&var.name).is_some() {
             return Err(CompileError::Redeclaration {
                 name: var.name.clone(),
             });
@@ -85,8 +105,21 @@ impl Function {
         } else {
             0
         };
-        let offset = last_offset.align_up(var.ty.align_of()); // アラインメント調整
-        var.offset = offset + var.ty.size_of(); // サイズ分オフセットを進める
+        // 可変長配列の変数は、実行時に確保した領域へのポインタを保持する
+        let (size, align) = if var.ty.is_vla() {
+            (8, 8)
+        } else {
+            (var.ty.size_of(), var.ty.align_of())
+        };
+        // 変数はフレームポインタからオフセットを引いた位置に置かれるため、
+        // 変数の末尾までのオフセットをアラインメントに揃える
+        // フレームポインタは16バイト境界にあるため、それより大きいアラインメントの変数は
+        // 余分に確保した領域の中で、実行時に境界に揃えた位置に置く
+        var.offset = if align > 16 {
+            (last_offset + size + align - 16).align_up(16)
+        } else {
+            (last_offset + size).align_up(align.max(1))
+        };
         self.locals.push(var); // オフセット計算のために末尾に追加
         Ok(())
     }
@@ -109,6 +142,34 @@ impl fmt::Debug for Function {
     }
 }
 
+// 組み込みのva_list型の要素となる構造体の識別番号
+const VA_LIST_TAG_ID: usize = 0;
+
+// 通常の識別子の名前空間に属する識別子
+#[derive(Clone, Debug)]
+enum ScopedIdent {
+    EnumConst { val: i64, ty: Type }, // 列挙定数
+    Typedef(Type),                    // typedef名
+    Var,                              // 変数（外側のtypedef名を隠すために登録する）
+}
+
+// タグの名前空間に属する識別子
+#[derive(Clone, Debug)]
+enum Tag {
+    // 列挙型は基底型で表す
+    // completeが偽の場合は前方参照のみで、列挙子の並びが未定義
+    Enum { ty: Type, complete: bool },
+    // 構造体・共用体はtag_typesの添字で表す
+    Struct(usize),
+}
+
+// ファイルまたはブロックのスコープ
+#[derive(Default)]
+struct Scope {
+    idents: Vec<(String, ScopedIdent)>,
+    tags: Vec<(String, Tag)>,
+}
+
 pub struct Ast {
     tokens: Vec<Token>,
     token_pos: usize,
@@ -116,20 +177,91 @@ pub struct Ast {
     pub funcs: Vec<Box<Function>>,
     current_func: Option<Box<Function>>,
     pub string_literals: Vec<String>,
+    scopes: Vec<Scope>,   // 先頭がファイルスコープ
+    tag_types: Vec<Type>, // 構造体・共用体の型（添字は型のid）
+    unsigned_char: bool,  // 符号指定のないcharをunsigned charとして扱うか
+    // #pragma packの指定（指定が現れたトークン位置と、それ以降の上限値）
+    packs: Vec<(usize, Option<usize>)>,
 }
 
 impl Ast {
-    pub fn new(tokens: &[Token]) -> Self {
+    pub fn new(tokens: &[Token], unsigned_char: bool) -> Self {
+        // #pragma packは構文の一部ではないため、トークン列から取り除いて位置を記録する
+        let mut packs = Vec::new();
+        let mut stripped = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            if let TokenKind::PragmaPack(pack) = token.kind {
+                packs.push((stripped.len(), pack));
+            } else {
+                stripped.push(token.clone());
+            }
+        }
+        // 組み込みのva_list型は__va_list_tag構造体の要素数1の配列
+        let va_list_tag = Self::va_list_tag();
+        let va_list = Type::from(
+            &TypeKind::Array {
+                base: Box::new(va_list_tag.clone()),
+                size: Some(1),
+            },
+            false,
+        );
+        let file_scope = Scope {
+            idents: vec![(
+                "__builtin_va_list".to_string(),
+                ScopedIdent::Typedef(va_list),
+            )],
+            tags: Vec::new(),
+        };
         Ast {
-            tokens: tokens.to_vec(),
+            tokens: stripped,
             token_pos: 0,
             globals: Vec::new(),
             funcs: Vec::new(),
             current_func: None,
             string_literals: Vec::new(),
+            scopes: vec![file_scope],
+            tag_types: vec![va_list_tag],
+            unsigned_char,
+            packs,
         }
     }
 
+    // System V ABIのva_listの要素となる構造体
+    // tag_typesの先頭（VA_LIST_TAG_ID）に置く
+    fn va_list_tag() -> Type {
+        let void_ptr = TypeKind::Ptr {
+            to: Box::new(Type::from(&TypeKind::Void, false)),
+        };
+        let members = [
+            ("gp_offset", TypeKind::UInt),
+            ("fp_offset", TypeKind::UInt),
+            ("overflow_arg_area", void_ptr.clone()),
+            ("reg_save_area", void_ptr),
+        ]
+        .iter()
+        .map(|(name, kind)| Var::new(name, Type::from(kind, false)))
+        .collect();
+        Type::from(
+            &TypeKind::Struct {
+                name: "__va_list_tag".to_string(),
+                members,
+                id: VA_LIST_TAG_ID,
+                complete: true,
+                align: None,
+            },
+            false,
+        )
+    }
+
+    // 現在のトークン位置で有効な#pragma packの値
+    fn current_pack(&self) -> Option<usize> {
+        self.packs
+            .iter()
+            .rev()
+            .find(|(pos, _)| *pos <= self.token_pos)
+            .and_then(|(_, pack)| *pack)
+    }
+
     fn get_current_func(&mut self) -> Result<&mut Box<Function>, CompileError> {
         self.current_func
             .as_mut()
@@ -138,8 +270,157 @@ impl Ast {
             })
     }
 
+    fn enter_scope(&mut self) {
+        self.scopes.push(Scope::default());
+    }
+
+    fn leave_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn current_scope(&mut self) -> &mut Scope {
+        self.scopes
+            .last_mut()
+            .expect("ファイルスコープは常に存在する")
+    }
+
+    // 内側のスコープから順に識別子を探す
+    fn find_scoped_ident(&self, name: &str) -> Option<&ScopedIdent> {
+        self.scopes.iter().rev().find_map(|scope| {
+            scope
+                .idents
+                .iter()
+                .rev()
+                .find(|(n, _)| n == name)
+                .map(|(_, ident)| ident)
+        })
+    }
+
+    // 現在のスコープに識別子を登録する
+    fn push_scoped_ident(&mut self, name: &str, ident: ScopedIdent) -> Result<(), CompileError> {
+        let scope = self.current_scope();
+        if scope.idents.iter().any(|(n, _)| n == name) {
+            return Err(CompileError::Redeclaration {
+                name: name.to_string(),
+            });
+        }
+        scope.idents.push((name.to_string(), ident));
+        Ok(())
+    }
+
+    // 変数名を現在のスコープに登録する
+    // 関数の宣言は繰り返せるため、同じスコープの変数とは重複してもよい
+    fn push_var_ident(&mut self, name: &str) -> Result<(), CompileError> {
+        let scope = self.current_scope();
+        match scope.idents.iter().find(|(n, _)| n == name) {
+            Some((_, ScopedIdent::Var)) => Ok(()),
+            Some(_) => Err(CompileError::Redeclaration {
+                name: name.to_string(),
+            }),
+            None => {
+                scope.idents.push((name.to_string(), ScopedIdent::Var));
+                Ok(())
+            }
+        }
+    }
+
+    // 識別子がtypedef名であればその型を返す
+    fn find_typedef(&self, name: &str) -> Option<Type> {
+        match self.find_scoped_ident(name) {
+            Some(ScopedIdent::Typedef(ty)) => Some(self.complete_type(ty)),
+            _ => None,
+        }
+    }
+
+    // 内側のスコープから順にタグを探す
+    // in_current_scopeが真の場合は現在のスコープのみを探す
+    fn find_tag(&mut self, name: &str, in_current_scope: bool) -> Option<&mut Tag> {
+        let depth = if in_current_scope {
+            1
+        } else {
+            self.scopes.len()
+        };
+        self.scopes.iter_mut().rev().take(depth).find_map(|scope| {
+            scope
+                .tags
+                .iter_mut()
+                .find(|(n, _)| n == name)
+                .map(|(_, tag)| tag)
+        })
+    }
+
+    // 不完全型の構造体・共用体を、完全型になっていればその型に置き換える
+    // ポインタや配列の要素型も置き換える
+    fn complete_type(&self, ty: &Type) -> Type {
+        let mut completed = match &ty.kind {
+            TypeKind::Struct {
+                id,
+                complete: false,
+                ..
+            }
+            | TypeKind::Union {
+                id,
+                complete: false,
+                ..
+            } => self.tag_types[*id].clone(),
+            TypeKind::Ptr { to } => Type::from(
+                &TypeKind::Ptr {
+                    to: Box::new(self.complete_type(to)),
+                },
+                false,
+            ),
+            TypeKind::Array { base, size } => Type::from(
+                &TypeKind::Array {
+                    base: Box::new(self.complete_type(base)),
+                    size: *size,
+                },
+                false,
+            ),
+            _ => return ty.clone(),
+        };
+        completed.is_const = ty.is_const;
+        completed.is_volatile = ty.is_volatile;
+        // _Alignasなどで引き上げたアラインメントは維持する
+        if ty.align_of() > completed.align_of() {
+            completed.set_align(ty.align_of());
+        }
+        completed
+    }
+
+    // 不完全型のオブジェクトは定義できない
+    fn check_complete(&self, var: &Var) -> Result<(), CompileError> {
+        if matches!(var.ty.kind, TypeKind::Array { size: None, .. }) {
+            return Err(CompileError::InvalidDeclaration {
+                msg: format!("配列 '{}' の要素数が決まりません", var.name),
+            });
+        }
+        if var.ty.is_incomplete() {
+            return Err(CompileError::InvalidDeclaration {
+                msg: format!(
+                    "'{}' の型 {} は不完全型です",
+                    var.name,
+                    var.ty.base_type().tag_name()
+                ),
+            });
+        }
+        Ok(())
+    }
+
     fn gen_gvar(&mut self, var: Var) -> Result<(), CompileError> {
-        if self.find_gvar(&var.name).is_some() {
+        if let Some(gvar) = self.find_gvar(&var.name) {
+            // 関数の宣言は繰り返してもよい
+            if gvar.ty.is_func() && var.ty.is_func() {
+                *gvar = var;
+                return Ok(());
+            }
+            // extern宣言は適合する型であれば繰り返してもよく、
+            // 定義や要素数が確定した型の宣言があればそれを優先する
+            if (gvar.is_extern || var.is_extern) && gvar.ty.is_compatible(&var.ty) {
+                if !var.is_extern || gvar.is_extern && !var.ty.is_incomplete() {
+                    *gvar = var;
+                }
+                return Ok(());
+            }
             return Err(CompileError::Redeclaration {
                 name: var.name.clone(),
             });
@@ -155,6 +436,25 @@ impl Ast {
             .map(|v| v as _)
     }
 
+    // 関数の戻り値の型を取得
+    // 宣言されていない関数は暗黙にintを返すものとして扱う
+    fn find_func_return_ty(&mut self, name: &str) -> Type {
+        if let Some(func) = &self.current_func
+            && func.name == name
+        {
+            return func.return_ty.clone();
+        }
+        if let Some(func) = self.funcs.iter().find(|func| func.name == name) {
+            return func.return_ty.clone();
+        }
+        if let Some(gvar) = self.find_gvar(name)
+            && let TypeKind::Func { return_ty, .. } = &gvar.ty.kind
+        {
+            return *return_ty.clone();
+        }
+        Type::from(&TypeKind::Int, false)
+    }
+
     // 現在のトークンを取得
     fn get_token(&self) -> Option<&Token> {
         self.tokens.get(self.token_pos)
@@ -209,7 +509,7 @@ impl Ast {
     fn consume_string(&mut self) -> Option<String> {
         match self.get_token() {
             Some(Token {
-                kind: TokenKind::String(s),
+                kind: TokenKind::String(s, _),
                 ..
             }) => {
                 let s_clone = s.clone();
@@ -220,13 +520,42 @@ impl Ast {
         }
     }
 
-    fn consume_number(&mut self) -> Option<i64> {
+    // 文字列リテラルを読み、実行文字集合でのバイト列を返す
+    fn consume_string_bytes(&mut self) -> Option<Vec<u8>> {
+        match self.get_token() {
+            Some(Token {
+                kind: TokenKind::String(_, bytes),
+                ..
+            }) => {
+                let bytes_clone = bytes.clone();
+                self.advance_token();
+                Some(bytes_clone)
+            }
+            _ => None,
+        }
+    }
+
+    fn consume_number(&mut self) -> Option<(i64, TypeKind)> {
         match self.get_token() {
             Some(Token {
-                kind: TokenKind::Number(val),
+                kind: TokenKind::Number(val, ty),
                 ..
             }) => {
-                let val_clone = *val;
+                let val_clone = (*val, *ty.clone());
+                self.advance_token();
+                Some(val_clone)
+            }
+            _ => None,
+        }
+    }
+
+    fn consume_float_number(&mut self) -> Option<(f64, u128, TypeKind)> {
+        match self.get_token() {
+            Some(Token {
+                kind: TokenKind::FloatNumber(bits, ext, ty),
+                ..
+            }) => {
+                let val_clone = (f64::from_bits(*bits), *ext, *ty.clone());
                 self.advance_token();
                 Some(val_clone)
             }
@@ -259,27 +588,16 @@ impl Ast {
         self.expect(&TokenKind::Keyword(word.to_string()))
     }
 
-    fn expect_number(&mut self) -> Result<i64, CompileError> {
+    fn peek_punctuator(&mut self, sym: &str) -> bool {
         match self.get_token() {
-            Some(token) => {
-                if let TokenKind::Number(val) = &token.kind {
-                    let val_clone = *val;
-                    self.advance_token();
-                    return Ok(val_clone);
-                }
-                Err(CompileError::UnexpectedToken {
-                    expected: TokenKind::Number(0),
-                    found: token.kind.clone(),
-                    span: token.span,
-                })
-            }
-            _ => Err(CompileError::UnexpectedEof),
+            Some(token) => matches!(&token.kind, TokenKind::Punctuator(s) if s == sym),
+            _ => false,
         }
     }
 
-    fn peek_punctuator(&mut self, sym: &str) -> bool {
+    fn peek_keyword(&mut self, word: &str) -> bool {
         match self.get_token() {
-            Some(token) => matches!(&token.kind, TokenKind::Punctuator(s) if s == sym),
+            Some(token) => matches!(&token.kind, TokenKind::Keyword(s) if s == word),
             _ => false,
         }
     }
@@ -306,57 +624,112 @@ impl Ast {
     // external_declaration ::= func_def
     //                          | declaration
     fn external_declaration(&mut self) -> Result<(), CompileError> {
+        if self.static_assert_declaration()? {
+            return Ok(());
+        }
+        let specifiers = self.declaration_specifiers()?;
+        if specifiers.is_empty() {
+            return Err(CompileError::InvalidDeclaration {
+                msg: "外部宣言のパースに失敗しました".to_string(),
+            });
+        }
+        let base_ty = Type::from_ds(&specifiers, self.unsigned_char)?;
+        // 宣言子を持たない宣言（例: enum E { A, B };）
+        if self.consume_punctuator(";").is_some() {
+            return Ok(());
+        }
+        let attrs = Self::declaration_attributes(&specifiers);
+        if Self::is_typedef(&specifiers) {
+            return self.typedef_declarator_list(base_ty, attrs, None);
+        }
+        let first = self.declarator(base_ty.clone())?;
         // 関数定義
-        let token_pos = self.token_pos;
-        if let Some(func) = self.func_def()? {
+        if first.ty.is_func() && self.peek_punctuator("{") {
+            // 関数本体から再帰的に参照できるように、先に関数を宣言する
+            self.push_var_ident(&first.name)?;
+            self.gen_gvar(*first.clone())?;
+            let func = self.func_def(*first)?;
             self.funcs.push(func);
             return Ok(());
         }
-        self.token_pos = token_pos; // 関数定義でなかった場合、トークン位置を元に戻す
         // グローバル変数宣言
-        if let Some(vars) = self.declaration()? {
-            for var in vars {
-                self.gen_gvar(var)?;
+        let is_extern = Self::is_extern(&specifiers);
+        for mut var in self.init_declarator_list_from(first, base_ty, attrs)? {
+            // 初期化子を持つextern宣言は定義になる
+            var.is_extern = is_extern && var.init.is_empty();
+            // extern宣言の不完全型は他の宣言で完全型になる
+            if !var.ty.is_func() && !var.is_extern {
+                self.check_complete(&var)?;
             }
-            return Ok(());
+            self.push_var_ident(&var.name)?;
+            self.gen_gvar(var)?;
         }
-        Err(CompileError::InvalidDeclaration {
-            msg: "外部宣言のパースに失敗しました".to_string(),
-        })
+        self.expect_punctuator(";")
     }
 
-    // func_def ::= declaration_specifier declarator compound_stmt
-    fn func_def(&mut self) -> Result<Option<Box<Function>>, CompileError> {
-        let specifier = self.declaration_specifier()?;
-        let base_kind = if let Some(specifier) = specifier {
-            Type::from_ds(&vec![specifier]).unwrap()
-        } else {
-            return Err(CompileError::InvalidTypeSpecifier {
-                msg: "関数定義の型指定子が無効です".to_string(),
-            });
-        };
-        let func_decl = if let Ok(var) = self.declarator(base_kind) {
-            var
-        } else {
-            return Err(CompileError::InvalidDeclaration {
-                msg: "関数定義のパースに失敗しました".to_string(),
-            });
-        };
+    // func_def ::= declaration_specifiers declarator compound_stmt
+    // 宣言指定子と宣言子はexternal_declarationで解析済み
+    fn func_def(&mut self, func_decl: Var) -> Result<Box<Function>, CompileError> {
         let mut func = Box::new(Function::new(&func_decl.name));
-        if let TypeKind::Func { params, return_ty } = func_decl.ty.kind {
+        // 仮引数は関数本体を囲むスコープに属する
+        self.enter_scope();
+        if let TypeKind::Func {
+            params,
+            return_ty,
+            variadic,
+        } = func_decl.ty.kind
+        {
             for param in params {
+                self.push_var_ident(&param.name)?;
                 func.gen_lvar(param.clone())?;
             }
-            func.return_ty = *return_ty;
-        } else {
-            return Ok(None);
+            func.param_count = func.locals.len();
+            func.return_ty = self.complete_type(&return_ty);
+            // 可変長引数の関数には、引数レジスタの値を保存する領域を確保する
+            // 汎用レジスタ6個分の48バイトとXMMレジスタ8個分の128バイトからなる
+            if variadic {
+                let area_ty = Type::from(
+                    &TypeKind::Array {
+                        base: Box::new(Type::from(&TypeKind::ULong, false)),
+                        size: Some(22),
+                    },
+                    false,
+                );
+                func.gen_lvar(Var::new("", area_ty))?;
+                func.va_area = func.locals.last().map(|var| var.offset);
+            }
+            // メモリで返す構造体・共用体の戻り値は、呼び出し元が用意した領域に書き込む
+            // その領域のアドレスは隠れた第1引数として渡されるため、ローカル変数に保存しておく
+            if func.return_ty.is_struct()
+                && func.return_ty.eightbyte_classes().is_none()
+                && !func.return_ty.is_x87_aggregate()
+            {
+                let ptr_ty = Type::from(
+                    &TypeKind::Ptr {
+                        to: Box::new(Type::from(&TypeKind::Void, false)),
+                    },
+                    false,
+                );
+                func.gen_lvar(Var::new("", ptr_ty))?;
+                func.sret = func.locals.last().map(|var| var.offset);
+            }
         }
         self.current_func = Some(func);
-        let func_body = if let Some(func_body) = self.compound_stmt()? {
-            func_body
-        } else {
-            return Ok(None);
-        };
+        // 可変長配列を指す仮引数の要素の大きさは、関数の先頭で求める
+        let mut vla_sizes = Vec::new();
+        for i in 0..self.get_current_func()?.param_count {
+            let mut ty = self.get_current_func()?.locals[i].ty.clone();
+            if ty.is_variably_modified() {
+                self.declare_vla_sizes(&mut ty, &mut vla_sizes)?;
+                self.get_current_func()?.locals[i].ty = ty;
+            }
+        }
+        let func_body = self
+            .compound_stmt()?
+            .ok_or_else(|| CompileError::InvalidDeclaration {
+                msg: "関数本体がありません".to_string(),
+            })?;
+        self.leave_scope();
         func = self
             .current_func
             .take()
@@ -364,12 +737,17 @@ impl Ast {
                 msg: "現在の関数が設定されていません".to_string(),
             })?;
         if let NodeKind::Block { body } = func_body.kind {
+            // 関数本体のすべての式に型を設定する
+            let mut body = vla_sizes.into_iter().chain(body).collect::<Vec<_>>();
+            for node in body.iter_mut() {
+                node.assign_types()?;
+            }
             func.body = body;
         } else {
             return Err(CompileError::InvalidDeclaration {
                 msg: "関数本体がブロックではありません".to_string(),
             });
         }
-        Ok(Some(func))
+        Ok(func)
     }
 }
@@ -1,48 +1,172 @@
-use crate::ast::{Ast, Var};
+use crate::ast::{Ast, Function, InitElem, ScopedIdent, Tag, Var};
 use crate::errors::CompileError;
 use crate::node::Node;
+use crate::token::TokenKind;
 use crate::types::{
-    DeclarationSpecifier, FunctionKind, StorageClassKind, Type, TypeKind, TypeQualifierKind,
-    TypeSpecifierQualifier,
+    Attributes, BitField, DeclarationSpecifier, FunctionKind, StorageClassKind, Type, TypeKind,
+    TypeQualifierKind, TypeSpecifierKind, TypeSpecifierQualifier,
 };
 
 impl Ast {
     // declaration ::= declaration_specifiers init_declarator_list ";"
-    pub(super) fn declaration(&mut self) -> Result<Option<Vec<Var>>, CompileError> {
+    //                 | static_assert_declaration
+    // 可変長配列のtypedefでサイズを求める文はbodyに追加する
+    #[allow(clippy::vec_box)]
+    pub(super) fn declaration(
+        &mut self,
+        body: &mut Vec<Box<Node>>,
+    ) -> Result<Option<Vec<Var>>, CompileError> {
+        if self.static_assert_declaration()? {
+            return Ok(Some(Vec::new()));
+        }
         let specifiers = self.declaration_specifiers()?;
         if specifiers.is_empty() {
             return Ok(None);
         }
-        let base_ty = Type::from_ds(&specifiers).unwrap();
-        let vars = self.init_declarator_list(base_ty)?;
-        if vars.is_empty() {
-            return Ok(None);
+        let base_ty = Type::from_ds(&specifiers, self.unsigned_char)?;
+        let attrs = Self::declaration_attributes(&specifiers);
+        if Self::is_typedef(&specifiers) {
+            self.typedef_declarator_list(base_ty, attrs, Some(body))?;
+            return Ok(Some(Vec::new()));
+        }
+        let mut vars = self.init_declarator_list(base_ty, attrs)?;
+        let is_extern = Self::is_extern(&specifiers);
+        for var in vars.iter_mut() {
+            var.is_extern = is_extern && var.init.is_empty();
         }
         self.expect_punctuator(";")?;
         Ok(Some(vars))
     }
 
+    // static_assert_declaration ::= "_Static_assert" "(" const_expr ("," string_literal+)? ")" ";"
+    // 静的表明を評価し、成り立たない場合はメッセージと位置を報告する
+    pub(super) fn static_assert_declaration(&mut self) -> Result<bool, CompileError> {
+        let Some(span) = self.consume_keyword("_Static_assert").map(|t| t.span) else {
+            return Ok(false);
+        };
+        self.expect_punctuator("(")?;
+        let val = self.eval_const_expr()?;
+        // C23ではメッセージを省略できる
+        let msg = if self.consume_punctuator(",").is_some() {
+            let mut msg = self
+                .consume_string()
+                .ok_or(CompileError::InvalidDeclaration {
+                    msg: "静的表明のメッセージには文字列リテラルが必要です".to_string(),
+                })?;
+            while let Some(s) = self.consume_string() {
+                msg.push_str(&s);
+            }
+            Some(msg)
+        } else {
+            None
+        };
+        self.expect_punctuator(")")?;
+        self.expect_punctuator(";")?;
+        if val == 0 {
+            return Err(CompileError::StaticAssertion { msg, span });
+        }
+        Ok(true)
+    }
+
+    // 宣言指定子にtypedefが含まれるかどうか
+    pub(super) fn is_typedef(specifiers: &[DeclarationSpecifier]) -> bool {
+        specifiers.contains(&DeclarationSpecifier::StorageClassSpecifier(
+            StorageClassKind::Typedef,
+        ))
+    }
+
+    // 宣言指定子にexternが含まれるかどうか
+    pub(super) fn is_extern(specifiers: &[DeclarationSpecifier]) -> bool {
+        specifiers.contains(&DeclarationSpecifier::StorageClassSpecifier(
+            StorageClassKind::Extern,
+        ))
+    }
+
+    // 宣言指定子に含まれる属性
+    pub(super) fn declaration_attributes(specifiers: &[DeclarationSpecifier]) -> Attributes {
+        Self::specifier_attributes(specifiers.iter().filter_map(|specifier| match specifier {
+            DeclarationSpecifier::TypeSpecifierQualifier(tsq) => Some(tsq),
+            _ => None,
+        }))
+    }
+
+    // 属性で指定されたアラインメントを宣言されたオブジェクトの型に適用する
+    // 本来のアラインメントより小さくはしない
+    fn apply_aligned(ty: &mut Type, attrs: Attributes) {
+        if let Some(aligned) = attrs.aligned
+            && !ty.is_func()
+            && aligned > ty.align_of()
+        {
+            ty.set_align(aligned);
+        }
+    }
+
+    // typedef宣言の declarator attribute_specifier* ("," declarator attribute_specifier*)* ";"
+    // 宣言子の名前を型の別名として現在のスコープに登録する
+    // 可変長配列の大きさはtypedef宣言の位置で評価し、その値を型に記録する（C11 6.7.8p3）
+    #[allow(clippy::vec_box)]
+    pub(super) fn typedef_declarator_list(
+        &mut self,
+        base_ty: Type,
+        attrs: Attributes,
+        mut body: Option<&mut Vec<Box<Node>>>,
+    ) -> Result<(), CompileError> {
+        loop {
+            let mut var = self.declarator(base_ty.clone())?;
+            let mut attrs = attrs;
+            attrs.merge(self.attribute_specifiers()?);
+            Self::apply_aligned(&mut var.ty, attrs);
+            if let Some(body) = body.as_deref_mut()
+                && var.ty.is_variably_modified()
+            {
+                self.declare_vla_sizes(&mut var.ty, body)?;
+            }
+            match self
+                .current_scope()
+                .idents
+                .iter()
+                .find(|(n, _)| *n == var.name)
+            {
+                // 同じ型の再定義は許される
+                Some((_, ScopedIdent::Typedef(ty))) if *ty == *var.ty => {}
+                _ => self.push_scoped_ident(&var.name, ScopedIdent::Typedef(*var.ty))?,
+            }
+            if self.consume_punctuator(",").is_none() {
+                return self.expect_punctuator(";");
+            }
+        }
+    }
+
     // declaration_specifiers ::= declaration_specifier+
     pub(super) fn declaration_specifiers(
         &mut self,
     ) -> Result<Vec<DeclarationSpecifier>, CompileError> {
         let mut specifiers = Vec::new();
-        while let Some(specifier) = self.declaration_specifier()? {
+        let mut has_type = false;
+        while let Some(specifier) = self.declaration_specifier(has_type)? {
+            has_type |= matches!(
+                specifier,
+                DeclarationSpecifier::TypeSpecifierQualifier(
+                    TypeSpecifierQualifier::TypeSpecifier(_)
+                )
+            );
             specifiers.push(specifier);
         }
         Ok(specifiers)
     }
 
     // declaration_specifier ::= storage_class_specifier | type_specifier_qualifier | function_specifier
+    // has_typeが真の場合、識別子はtypedef名ではなく宣言子の名前とみなす
     pub(super) fn declaration_specifier(
         &mut self,
+        has_type: bool,
     ) -> Result<Option<DeclarationSpecifier>, CompileError> {
         if let Some(storage_class_specifier) = self.storage_class_specifier() {
             return Ok(Some(DeclarationSpecifier::StorageClassSpecifier(
                 storage_class_specifier,
             )));
         }
-        if let Some(type_specifier_qualifier) = self.type_specifier_qualifier()? {
+        if let Some(type_specifier_qualifier) = self.type_specifier_qualifier(has_type)? {
             return Ok(Some(DeclarationSpecifier::TypeSpecifierQualifier(
                 type_specifier_qualifier,
             )));
@@ -56,32 +180,73 @@ impl Ast {
     }
 
     // init_declarator_list ::= init_declarator ("," init_declarator)*
-    fn init_declarator_list(&mut self, base_ty: Type) -> Result<Vec<Var>, CompileError> {
-        let mut vars = Vec::new();
-        if let Some(var) = self.init_declarator(base_ty.clone())? {
-            vars.push(*var);
+    // 宣言子がない場合は空のリストを返す
+    // attrsは宣言指定子に含まれる属性で、すべての宣言子に適用される
+    fn init_declarator_list(
+        &mut self,
+        base_ty: Type,
+        attrs: Attributes,
+    ) -> Result<Vec<Var>, CompileError> {
+        match self.declarator(base_ty.clone()) {
+            Ok(first) => self.init_declarator_list_from(first, base_ty, attrs),
+            Err(_) => Ok(Vec::new()),
         }
+    }
+
+    // 先頭の宣言子を解析済みのinit_declarator_list
+    pub(super) fn init_declarator_list_from(
+        &mut self,
+        first: Box<Var>,
+        base_ty: Type,
+        attrs: Attributes,
+    ) -> Result<Vec<Var>, CompileError> {
+        let mut vars = vec![*self.init_declarator_initializer(first, attrs)?];
         while self.consume_punctuator(",").is_some() {
-            if let Some(var) = self.init_declarator(base_ty.clone())? {
+            if let Some(var) = self.init_declarator(base_ty.clone(), attrs)? {
                 vars.push(*var);
             }
         }
         Ok(vars)
     }
 
-    // init_declarator ::= declarator
-    //                     | declarator "=" initializer
-    fn init_declarator(&mut self, base_ty: Type) -> Result<Option<Box<Var>>, CompileError> {
-        if let Ok(mut var) = self.declarator(base_ty) {
-            if self.consume_punctuator("=").is_some() {
-                // TODO: 代入時の型チェック
-                var.init = self.initializer()?; // initializerを設定
-            }
-            return Ok(Some(var));
+    // init_declarator ::= declarator attribute_specifier*
+    //                     | declarator attribute_specifier* "=" initializer
+    fn init_declarator(
+        &mut self,
+        base_ty: Type,
+        attrs: Attributes,
+    ) -> Result<Option<Box<Var>>, CompileError> {
+        if let Ok(var) = self.declarator(base_ty) {
+            return Ok(Some(self.init_declarator_initializer(var, attrs)?));
         }
         Ok(None)
     }
 
+    // init_declaratorの attribute_specifier* ("=" initializer)? の部分
+    fn init_declarator_initializer(
+        &mut self,
+        mut var: Box<Var>,
+        mut attrs: Attributes,
+    ) -> Result<Box<Var>, CompileError> {
+        attrs.merge(self.attribute_specifiers()?);
+        Self::apply_aligned(&mut var.ty, attrs);
+        if self.consume_punctuator("=").is_some() {
+            if var.ty.is_vla() {
+                return Err(CompileError::InvalidInitializer {
+                    msg: format!("可変長配列 '{}' は初期化できません", var.name),
+                });
+            }
+            // TODO: 代入時の型チェック
+            let ty = *var.ty.clone();
+            let count = self.initializer(&ty, 0, &mut var.init)?; // initializerを設定
+            // 要素数が省略された配列は初期化子の要素数で完全型になる
+            if matches!(ty.kind, TypeKind::Array { size: None, .. }) {
+                var.ty.set_array_len(count);
+            }
+        }
+        Ok(var)
+    }
+
     // storage_class_specifier ::= "auto" | "extern" | "register" | "static" | "typedef"
     fn storage_class_specifier(&mut self) -> Option<StorageClassKind> {
         StorageClassKind::all()
@@ -89,38 +254,339 @@ impl Ast {
             .find(|specifier| self.consume_keyword(&specifier.to_string()).is_some())
     }
 
-    // type_specifier ::= "void" | "char" | "short" | "int" | "long" | "float" | "double" | struct_or_union_specifier
-    fn type_specifier(&mut self) -> Result<Option<TypeKind>, CompileError> {
+    // type_specifier ::= "void" | "char" | "short" | "int" | "long" | "float" | "double"
+    //                    | "signed" | "unsigned" | "_Bool" | struct_or_union_specifier
+    //                    | enum_specifier | typedef_name
+    fn type_specifier(
+        &mut self,
+        has_type: bool,
+    ) -> Result<Option<TypeSpecifierKind>, CompileError> {
         if let Some(ty) = self.struct_or_union_specifier()? {
-            return Ok(Some(ty));
+            return Ok(Some(TypeSpecifierKind::Struct(ty)));
+        }
+        if let Some(ty) = self.enum_specifier()? {
+            return Ok(Some(TypeSpecifierKind::Enum(ty)));
         }
-        Ok(TypeKind::all()
+        if !has_type && let Some((name, ty)) = self.consume_typedef_name() {
+            return Ok(Some(TypeSpecifierKind::TypedefName(name, ty)));
+        }
+        Ok(TypeSpecifierKind::all()
             .into_iter()
             .find(|specifier| self.consume_keyword(&specifier.to_string()).is_some()))
     }
 
-    // struct_or_union_specifier ::= "struct" ident? "{" struct_declaration_list "}"
+    // typedef_name ::= identifier
+    // 識別子が変数などに隠されていないtypedef名の場合のみ読み進める
+    fn consume_typedef_name(&mut self) -> Option<(String, Type)> {
+        let Some(TokenKind::Identifier(name)) = self.get_token().map(|t| &t.kind) else {
+            return None;
+        };
+        let name = name.clone();
+        let ty = self.find_typedef(&name)?;
+        self.advance_token();
+        Some((name, ty))
+    }
+
+    // struct_or_union_specifier ::= ("struct" | "union") attribute_specifier* identifier? "{" struct_declaration_list "}" attribute_specifier*
+    //                               | ("struct" | "union") attribute_specifier* identifier
     fn struct_or_union_specifier(&mut self) -> Result<Option<TypeKind>, CompileError> {
-        if self.consume_keyword("struct").is_some() {
-            let struct_name = if let Some(name) = self.consume_ident() {
-                name
+        let is_union = if self.consume_keyword("struct").is_some() {
+            false
+        } else if self.consume_keyword("union").is_some() {
+            true
+        } else {
+            return Ok(None);
+        };
+        let mut attrs = self.attribute_specifiers()?;
+        let tag = self.consume_ident();
+
+        if !self.peek_punctuator("{") {
+            let tag = tag.ok_or_else(|| CompileError::InvalidTypeSpecifier {
+                msg: "構造体・共用体のタグがありません".to_string(),
+            })?;
+            // "struct S;" は現在のスコープで新しいタグを宣言する
+            // それ以外は外側のスコープも含めてタグを参照する
+            let forward_decl = self.peek_punctuator(";");
+            if let Some(tag_ref) = self.find_tag(&tag, forward_decl).cloned() {
+                let id = self.struct_tag_id(&tag, tag_ref, is_union)?;
+                return Ok(Some(self.tag_types[id].kind.clone()));
+            }
+            let id = self.declare_struct_tag(&tag, is_union);
+            return Ok(Some(self.tag_types[id].kind.clone()));
+        }
+
+        // 同じスコープで前方宣言されたタグは、その型を完全型にする
+        let id = match &tag {
+            Some(tag) => match self.find_tag(tag, true).cloned() {
+                Some(tag_ref) => {
+                    let id = self.struct_tag_id(tag, tag_ref, is_union)?;
+                    if !self.tag_types[id].is_incomplete() {
+                        return Err(CompileError::Redeclaration { name: tag.clone() });
+                    }
+                    id
+                }
+                None => self.declare_struct_tag(tag, is_union),
+            },
+            None => self.declare_struct_tag("", is_union),
+        };
+        // #pragma pack は定義の開始時点の値を用いる
+        let pack = self.current_pack();
+        self.expect_punctuator("{")?;
+        let members = self.struct_declaration_list()?;
+        self.expect_punctuator("}")?;
+        attrs.merge(self.attribute_specifiers()?);
+        let members = Self::align_members(members, attrs.packed, pack);
+        let name = tag.unwrap_or_default();
+        let kind = if is_union {
+            TypeKind::Union {
+                name,
+                members,
+                id,
+                complete: true,
+                align: attrs.aligned,
+            }
+        } else {
+            TypeKind::Struct {
+                name,
+                members,
+                id,
+                complete: true,
+                align: attrs.aligned,
+            }
+        };
+        self.tag_types[id] = Type::from(&kind, false);
+        Ok(Some(self.tag_types[id].kind.clone()))
+    }
+
+    // 構造体・共用体のメンバーのアラインメントを決める
+    // packedの場合は1、#pragma packの値があればそれを上限とし、メンバーに指定されたアラインメントは維持する
+    // いずれかが指定されたビットフィールドは記憶単位をまたいで詰めて配置する
+    fn align_members(
+        members: Vec<(Var, Attributes)>,
+        packed: bool,
+        pack: Option<usize>,
+    ) -> Vec<Var> {
+        members
+            .into_iter()
+            .map(|(mut member, attrs)| {
+                let natural = if packed || attrs.packed {
+                    1
+                } else {
+                    member.ty.align_of()
+                };
+                let mut align = natural.max(attrs.aligned.unwrap_or(1));
+                if let Some(pack) = pack {
+                    align = align.min(pack);
+                }
+                member.ty.set_align(align);
+                if let Some(bit_field) = &mut member.bit_field {
+                    bit_field.packed = packed || attrs.packed || pack.is_some();
+                }
+                member
+            })
+            .collect()
+    }
+
+    // 不完全型の構造体・共用体のタグを現在のスコープに宣言し、そのidを返す
+    fn declare_struct_tag(&mut self, tag: &str, is_union: bool) -> usize {
+        let id = self.tag_types.len();
+        let (name, members) = (tag.to_string(), Vec::new());
+        let kind = if is_union {
+            TypeKind::Union {
+                name,
+                members,
+                id,
+                complete: false,
+                align: None,
+            }
+        } else {
+            TypeKind::Struct {
+                name,
+                members,
+                id,
+                complete: false,
+                align: None,
+            }
+        };
+        self.tag_types.push(Type::from(&kind, false));
+        if !tag.is_empty() {
+            self.current_scope()
+                .tags
+                .push((tag.to_string(), Tag::Struct(id)));
+        }
+        id
+    }
+
+    // タグが構造体・共用体の種類と一致する場合、そのidを返す
+    fn struct_tag_id(
+        &self,
+        tag: &str,
+        tag_ref: Tag,
+        is_union: bool,
+    ) -> Result<usize, CompileError> {
+        match tag_ref {
+            Tag::Struct(id)
+                if matches!(self.tag_types[id].kind, TypeKind::Union { .. }) == is_union =>
+            {
+                Ok(id)
+            }
+            _ => Err(CompileError::InvalidTypeSpecifier {
+                msg: format!(
+                    "タグ '{}' は {} として宣言されていません",
+                    tag,
+                    if is_union { "union" } else { "struct" }
+                ),
+            }),
+        }
+    }
+
+    // enum_specifier ::= "enum" identifier? enum_type_specifier? "{" enumerator_list ","? "}"
+    //                    | "enum" identifier enum_type_specifier?
+    // enum_type_specifier ::= ":" specifier_qualifier_list
+    // 列挙型は基底型として扱う
+    fn enum_specifier(&mut self) -> Result<Option<TypeKind>, CompileError> {
+        if self.consume_keyword("enum").is_none() {
+            return Ok(None);
+        }
+        let tag = self.consume_ident();
+        // C23で導入された基底型の指定
+        let fixed_ty = if self.consume_punctuator(":").is_some() {
+            let specifiers = self.specifier_qualifier_list()?;
+            let ty = Type::from_tsq(&specifiers, self.unsigned_char)?;
+            if !ty.is_integer() {
+                return Err(CompileError::InvalidTypeSpecifier {
+                    msg: format!("列挙型の基底型は整数型でなければなりません: {}", ty.kind),
+                });
+            }
+            Some(ty)
+        } else {
+            None
+        };
+
+        if self.consume_punctuator("{").is_none() {
+            // 列挙型の参照または前方宣言
+            let tag = tag.ok_or_else(|| CompileError::InvalidTypeSpecifier {
+                msg: "列挙型のタグがありません".to_string(),
+            })?;
+            if let Some(Tag::Struct(_)) = self.find_tag(&tag, false) {
+                return Err(CompileError::InvalidTypeSpecifier {
+                    msg: format!("タグ '{}' は enum として宣言されていません", tag),
+                });
+            }
+            if let Some(Tag::Enum { ty, .. }) = self.find_tag(&tag, false) {
+                if let Some(fixed_ty) = &fixed_ty
+                    && fixed_ty.kind != ty.kind
+                {
+                    return Err(CompileError::InvalidTypeSpecifier {
+                        msg: format!("列挙型 '{}' の基底型が以前の宣言と異なります", tag),
+                    });
+                }
+                return Ok(Some(ty.kind.clone()));
+            }
+            // 未定義のタグは前方参照として登録する
+            // 基底型が指定されていない場合はgccと同様にunsigned intとみなす
+            let ty = fixed_ty.unwrap_or_else(|| Type::from(&TypeKind::UInt, false));
+            let kind = ty.kind.clone();
+            self.current_scope().tags.push((
+                tag,
+                Tag::Enum {
+                    ty,
+                    complete: false,
+                },
+            ));
+            return Ok(Some(kind));
+        }
+
+        let values = self.enumerator_list(fixed_ty.as_ref())?;
+        self.expect_punctuator("}")?;
+        let ty = fixed_ty.unwrap_or_else(|| {
+            // 基底型は全ての列挙子の値を表現できる型とする
+            // 負の値を含まない場合は符号なし整数型を優先する
+            let candidates = if values.iter().any(|val| *val < 0) {
+                [TypeKind::Int, TypeKind::Long]
             } else {
-                "".to_string()
+                [TypeKind::UInt, TypeKind::ULong]
             };
-            self.expect_punctuator("{")?;
-            let members = self.struct_declaration_list()?;
-            self.expect_punctuator("}")?;
-            return Ok(Some(TypeKind::Struct {
-                name: struct_name,
-                members,
-            }));
+            candidates
+                .into_iter()
+                .map(|kind| Type::from(&kind, false))
+                .find(|ty| values.iter().all(|val| ty.can_represent(*val)))
+                .unwrap_or_else(|| Type::from(&TypeKind::Long, false))
+        });
+        let kind = ty.kind.clone();
+        if let Some(tag) = tag {
+            match self.find_tag(&tag, true) {
+                Some(Tag::Enum { complete: true, .. } | Tag::Struct(_)) => {
+                    return Err(CompileError::Redeclaration { name: tag });
+                }
+                Some(Tag::Enum {
+                    ty: old_ty,
+                    complete,
+                }) => {
+                    *old_ty = ty;
+                    *complete = true;
+                }
+                None => self
+                    .current_scope()
+                    .tags
+                    .push((tag, Tag::Enum { ty, complete: true })),
+            }
         }
-        Ok(None)
+        Ok(Some(kind))
+    }
+
+    // enumerator_list ::= enumerator ("," enumerator)* ","?
+    // 列挙子の値の一覧を返す
+    fn enumerator_list(&mut self, fixed_ty: Option<&Type>) -> Result<Vec<i64>, CompileError> {
+        let mut values = Vec::new();
+        loop {
+            let next = values.last().map_or(0, |val: &i64| val.wrapping_add(1));
+            values.push(self.enumerator(next, fixed_ty)?);
+            if self.consume_punctuator(",").is_none() || self.peek_punctuator("}") {
+                return Ok(values);
+            }
+        }
+    }
+
+    // enumerator ::= identifier ("=" const_expr)?
+    // 列挙子は定義された時点から通常の識別子としてスコープに登録する
+    fn enumerator(&mut self, next: i64, fixed_ty: Option<&Type>) -> Result<i64, CompileError> {
+        let name = self
+            .consume_ident()
+            .ok_or_else(|| CompileError::InvalidDeclaration {
+                msg: "列挙子の名前が必要です".to_string(),
+            })?;
+        let val = if self.consume_punctuator("=").is_some() {
+            self.eval_const_expr()?
+        } else {
+            next
+        };
+        // 基底型が指定されている場合、列挙子はその型を持つ
+        // 指定されていない場合はintで表現できればint、できなければlongとする
+        let ty = match fixed_ty {
+            Some(ty) if ty.can_represent(val) => ty.clone(),
+            Some(ty) => {
+                return Err(CompileError::InvalidDeclaration {
+                    msg: format!(
+                        "列挙子 '{}' の値 {} は {} で表現できません",
+                        name, val, ty.kind
+                    ),
+                });
+            }
+            None => [TypeKind::Int, TypeKind::Long]
+                .into_iter()
+                .map(|kind| Type::from(&kind, false))
+                .find(|ty| ty.can_represent(val))
+                .unwrap_or_else(|| Type::from(&TypeKind::Long, false)),
+        };
+        self.push_scoped_ident(&name, ScopedIdent::EnumConst { val, ty })?;
+        Ok(val)
     }
 
     // struct_declaration_list ::= struct_declaration+
-    fn struct_declaration_list(&mut self) -> Result<Vec<Var>, CompileError> {
-        let mut members: Vec<Var> = Vec::new();
+    // メンバーごとに指定された属性も返す
+    fn struct_declaration_list(&mut self) -> Result<Vec<(Var, Attributes)>, CompileError> {
+        let mut members = Vec::new();
         while let Some(member_list) = self.struct_declaration()? {
             members.extend(member_list);
         }
@@ -128,62 +594,262 @@ impl Ast {
     }
 
     // struct_declaration ::= specifier_qualifier_list struct_declarator_list? ";"
-    fn struct_declaration(&mut self) -> Result<Option<Vec<Var>>, CompileError> {
+    //                        | static_assert_declaration
+    fn struct_declaration(&mut self) -> Result<Option<Vec<(Var, Attributes)>>, CompileError> {
+        if self.static_assert_declaration()? {
+            return Ok(Some(Vec::new()));
+        }
         let specifiers = self.specifier_qualifier_list()?;
         if specifiers.is_empty() {
             return Ok(None);
         }
-        let base_ty = Type::from_tsq(&specifiers).unwrap();
-        let members = self.struct_declarator_list(&base_ty)?;
+        let base_ty = Type::from_tsq(&specifiers, self.unsigned_char)?;
+        let spec_attrs = Self::specifier_attributes(&specifiers);
+        let mut members = self.struct_declarator_list(&base_ty)?;
         self.expect_punctuator(";")?;
+        for (member, attrs) in members.iter_mut() {
+            self.check_complete(member)?;
+            if member.ty.is_variably_modified() {
+                return Err(CompileError::InvalidDeclaration {
+                    msg: format!("メンバー '{}' に可変長配列は使えません", member.name),
+                });
+            }
+            attrs.merge(spec_attrs);
+        }
         if members.is_empty() {
-            return Ok(None);
+            // 宣言子のない構造体・共用体は無名メンバーとなり、そのメンバーを直接参照できる
+            if base_ty.is_struct() {
+                return Ok(Some(vec![(Var::new("", base_ty), spec_attrs)]));
+            }
+            return Err(CompileError::InvalidDeclaration {
+                msg: "メンバーの宣言子がありません".to_string(),
+            });
         }
         Ok(Some(members))
     }
 
     // struct_declarator_list ::= struct_declarator ("," struct_declarator)*
-    fn struct_declarator_list(&mut self, base_ty: &Type) -> Result<Vec<Var>, CompileError> {
+    fn struct_declarator_list(
+        &mut self,
+        base_ty: &Type,
+    ) -> Result<Vec<(Var, Attributes)>, CompileError> {
         let mut members = Vec::new();
         if let Some(member) = self.struct_declarator(base_ty)? {
-            members.push(*member);
+            members.push(member);
         }
         while self.consume_punctuator(",").is_some() {
             if let Some(member) = self.struct_declarator(base_ty)? {
-                members.push(*member);
+                members.push(member);
             }
         }
         Ok(members)
     }
 
-    // struct_declarator ::= declarator
-    fn struct_declarator(&mut self, base_ty: &Type) -> Result<Option<Box<Var>>, CompileError> {
-        if let Ok(var) = self.declarator(base_ty.clone()) {
-            return Ok(Some(var));
+    // struct_declarator ::= declarator attribute_specifier*
+    //                       | declarator? attribute_specifier* ":" const_expr attribute_specifier*
+    fn struct_declarator(
+        &mut self,
+        base_ty: &Type,
+    ) -> Result<Option<(Var, Attributes)>, CompileError> {
+        let mut var = if self.peek_punctuator(":") {
+            // 名前のないビットフィールド
+            Box::new(Var::new("", base_ty.clone()))
+        } else if let Ok(var) = self.declarator(base_ty.clone()) {
+            var
+        } else {
+            return Ok(None);
+        };
+        let mut attrs = self.attribute_specifiers()?;
+        if self.consume_punctuator(":").is_some() {
+            let width = self.eval_const_expr()?;
+            if !var.ty.is_integer() {
+                return Err(CompileError::InvalidDeclaration {
+                    msg: format!(
+                        "ビットフィールド '{}' の型 {} は整数型ではありません",
+                        var.name, var.ty.kind
+                    ),
+                });
+            }
+            // _Boolのビットフィールドの幅は1以下
+            let max_width = if var.ty.is_bool() {
+                1
+            } else {
+                var.ty.size_of() * 8
+            };
+            if width < 0 || width as usize > max_width {
+                return Err(CompileError::InvalidDeclaration {
+                    msg: format!("ビットフィールド '{}' の幅 {} は無効です", var.name, width),
+                });
+            }
+            if width == 0 && !var.name.is_empty() {
+                return Err(CompileError::InvalidDeclaration {
+                    msg: format!(
+                        "名前のあるビットフィールド '{}' の幅は0にできません",
+                        var.name
+                    ),
+                });
+            }
+            var.bit_field = Some(BitField {
+                width: width as usize,
+                bit_offset: 0, // 構造体の配置時に決まる
+                packed: false,
+            });
         }
-        Ok(None)
+        attrs.merge(self.attribute_specifiers()?);
+        Ok(Some((*var, attrs)))
     }
 
     // specifier_qualifier_list ::= type_specifier_qualifier+
     fn specifier_qualifier_list(&mut self) -> Result<Vec<TypeSpecifierQualifier>, CompileError> {
         let mut specifiers = Vec::new();
-        while let Some(specifier) = self.type_specifier_qualifier()? {
+        let mut has_type = false;
+        while let Some(specifier) = self.type_specifier_qualifier(has_type)? {
+            has_type |= matches!(specifier, TypeSpecifierQualifier::TypeSpecifier(_));
             specifiers.push(specifier);
         }
         Ok(specifiers)
     }
 
-    // type_specifier_qualifier ::= type_specifier | type_qualifier
-    fn type_specifier_qualifier(&mut self) -> Result<Option<TypeSpecifierQualifier>, CompileError> {
-        if let Some(specifier) = self.type_specifier()? {
+    // type_specifier_qualifier ::= type_specifier | type_qualifier | alignment_specifier | attribute_specifier
+    fn type_specifier_qualifier(
+        &mut self,
+        has_type: bool,
+    ) -> Result<Option<TypeSpecifierQualifier>, CompileError> {
+        if let Some(specifier) = self.type_specifier(has_type)? {
             return Ok(Some(TypeSpecifierQualifier::TypeSpecifier(specifier)));
         }
         if let Some(qualifier) = self.type_qualifier() {
             return Ok(Some(TypeSpecifierQualifier::TypeQualifier(qualifier)));
         }
+        if let Some(aligned) = self.alignment_specifier()? {
+            return Ok(Some(TypeSpecifierQualifier::Attributes(Attributes {
+                aligned: Some(aligned),
+                packed: false,
+            })));
+        }
+        if self.peek_keyword("__attribute__") {
+            let attrs = self.attribute_specifiers()?;
+            return Ok(Some(TypeSpecifierQualifier::Attributes(attrs)));
+        }
         Ok(None)
     }
 
+    // 宣言指定子の並びに含まれる属性をまとめる
+    pub(super) fn specifier_attributes<'a, I>(specifiers: I) -> Attributes
+    where
+        I: IntoIterator<Item = &'a TypeSpecifierQualifier>,
+    {
+        let mut attrs = Attributes::default();
+        for specifier in specifiers {
+            if let TypeSpecifierQualifier::Attributes(a) = specifier {
+                attrs.merge(*a);
+            }
+        }
+        attrs
+    }
+
+    // alignment_specifier ::= "_Alignas" "(" (type_name | const_expr) ")"
+    // 指定されたアラインメントを返す（0の場合は指定がないものとする）
+    fn alignment_specifier(&mut self) -> Result<Option<usize>, CompileError> {
+        if self.consume_keyword("_Alignas").is_none() {
+            return Ok(None);
+        }
+        self.expect_punctuator("(")?;
+        let align = if self.is_type_name_at(self.token_pos) {
+            let ty = self.type_name()?;
+            self.complete_type(&ty).align_of()
+        } else {
+            let align = self.eval_const_expr()?;
+            Self::check_alignment(align)?
+        };
+        self.expect_punctuator(")")?;
+        Ok(Some(align).filter(|align| *align > 0))
+    }
+
+    // アラインメントの値は0か2の冪でなければならない
+    fn check_alignment(align: i64) -> Result<usize, CompileError> {
+        if align == 0 || align > 0 && (align as u64).is_power_of_two() {
+            Ok(align as usize)
+        } else {
+            Err(CompileError::InvalidDeclaration {
+                msg: format!("無効なアラインメントです: {}", align),
+            })
+        }
+    }
+
+    // attribute_specifier ::= "__attribute__" "(" "(" attribute ("," attribute)* ")" ")"
+    // attribute ::= (identifier | keyword) ("(" argument_list ")")?
+    // aligned と packed 以外の属性は無視する
+    pub(super) fn attribute_specifiers(&mut self) -> Result<Attributes, CompileError> {
+        let mut attrs = Attributes::default();
+        while self.consume_keyword("__attribute__").is_some() {
+            self.expect_punctuator("(")?;
+            self.expect_punctuator("(")?;
+            while !self.peek_punctuator(")") {
+                let name = match self.get_token().map(|t| t.kind.clone()) {
+                    Some(TokenKind::Identifier(name) | TokenKind::Keyword(name)) => name,
+                    _ => {
+                        return Err(CompileError::InvalidDeclaration {
+                            msg: "属性名が必要です".to_string(),
+                        });
+                    }
+                };
+                self.advance_token();
+                // __packed__ のような下線で囲まれた綴りも同じ属性とする
+                let name = name
+                    .strip_prefix("__")
+                    .and_then(|n| n.strip_suffix("__"))
+                    .unwrap_or(&name);
+                match name {
+                    "packed" => attrs.packed = true,
+                    "aligned" => {
+                        // 引数がない場合は対象環境の最大のアラインメントとする
+                        let align = if self.consume_punctuator("(").is_some() {
+                            let align = self.eval_const_expr()?;
+                            self.expect_punctuator(")")?;
+                            Self::check_alignment(align)?
+                        } else {
+                            16
+                        };
+                        attrs.merge(Attributes {
+                            aligned: Some(align).filter(|align| *align > 0),
+                            packed: false,
+                        });
+                    }
+                    _ => self.skip_parenthesized()?,
+                }
+                if self.consume_punctuator(",").is_none() {
+                    break;
+                }
+            }
+            self.expect_punctuator(")")?;
+            self.expect_punctuator(")")?;
+        }
+        Ok(attrs)
+    }
+
+    // 括弧で囲まれたトークン列があれば読み飛ばす
+    fn skip_parenthesized(&mut self) -> Result<(), CompileError> {
+        if !self.peek_punctuator("(") {
+            return Ok(());
+        }
+        let mut depth = 0;
+        loop {
+            if self.consume_punctuator("(").is_some() {
+                depth += 1;
+            } else if self.consume_punctuator(")").is_some() {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(());
+                }
+            } else if self.at_eof() {
+                return Err(CompileError::UnexpectedEof);
+            } else {
+                self.advance_token();
+            }
+        }
+    }
+
     // type_qualifier ::= "const" | "volatile" | "restrict"
     fn type_qualifier(&mut self) -> Option<TypeQualifierKind> {
         TypeQualifierKind::all()
@@ -207,13 +873,14 @@ impl Ast {
         qualifiers
     }
 
-    // pointer ::= "*" type_qualifier_list* pointer?
-    #[allow(clippy::never_loop)]
+    // pointer ::= "*" type_qualifier_list pointer?
     fn pointer(&mut self, base_ty: Box<Type>) -> Box<Type> {
-        while self.consume_punctuator("*").is_some() {
-            return self.pointer(Box::new(Type::from(&TypeKind::Ptr { to: base_ty }, false)));
+        if self.consume_punctuator("*").is_some() {
+            // "*" の後の型修飾子はそのポインタ自体を修飾する
+            let mut ty = Type::from(&TypeKind::Ptr { to: base_ty }, false);
+            ty.qualify(&self.type_qualifier_list());
+            return self.pointer(Box::new(ty));
         }
-        self.type_qualifier_list(); // 現状は型修飾子を無視
         base_ty
     }
 
@@ -225,13 +892,21 @@ impl Ast {
 
     // direct_declarator ::= "(" declarator ")"
     //                       | identifier
-    //                       | direct_declarator "[" type_qualifier_list? assignment_expression? "]"
+    //                       | direct_declarator "[" "static"? type_qualifier_list? "static"? (assignment_expression | "*")? "]"
     //                       | direct_declarator "(" parameter_type_list ")"
     fn direct_declarator(&mut self, base_ty: Box<Type>) -> Result<Box<Var>, CompileError> {
         let name = if self.consume_punctuator("(").is_some() {
-            let inner_var = self.declarator(*base_ty.clone())?;
+            // 括弧内の宣言子は、括弧の後に続く配列や関数の型を基底型とする
+            // 例: int (*fp)(int) の fp は int (int) へのポインタ
+            let start = self.token_pos;
+            self.declarator(*base_ty.clone())?; // 括弧内を読み飛ばす
             self.expect_punctuator(")")?;
-            inner_var.name
+            let outer_ty = self.parse_postfix_declarators(base_ty)?;
+            let end = self.token_pos;
+            self.token_pos = start;
+            let inner_var = self.declarator(*outer_ty)?;
+            self.token_pos = end;
+            return Ok(inner_var);
         } else if let Some(name) = self.consume_ident() {
             name
         } else {
@@ -246,25 +921,19 @@ impl Ast {
 
     // 右結合で解析
     fn parse_postfix_declarators(&mut self, base_ty: Box<Type>) -> Result<Box<Type>, CompileError> {
-        // "[" type_qualifier_list? assignment_expression? "]"
+        // "[" "static"? type_qualifier_list? "static"? (assignment_expression | "*")? "]"
         if self.consume_punctuator("[").is_some() {
-            self.type_qualifier_list(); // 現状は型修飾子を無視
-            let array_size = self.expect_number()? as usize; // TODO: assign_exprに置き換え
-            self.expect_punctuator("]")?;
+            let (qualifiers, len) = self.array_declarator_suffix()?;
             let inner_ty = self.parse_postfix_declarators(base_ty)?;
-            Ok(Box::new(Type::from(
-                &TypeKind::Array {
-                    base: inner_ty,
-                    size: array_size,
-                },
-                false,
-            )))
+            let mut ty = self.array_of(inner_ty, len)?;
+            ty.qualify_outermost(&qualifiers);
+            Ok(Box::new(ty))
         }
         // "(" parameter_type_list ")"
         else if self.consume_punctuator("(").is_some() {
-            let params = if self.consume_punctuator(")").is_some() {
+            let (params, variadic) = if self.consume_punctuator(")").is_some() {
                 // パラメータが0個の場合
-                Vec::new()
+                (Vec::new(), false)
             } else {
                 // パラメータが1個以上の場合
                 let params = self.parameter_type_list()?;
@@ -276,6 +945,7 @@ impl Ast {
                 &TypeKind::Func {
                     return_ty: inner_ty,
                     params,
+                    variadic,
                 },
                 false,
             )))
@@ -284,37 +954,183 @@ impl Ast {
         }
     }
 
-    // parameter_type_list ::= parameter_list
-    fn parameter_type_list(&mut self) -> Result<Vec<Var>, CompileError> {
-        self.parameter_list()
+    // 配列宣言子の "[" に続く部分を解析し、型修飾子と要素数の式を返す
+    // 型修飾子とstaticは仮引数でのみ意味を持ち、staticは要素数の下限を示すだけなので無視する
+    #[allow(clippy::type_complexity)]
+    fn array_declarator_suffix(
+        &mut self,
+    ) -> Result<(Vec<TypeQualifierKind>, Option<Box<Node>>), CompileError> {
+        let mut is_static = self.consume_keyword("static").is_some();
+        let qualifiers = self.type_qualifier_list();
+        is_static |= self.consume_keyword("static").is_some();
+        // [*] は要素数を指定しない可変長配列で、仮引数の調整によりポインタになる
+        let len = if self.peek_punctuator("*")
+            && matches!(
+                self.tokens.get(self.token_pos + 1).map(|t| &t.kind),
+                Some(TokenKind::Punctuator(s)) if s == "]"
+            ) {
+            self.consume_punctuator("*");
+            None
+        } else {
+            self.array_length()?
+        };
+        if is_static && len.is_none() {
+            return Err(CompileError::InvalidDeclaration {
+                msg: "staticを指定した配列宣言子には要素数が必要です".to_string(),
+            });
+        }
+        self.expect_punctuator("]")?;
+        Ok((qualifiers, len))
+    }
+
+    // 配列の要素数の式を解析する
+    // 要素数が省略された場合はNoneを返す
+    fn array_length(&mut self) -> Result<Option<Box<Node>>, CompileError> {
+        if self.peek_punctuator("]") {
+            return Ok(None);
+        }
+        let mut len = self
+            .assign_expr()?
+            .ok_or(CompileError::InvalidDeclaration {
+                msg: "配列の要素数が必要です".to_string(),
+            })?;
+        len.assign_types()?;
+        Ok(Some(len))
+    }
+
+    // 要素数の式から配列型を作成する
+    // 要素数が省略されていれば不完全型の配列とし、
+    // 整数定数式でないか、要素型が可変長配列であれば可変長配列とする
+    fn array_of(&mut self, base: Box<Type>, len: Option<Box<Node>>) -> Result<Type, CompileError> {
+        if matches!(base.kind, TypeKind::Array { size: None, .. }) {
+            return Err(CompileError::InvalidDeclaration {
+                msg: format!("配列の要素型 {} の要素数は省略できません", base.kind),
+            });
+        }
+        let Some(len) = len else {
+            return Ok(Type::from(&TypeKind::Array { base, size: None }, false));
+        };
+        if let Ok(size) = len.eval_const()
+            && !base.is_variably_modified()
+        {
+            return Ok(Type::from(
+                &TypeKind::Array {
+                    base,
+                    size: Some(size as usize),
+                },
+                false,
+            ));
+        }
+        if self.current_func.is_none() {
+            return Err(CompileError::InvalidDeclaration {
+                msg: "ファイルスコープの配列の要素数は整数定数式でなければなりません".to_string(),
+            });
+        }
+        if !len.ty.as_ref().is_some_and(|ty| ty.is_integer()) {
+            return Err(CompileError::InvalidDeclaration {
+                msg: format!("配列の要素数は整数型でなければなりません: {:?}", len.ty),
+            });
+        }
+        // サイズの計算はsize_tで行う
+        let len = Self::cast(Some(len), Box::new(Type::from(&TypeKind::ULong, false)))?;
+        Ok(Type::from(
+            &TypeKind::Vla {
+                base,
+                len,
+                size_offset: None,
+            },
+            false,
+        ))
+    }
+
+    // parameter_type_list ::= parameter_list ("," "...")?
+    //                         | "..."
+    // (void) は仮引数がないことを表す
+    // 仮引数の並びと、可変長引数を取るかどうかを返す
+    fn parameter_type_list(&mut self) -> Result<(Vec<Var>, bool), CompileError> {
+        // C23では名前付きの仮引数のない可変長引数の関数も宣言できる
+        if self.consume_punctuator("...").is_some() {
+            return Ok((Vec::new(), true));
+        }
+        let params = self.parameter_list()?;
+        let variadic = self.consume_punctuator("...").is_some();
+        if let [param] = params.as_slice()
+            && param.name.is_empty()
+            && param.ty.kind == TypeKind::Void
+            && !variadic
+        {
+            return Ok((Vec::new(), false));
+        }
+        Ok((params, variadic))
     }
 
     // parameter_list ::= parameter_declaration ("," parameter_declaration)*
+    // 末尾の "," "..." は "," まで読み進め、"..." はparameter_type_listで読む
+    // 仮引数の並びは独自のスコープを持ち、可変長配列の要素数の式から前の仮引数を参照できる
+    // 関数定義と同じ順に仮引数を一時的な関数のローカル変数として登録するため、オフセットは関数定義と一致する
     fn parameter_list(&mut self) -> Result<Vec<Var>, CompileError> {
+        let outer_func = self.current_func.replace(Box::new(Function::new("")));
+        self.enter_scope();
+        let params = self.parameter_declarations();
+        self.leave_scope();
+        self.current_func = outer_func;
+        params
+    }
+
+    fn parameter_declarations(&mut self) -> Result<Vec<Var>, CompileError> {
         let mut params = Vec::new();
-        let param = self.parameter_declaration()?;
-        params.push(*param);
-        while self.consume_punctuator(",").is_some() {
+        loop {
             let param = self.parameter_declaration()?;
+            self.push_var_ident(&param.name)?;
+            self.get_current_func()?.gen_lvar(*param.clone())?;
             params.push(*param);
+            if self.consume_punctuator(",").is_none() || self.peek_punctuator("...") {
+                return Ok(params);
+            }
         }
-        Ok(params)
     }
 
     // parameter_declaration ::= declaration_specifiers declarator
+    //                           | declaration_specifiers abstract_declarator?
+    // 配列型と関数型の仮引数はポインタ型に調整する
     fn parameter_declaration(&mut self) -> Result<Box<Var>, CompileError> {
         let specifiers = self.declaration_specifiers()?;
         if !specifiers.is_empty() {
-            let base_kind = Type::from_ds(&specifiers).unwrap();
-            if let Ok(var) = self.declarator(base_kind) {
+            let base_kind = Type::from_ds(&specifiers, self.unsigned_char)?;
+            let token_pos = self.token_pos;
+            if let Ok(mut var) = self.declarator(base_kind.clone()) {
+                var.ty = Box::new(var.ty.param_adjusted());
                 return Ok(var);
             }
+            // 名前のない仮引数
+            self.token_pos = token_pos;
+            if let Ok(ty) = self.abstract_declarator(&base_kind) {
+                return Ok(Box::new(Var::new("", ty.param_adjusted())));
+            }
         }
         Err(CompileError::InvalidDeclaration {
             msg: "無効なパラメータ宣言です".to_string(),
         })
     }
 
+    // pos番目のトークンが型名の始まりかどうか
+    // typedef名は変数などに隠されていない場合のみ型名とみなす
+    pub(super) fn is_type_name_at(&self, pos: usize) -> bool {
+        match self.tokens.get(pos).map(|t| &t.kind) {
+            Some(TokenKind::Keyword(word)) => {
+                ["struct", "union", "enum"].contains(&word.as_str())
+                    || TypeSpecifierKind::all()
+                        .iter()
+                        .any(|specifier| specifier.to_string() == *word)
+                    || TypeQualifierKind::all()
+                        .iter()
+                        .any(|qualifier| qualifier.to_string() == *word)
+            }
+            Some(TokenKind::Identifier(name)) => self.find_typedef(name).is_some(),
+            _ => false,
+        }
+    }
+
     // type_name ::= specifier_qualifier_list abstract_declarator?
     pub(super) fn type_name(&mut self) -> Result<Box<Type>, CompileError> {
         let specifiers = self.specifier_qualifier_list()?;
@@ -323,7 +1139,7 @@ impl Ast {
                 msg: "無効な型名です".to_string(),
             });
         }
-        let base_ty = Type::from_tsq(&specifiers).unwrap();
+        let base_ty = Type::from_tsq(&specifiers, self.unsigned_char)?;
         if let Ok(abstract_ty) = self.abstract_declarator(&base_ty) {
             return Ok(abstract_ty);
         }
@@ -344,15 +1160,27 @@ impl Ast {
         &mut self,
         base_ty: Box<Type>,
     ) -> Result<Box<Type>, CompileError> {
-        let mut current_ty = if self.consume_punctuator("(").is_some() {
-            let inner_ty = self.abstract_declarator(&base_ty)?;
+        // "(" の後に "*" か "(" が続く場合は括弧で囲まれた抽象宣言子、それ以外は仮引数の並び
+        let nested = self.peek_punctuator("(")
+            && matches!(
+                self.tokens.get(self.token_pos + 1).map(|t| &t.kind),
+                Some(TokenKind::Punctuator(s)) if s == "*" || s == "("
+            );
+        if nested {
+            // 括弧内の抽象宣言子は、括弧の後に続く配列や関数の型を基底型とする
+            // 例: int (*)[3] は int [3] へのポインタ
+            self.consume_punctuator("(");
+            let start = self.token_pos;
+            self.abstract_declarator(&base_ty)?; // 括弧内を読み飛ばす
             self.expect_punctuator(")")?;
-            inner_ty
-        } else {
-            base_ty
-        };
-        current_ty = self.parse_abstract_postfix_declarators(current_ty)?;
-        Ok(current_ty)
+            let outer_ty = self.parse_abstract_postfix_declarators(base_ty)?;
+            let end = self.token_pos;
+            self.token_pos = start;
+            let inner_ty = self.abstract_declarator(&outer_ty)?;
+            self.token_pos = end;
+            return Ok(inner_ty);
+        }
+        self.parse_abstract_postfix_declarators(base_ty)
     }
 
     // 右結合で解析
@@ -360,25 +1188,19 @@ impl Ast {
         &mut self,
         base_ty: Box<Type>,
     ) -> Result<Box<Type>, CompileError> {
-        // "[" type_qualifier_list? assignment_expression? "]"
+        // "[" "static"? type_qualifier_list? "static"? (assignment_expression | "*")? "]"
         if self.consume_punctuator("[").is_some() {
-            self.type_qualifier_list(); // 現状は型修飾子を無視
-            let array_size = self.expect_number()? as usize; // TODO: assign_exprに置き換え
-            self.expect_punctuator("]")?;
+            let (qualifiers, len) = self.array_declarator_suffix()?;
             let inner_ty = self.parse_abstract_postfix_declarators(base_ty)?;
-            Ok(Box::new(Type::from(
-                &TypeKind::Array {
-                    base: inner_ty,
-                    size: array_size,
-                },
-                false,
-            )))
+            let mut ty = self.array_of(inner_ty, len)?;
+            ty.qualify_outermost(&qualifiers);
+            Ok(Box::new(ty))
         }
         // "(" parameter_type_list ")"
         else if self.consume_punctuator("(").is_some() {
-            let params = if self.consume_punctuator(")").is_some() {
+            let (params, variadic) = if self.consume_punctuator(")").is_some() {
                 // パラメータが0個の場合
-                Vec::new()
+                (Vec::new(), false)
             } else {
                 // パラメータが1個以上の場合
                 let params = self.parameter_type_list()?;
@@ -390,6 +1212,7 @@ impl Ast {
                 &TypeKind::Func {
                     return_ty: inner_ty,
                     params,
+                    variadic,
                 },
                 false,
             )))
@@ -398,25 +1221,291 @@ impl Ast {
         }
     }
 
-    // initializer ::= assignment_expr
-    //                 | "{" initializer_list "}" // 未実装
-    //                 | "{" initializer_list "," "}" // 未実装
-    fn initializer(&mut self) -> Result<Vec<Option<Box<Node>>>, CompileError> {
+    // initializer ::= assign_expr
+    //                 | "{" initializer_list ","? "}"
+    // tyはオブジェクト先頭からoffsetの位置にある初期化対象の型
+    // 初期化した要素数を返し、要素数が省略された配列ではそれを配列の要素数とする
+    fn initializer(
+        &mut self,
+        ty: &Type,
+        offset: usize,
+        init: &mut Vec<InitElem>,
+    ) -> Result<usize, CompileError> {
+        // 文字型の配列は文字列リテラルで初期化でき、それを波括弧で囲んでもよい
+        if ty.is_char_array() && self.is_string_at(self.token_pos) {
+            return self.string_initializer(ty, offset, init);
+        }
         if self.consume_punctuator("{").is_some() {
-            let init_list = self.initializer_list()?;
+            let count = if ty.is_char_array() && self.is_string_at(self.token_pos) {
+                self.string_initializer(ty, offset, init)?
+            } else if ty.is_array() || ty.is_struct() {
+                self.initializer_list(ty, offset, init, true)?
+            } else {
+                // スカラーの初期化子も波括弧で囲むことができる
+                self.initializer(ty, offset, init)?
+            };
+            self.consume_punctuator(",");
             self.expect_punctuator("}")?;
-            return Ok(init_list);
+            return Ok(count);
+        }
+        if ty.is_array() {
+            return Err(CompileError::InvalidInitializer {
+                msg: "配列は波括弧で囲まれた初期化子で初期化する必要があります".to_string(),
+            });
         }
-        Ok(vec![self.assign_expr()?])
+        let span = self.get_token().map(|t| t.span).unwrap_or_default();
+        let mut expr = self
+            .assign_expr()?
+            .ok_or_else(|| CompileError::InvalidInitializer {
+                msg: "初期化式が必要です".to_string(),
+            })?;
+        expr.assign_types()?;
+        Self::check_pointer_qualifiers(ty, &expr, "初期化");
+        // ファイルスコープの浮動小数点数はビット表現を出力するため、算術定数式でなければならない
+        if self.current_func.is_none() && ty.is_floating_point() {
+            let is_const = if ty.kind == TypeKind::LongDouble {
+                expr.eval_long_double().is_ok()
+            } else {
+                expr.eval_float().is_ok()
+            };
+            if !is_const {
+                return Err(CompileError::NonConstantInitializer {
+                    msg: "浮動小数点数の初期化式は算術定数式でなければなりません".to_string(),
+                    span,
+                });
+            }
+        }
+        init.push(InitElem {
+            offset,
+            bit_field: None,
+            ty: ty.clone(),
+            expr,
+        });
+        Ok(1)
     }
 
-    // initializer_list ::= initializer ("," initializer)*
-    fn initializer_list(&mut self) -> Result<Vec<Option<Box<Node>>>, CompileError> {
-        let mut init_list = Vec::new();
-        init_list.extend(self.initializer()?);
-        while self.consume_punctuator(",").is_some() {
-            init_list.extend(self.initializer()?);
+    // 文字型の配列を文字列リテラルで初期化する
+    // 連続する文字列リテラルは連結し、終端のヌル文字は配列に収まる場合のみ格納する
+    fn string_initializer(
+        &mut self,
+        ty: &Type,
+        offset: usize,
+        init: &mut Vec<InitElem>,
+    ) -> Result<usize, CompileError> {
+        let mut bytes = Vec::new();
+        while let Some(s) = self.consume_string_bytes() {
+            bytes.extend(s);
+        }
+        bytes.push(0);
+        let len = match &ty.kind {
+            TypeKind::Array {
+                size: Some(size), ..
+            } => *size,
+            _ => bytes.len(),
+        };
+        if bytes.len() - 1 > len {
+            return Err(CompileError::InvalidInitializer {
+                msg: format!("文字列リテラルが {} に収まりません", ty.kind),
+            });
+        }
+        let base = ty.base_type();
+        for (i, &b) in bytes.iter().take(len).enumerate() {
+            let val = if base.is_unsigned() {
+                b as i64
+            } else {
+                b as i8 as i64
+            };
+            init.push(InitElem {
+                offset: offset + i,
+                bit_field: None,
+                ty: base.clone(),
+                expr: Box::new(Node::new_num(val)),
+            });
         }
-        Ok(init_list)
+        Ok(bytes.len())
+    }
+
+    // pos番目のトークンが文字列リテラルかどうか
+    fn is_string_at(&self, pos: usize) -> bool {
+        matches!(
+            self.tokens.get(pos).map(|t| &t.kind),
+            Some(TokenKind::String(..))
+        )
+    }
+
+    // initializer_list ::= designation? initializer ("," designation? initializer)*
+    // bracedが偽の場合は波括弧を省略した部分オブジェクトの初期化子で、
+    // 要素がすべて埋まった時点で残りを外側の初期化子に任せる
+    // 初期化した要素の最大の番号に1を足した値を返す
+    fn initializer_list(
+        &mut self,
+        ty: &Type,
+        offset: usize,
+        init: &mut Vec<InitElem>,
+        braced: bool,
+    ) -> Result<usize, CompileError> {
+        let mut index = 0;
+        let mut count = 0;
+        loop {
+            while ty.is_unnamed_bit_field(index) {
+                index += 1;
+            }
+            let (elem_ty, elem_offset, bit_field) = if braced
+                && let Some((designated, elem_ty, elem_offset, bit_field)) = self.designation(ty)?
+            {
+                index = designated;
+                (elem_ty, elem_offset, bit_field)
+            } else if let Some((elem_ty, elem_offset, bit_field)) = ty.element(index) {
+                (elem_ty.clone(), elem_offset, bit_field)
+            } else if braced {
+                return Err(CompileError::InvalidInitializer {
+                    msg: format!("{} の初期化子の要素が多すぎます", ty.kind),
+                });
+            } else {
+                return Ok(count);
+            };
+            let start = init.len();
+            let span = self.get_token().map(|t| t.span).unwrap_or_default();
+            self.initializer_elem(&elem_ty, offset + elem_offset, init)?;
+            // ビットフィールドはスカラーなので、初期化子の要素は1つになる
+            for elem in init[start..].iter_mut() {
+                elem.bit_field = bit_field;
+                // ファイルスコープのビットフィールドは値をバイト列に詰めて出力するため、定数でなければならない
+                if self.current_func.is_none()
+                    && bit_field.is_some()
+                    && elem.expr.eval_const().is_err()
+                {
+                    return Err(CompileError::NonConstantInitializer {
+                        msg: "ビットフィールドの初期化式は整数定数式でなければなりません"
+                            .to_string(),
+                        span,
+                    });
+                }
+            }
+            index += 1;
+            count = count.max(index);
+            while ty.is_unnamed_bit_field(index) {
+                index += 1;
+            }
+            // 共用体は1つのメンバーのみを初期化する
+            if matches!(ty.kind, TypeKind::Union { .. }) {
+                index = ty.element_count();
+            }
+
+            if self.peek_punctuator("}")
+                || !braced && (index >= ty.element_count() || self.peek_designation_after_comma())
+            {
+                return Ok(count);
+            }
+            self.expect_punctuator(",")?;
+            if self.peek_punctuator("}") {
+                return Ok(count);
+            }
+        }
+    }
+
+    // 初期化子の並びの要素を1つ解析する
+    // 波括弧のない構造体や配列は、式が同じ型の値でなければ波括弧の省略とみなす
+    fn initializer_elem(
+        &mut self,
+        ty: &Type,
+        offset: usize,
+        init: &mut Vec<InitElem>,
+    ) -> Result<usize, CompileError> {
+        if self.peek_punctuator("{")
+            || !(ty.is_array() || ty.is_struct())
+            || ty.is_char_array() && self.is_string_at(self.token_pos)
+        {
+            return self.initializer(ty, offset, init);
+        }
+        if ty.is_struct() {
+            let token_pos = self.token_pos;
+            let string_count = self.string_literals.len();
+            if let Some(mut expr) = self.assign_expr()? {
+                expr.assign_types()?;
+                if expr
+                    .ty
+                    .as_ref()
+                    .is_some_and(|expr_ty| expr_ty.kind == ty.kind)
+                {
+                    init.push(InitElem {
+                        offset,
+                        bit_field: None,
+                        ty: ty.clone(),
+                        expr,
+                    });
+                    return Ok(1);
+                }
+            }
+            // 構造体の値でなかった場合、トークン位置を元に戻す
+            self.token_pos = token_pos;
+            self.string_literals.truncate(string_count);
+        }
+        self.initializer_list(ty, offset, init, false)
+    }
+
+    // designation ::= designator+ "="
+    // 最初の指示子が指す要素の番号と、指示子全体が指す部分オブジェクトの型とオフセット、
+    // ビットフィールドの情報を返す
+    #[allow(clippy::type_complexity)]
+    fn designation(
+        &mut self,
+        ty: &Type,
+    ) -> Result<Option<(usize, Type, usize, Option<BitField>)>, CompileError> {
+        let Some(index) = self.designator(ty)? else {
+            return Ok(None);
+        };
+        let (first_ty, first_offset, first_bit_field) = ty.element(index).unwrap();
+        let (mut elem_ty, mut offset, mut bit_field) =
+            (first_ty.clone(), first_offset, first_bit_field);
+        while let Some(sub_index) = self.designator(&elem_ty)? {
+            let (sub_ty, sub_offset, sub_bit_field) = elem_ty.element(sub_index).unwrap();
+            (elem_ty, offset, bit_field) = (sub_ty.clone(), offset + sub_offset, sub_bit_field);
+        }
+        self.expect_punctuator("=")?;
+        Ok(Some((index, elem_ty, offset, bit_field)))
+    }
+
+    // designator ::= "[" const_expr "]" | "." identifier
+    // 指示子が指す要素の番号を返す
+    fn designator(&mut self, ty: &Type) -> Result<Option<usize>, CompileError> {
+        if self.consume_punctuator("[").is_some() {
+            let index = self.eval_const_expr()?;
+            self.expect_punctuator("]")?;
+            if !ty.is_array() || index < 0 || index as usize >= ty.element_count() {
+                return Err(CompileError::InvalidInitializer {
+                    msg: format!("指示子 [{}] は {} の範囲外です", index, ty.kind),
+                });
+            }
+            return Ok(Some(index as usize));
+        }
+        if self.consume_punctuator(".").is_some() {
+            let name = self
+                .consume_ident()
+                .ok_or_else(|| CompileError::InvalidInitializer {
+                    msg: "指示子のメンバー名が必要です".to_string(),
+                })?;
+            let index = match &ty.kind {
+                TypeKind::Struct { members, .. } | TypeKind::Union { members, .. } => {
+                    members.iter().position(|member| member.name == name)
+                }
+                _ => None,
+            };
+            return index
+                .map(Some)
+                .ok_or_else(|| CompileError::InvalidInitializer {
+                    msg: format!("{} にメンバー '{}' はありません", ty.kind, name),
+                });
+        }
+        Ok(None)
+    }
+
+    // 次のトークンが "," で、その後に指示子が続くかどうか
+    fn peek_designation_after_comma(&mut self) -> bool {
+        self.peek_punctuator(",")
+            && matches!(
+                self.tokens.get(self.token_pos + 1).map(|t| &t.kind),
+                Some(TokenKind::Punctuator(s)) if s == "[" || s == "."
+            )
     }
 }
@@ -1,16 +1,27 @@
 use core::str::FromStr;
 
-use crate::ast::Ast;
-use crate::errors::CompileError;
+use crate::ast::{Ast, ScopedIdent, VA_LIST_TAG_ID, Var};
+use crate::errors::{CompileError, warn};
 use crate::node::{Node, NodeKind};
+use crate::types::{Type, TypeKind};
 
 impl Ast {
     // const_expr ::= cond_expr
-    #[allow(dead_code)]
     pub(super) fn const_expr(&mut self) -> Result<Option<Box<Node>>, CompileError> {
         self.cond_expr()
     }
 
+    // 整数定数式を解析して評価する
+    pub(super) fn eval_const_expr(&mut self) -> Result<i64, CompileError> {
+        let mut node = self
+            .const_expr()?
+            .ok_or_else(|| CompileError::InvalidExpression {
+                msg: "定数式が必要です".to_string(),
+            })?;
+        node.assign_types()?;
+        node.eval_const()
+    }
+
     // expr ::= assign_expr
     pub(super) fn expr(&mut self) -> Result<Option<Box<Node>>, CompileError> {
         self.assign_expr()
@@ -27,13 +38,64 @@ impl Ast {
             if self.consume_punctuator(op).is_some()
                 && let Ok(kind) = NodeKind::from_str(op)
             {
-                node = Some(Box::new(Node::new(kind, node, self.assign_expr()?)));
+                Self::check_modifiable(&mut node)?;
+                let mut rhs = self.assign_expr()?;
+                if kind == NodeKind::Assign
+                    && let (Some(lhs), Some(rhs)) = (&node, &mut rhs)
+                {
+                    rhs.assign_types()?;
+                    if let Some(lhs_ty) = &lhs.ty {
+                        Self::check_pointer_qualifiers(lhs_ty, rhs, "代入");
+                    }
+                }
+                node = Some(Box::new(Node::new(kind, node, rhs)));
                 break;
             }
         }
         Ok(node)
     }
 
+    // 代入やインクリメント・デクリメントの対象が変更可能な左辺値であることを確認する
+    // const修飾されたオブジェクトや、constなメンバーを持つ構造体・共用体は変更できない
+    fn check_modifiable(node: &mut Option<Box<Node>>) -> Result<(), CompileError> {
+        if let Some(n) = node {
+            n.assign_types()?;
+            if let Some(ty) = &n.ty
+                && ty.contains_const()
+            {
+                return Err(CompileError::InvalidExpression {
+                    msg: format!("const修飾されたオブジェクトは変更できません: {:?}", ty),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    // ポインタへの変換で、指す型の修飾子が失われる場合は警告する
+    pub(super) fn check_pointer_qualifiers(to: &Type, from: &Node, context: &str) {
+        let (TypeKind::Ptr { to: to_base }, Some(from_ty)) = (&to.kind, &from.ty) else {
+            return;
+        };
+        if !from_ty.is_ptr_or_array() {
+            return;
+        }
+        let from_base = from_ty.base_type();
+        let discarded = [
+            (from_base.is_const && !to_base.is_const, "const"),
+            (from_base.is_volatile && !to_base.is_volatile, "volatile"),
+        ]
+        .into_iter()
+        .filter_map(|(lost, qualifier)| lost.then_some(qualifier))
+        .collect::<Vec<_>>();
+        if !discarded.is_empty() {
+            warn(&format!(
+                "{}でポインタの指す型から修飾子 '{}' が失われます",
+                context,
+                discarded.join(" ")
+            ));
+        }
+    }
+
     // cond_expr ::= logical_or_expr
     //               | logical_or_expr "?" expr ":" cond_expr
     fn cond_expr(&mut self) -> Result<Option<Box<Node>>, CompileError> {
@@ -263,29 +325,68 @@ impl Ast {
     }
 
     // cast_expr ::= unary_expr
-    //               | "(" type_name ")" cast_expr // 未実装
+    //               | "(" type_name ")" cast_expr
     fn cast_expr(&mut self) -> Result<Option<Box<Node>>, CompileError> {
+        if self.peek_punctuator("(") && self.is_type_name_at(self.token_pos + 1) {
+            self.consume_punctuator("(");
+            let ty = self.type_name()?;
+            self.expect_punctuator(")")?;
+            let operand = self.cast_expr()?;
+            let operand = self.assign_identifier(operand)?;
+            return Ok(Some(Self::cast(operand, ty)?));
+        }
         self.unary_expr()
     }
 
+    // 式を指定した型に変換するノードを作成する
+    pub(super) fn cast(node: Option<Box<Node>>, ty: Box<Type>) -> Result<Box<Node>, CompileError> {
+        let mut node = node.ok_or_else(|| CompileError::InvalidExpression {
+            msg: "キャストする式がありません".to_string(),
+        })?;
+        node.assign_types()?;
+        let from = node
+            .ty
+            .as_ref()
+            .ok_or_else(|| CompileError::InternalError {
+                msg: "キャストする式の型情報が設定されていません".to_string(),
+            })?;
+        // voidへのキャスト以外は、スカラー型（ポインタを含む）同士のみ変換できる
+        // ポインタと浮動小数点型の間では変換できない
+        let convertible = |ty: &Type| ty.is_scalar() || ty.is_ptr_like();
+        if !(ty.kind == TypeKind::Void || convertible(from) && convertible(&ty))
+            || from.is_floating_point() && ty.is_ptr_like()
+            || from.is_ptr_like() && ty.is_floating_point()
+        {
+            return Err(CompileError::InvalidExpression {
+                msg: format!("{} を {} にキャストできません", from.kind, ty.kind),
+            });
+        }
+        let mut cast = Node::new_unary(NodeKind::Cast, Some(node));
+        cast.ty = Some(ty);
+        Ok(Box::new(cast))
+    }
+
     // unary_expr ::= postfix_expr
     //                | ("++" | "--") unary_expr
     //                | ( "&" | "*" | "+" | "-" | "~" | "!") cast_expr
     //                | sizeof unary_expr
     //                | sizeof "(" type_name ")"
+    //                | _Alignof unary_expr
+    //                | _Alignof "(" type_name ")"
     fn unary_expr(&mut self) -> Result<Option<Box<Node>>, CompileError> {
         if self.consume_punctuator("++").is_some() {
             // pre-increment
             let mut node = self.unary_expr()?;
+            Self::check_modifiable(&mut node)?;
             if let Some(n) = &mut node
                 && let Some(ty) = &n.ty
                 && ty.is_ptr_or_array()
             {
-                let size = ty.base_type().size_of();
+                let size = Node::new_size(ty.base_type());
                 return Ok(Some(Box::new(Node::new(
                     NodeKind::AddAssign,
                     node,
-                    Some(Box::new(Node::new_num(size as i64))),
+                    Some(Box::new(size.clone())),
                 ))));
             }
             return Ok(Some(Box::new(Node::new_unary(NodeKind::PreInc, node))));
@@ -293,15 +394,16 @@ impl Ast {
         if self.consume_punctuator("--").is_some() {
             // pre-decrement
             let mut node = self.unary_expr()?;
+            Self::check_modifiable(&mut node)?;
             if let Some(n) = &mut node
                 && let Some(ty) = &n.ty
                 && ty.is_ptr_or_array()
             {
-                let size = ty.base_type().size_of();
+                let size = Node::new_size(ty.base_type());
                 return Ok(Some(Box::new(Node::new(
                     NodeKind::SubAssign,
                     node,
-                    Some(Box::new(Node::new_num(size as i64))),
+                    Some(Box::new(size.clone())),
                 ))));
             }
             return Ok(Some(Box::new(Node::new_unary(NodeKind::PreDec, node))));
@@ -309,19 +411,45 @@ impl Ast {
 
         if self.consume_punctuator("+").is_some() {
             // unary plus
-            return self.cast_expr();
+            // 整数型のオペランドは整数拡張する
+            let operand = self.cast_expr()?;
+            let mut operand = self.assign_identifier(operand)?;
+            if let Some(n) = &mut operand {
+                n.assign_types()?;
+                if let Some(ty) = n.promoted_type()
+                    && ty.is_integer()
+                {
+                    return Ok(Some(Self::cast(operand, Box::new(ty))?));
+                }
+            }
+            return Ok(operand);
         }
         if self.consume_punctuator("-").is_some() {
             // unary minus
+            // 浮動小数点数は0から引くと-0.0にならないため、符号を反転する
+            let operand = self.cast_expr()?;
+            let mut operand = self.assign_identifier(operand)?;
+            if let Some(n) = &mut operand {
+                n.assign_types()?;
+                if n.is_floating_point() {
+                    return Ok(Some(Box::new(Node::new_unary(NodeKind::Neg, operand))));
+                }
+            }
             return Ok(Some(Box::new(Node::new(
                 NodeKind::Sub,
                 Some(Box::new(Node::new_num(0))),
-                self.cast_expr()?,
+                operand,
             ))));
         }
         if self.consume_punctuator("&").is_some() {
             // address-of
-            let mut node = Box::new(Node::new_unary(NodeKind::Addr, self.cast_expr()?));
+            let operand = self.cast_expr()?;
+            if operand.as_ref().is_some_and(|n| n.is_bit_field()) {
+                return Err(CompileError::InvalidExpression {
+                    msg: "ビットフィールドのアドレスは取得できません".to_string(),
+                });
+            }
+            let mut node = Box::new(Node::new_unary(NodeKind::Addr, operand));
             node.assign_types()?;
             if node.ty.is_none() {
                 return Err(CompileError::InternalError {
@@ -357,37 +485,57 @@ impl Ast {
         }
 
         if self.consume_keyword("sizeof").is_some() {
-            // sizeof ( type_name )
-            if self.peek_punctuator("(") {
-                let token_pos = self.token_pos;
-                self.consume_punctuator("(");
-                if let Ok(ty) = self.type_name() {
-                    self.expect_punctuator(")")?;
-                    let size = ty.size_of();
-                    return Ok(Some(Box::new(Node::new_num(size as i64))));
-                }
-                self.token_pos = token_pos; // 型名をパースできなかった場合、トークン位置を元に戻す
-            }
+            let ty = self.type_operand("sizeof")?;
+            return Ok(Some(Box::new(Node::new_size(&ty))));
+        }
 
-            // sizeof unary_expr
-            let mut node = self.unary_expr()?;
-            if let Some(n) = &mut node {
-                n.assign_types()?;
-                if let Some(ty) = &n.ty {
-                    let size = ty.size_of();
-                    return Ok(Some(Box::new(Node::new_num(size as i64))));
-                } else {
-                    return Err(CompileError::InternalError {
-                        msg: "sizeof演算子の型情報が設定されていません".to_string(),
-                    })?;
-                }
-            }
+        if self.consume_keyword("_Alignof").is_some() {
+            // 式に対する_AlignofはGNU拡張
+            let ty = self.type_operand("_Alignof")?;
+            return Ok(Some(Box::new(Node::new_num(ty.align_of() as i64))));
         }
 
         self.postfix_expr()
     }
 
-    // 未確定の識別子をローカル変数またはグローバル変数に割り当てる
+    // sizeof・_Alignof演算子の "(" type_name ")" または unary_expr の部分
+    // オペランドの完全型を返す
+    fn type_operand(&mut self, op: &str) -> Result<Type, CompileError> {
+        let ty = if self.peek_punctuator("(") && self.is_type_name_at(self.token_pos + 1) {
+            self.consume_punctuator("(");
+            let ty = self.type_name()?;
+            self.expect_punctuator(")")?;
+            ty
+        } else {
+            let mut node = self.unary_expr()?.ok_or(CompileError::InvalidExpression {
+                msg: format!("{}演算子のオペランドがありません", op),
+            })?;
+            node.assign_types()?;
+            if node.is_bit_field() {
+                return Err(CompileError::InvalidExpression {
+                    msg: format!("ビットフィールドに{}演算子は適用できません", op),
+                });
+            }
+            node.ty.ok_or(CompileError::InternalError {
+                msg: format!("{}演算子の型情報が設定されていません", op),
+            })?
+        };
+        let ty = self.complete_type(&ty);
+        if ty.is_incomplete() {
+            // 要素数が省略された配列はその配列型自体を示す
+            let name = if matches!(ty.kind, TypeKind::Array { size: None, .. }) {
+                ty.tag_name()
+            } else {
+                ty.base_type().tag_name()
+            };
+            return Err(CompileError::InvalidExpression {
+                msg: format!("不完全型 {} に{}演算子は適用できません", name, op),
+            });
+        }
+        Ok(ty)
+    }
+
+    // 未確定の識別子をローカル変数、列挙定数またはグローバル変数に割り当てる
     // その他のノードはそのまま返す
     fn assign_identifier(
         &mut self,
@@ -401,11 +549,19 @@ impl Ast {
                 && let Some(lvar) = current_func.find_lvar(name)
             {
                 // ローカル変数ノードを作成
-                let node = Node::new_var(&lvar.name, lvar.offset, &lvar.ty, true);
+                // 宣言後に完全型になった構造体の型を反映する
+                let (offset, ty) = (lvar.offset, lvar.ty.clone());
+                let node = Node::new_var(name, offset, &self.complete_type(&ty), true);
+                return Ok(Some(Box::new(node)));
+            } else if let Some(ScopedIdent::EnumConst { val, ty }) = self.find_scoped_ident(name) {
+                // 列挙定数は数値ノードに畳み込む
+                let mut node = Node::new_num(*val);
+                node.ty = Some(Box::new(ty.clone()));
                 return Ok(Some(Box::new(node)));
             } else if let Some(gvar) = self.find_gvar(name) {
                 // グローバル変数ノードを作成
-                let node = Node::new_var(&gvar.name, 0, &gvar.ty, false);
+                let ty = gvar.ty.clone();
+                let node = Node::new_var(name, 0, &self.complete_type(&ty), false);
                 return Ok(Some(Box::new(node)));
             }
             Err(CompileError::UndefinedIdentifier { name: name.clone() })?;
@@ -440,39 +596,47 @@ impl Ast {
             } else if self.consume_punctuator("(").is_some() {
                 let args = self.argument_expr_list()?;
                 self.expect_punctuator(")")?;
-                node = Some(Box::new(Node::from(NodeKind::Call {
-                    name: if let Some(n) = &node
-                        && let NodeKind::Identifier { name } = &n.kind
-                    {
-                        name.clone()
-                    } else {
-                        return Err(CompileError::InternalError {
-                            msg: "関数呼び出しの関数名のパースに失敗しました".to_string(),
-                        });
-                    },
-                    args,
-                })));
+                node = Some(self.func_call(node, args)?);
             } else if self.consume_punctuator(".").is_some() {
-                unimplemented!("構造体メンバアクセスは未実装です");
+                node = self.assign_identifier(node)?; // 識別子を変数に割り当て
+                node = self.struct_member(node)?;
             } else if self.consume_punctuator("->").is_some() {
-                unimplemented!("構造体ポインタメンバアクセスは未実装です");
+                // p->m は (*p).m として扱う
+                node = self.assign_identifier(node)?; // 識別子を変数に割り当て
+                if let Some(n) = &mut node {
+                    n.assign_types()?;
+                    if let Some(ty) = &n.ty
+                        && !(ty.is_ptr_or_array() && ty.base_type().is_struct())
+                    {
+                        return Err(CompileError::InvalidExpression {
+                            msg: format!(
+                                "->演算子は構造体・共用体へのポインタにのみ適用可能です: {}",
+                                ty.tag_name()
+                            ),
+                        });
+                    }
+                }
+                let mut deref = Box::new(Node::new_unary(NodeKind::Deref, node));
+                deref.assign_types()?;
+                node = self.struct_member(Some(deref))?;
             } else if self.consume_punctuator("++").is_some() {
                 // post-increment
                 node = self.assign_identifier(node)?; // 識別子を変数に割り当て
+                Self::check_modifiable(&mut node)?;
                 if let Some(n) = &mut node
                     && let Some(ty) = &n.ty
                     && ty.is_ptr_or_array()
                 {
-                    let size = ty.base_type().size_of();
+                    let size = Node::new_size(ty.base_type());
                     let assign_node = Some(Box::new(Node::new(
                         NodeKind::AddAssign,
                         node,
-                        Some(Box::new(Node::new_num(size as i64))),
+                        Some(Box::new(size.clone())),
                     )));
                     node = Some(Box::new(Node::new(
                         NodeKind::Sub,
                         assign_node,
-                        Some(Box::new(Node::new_num(size as i64))),
+                        Some(Box::new(size.clone())),
                     )))
                 } else {
                     node = Some(Box::new(Node::new_unary(NodeKind::PostInc, node)));
@@ -480,20 +644,21 @@ impl Ast {
             } else if self.consume_punctuator("--").is_some() {
                 // post-decrement
                 node = self.assign_identifier(node)?; // 識別子を変数に割り当て
+                Self::check_modifiable(&mut node)?;
                 if let Some(n) = &mut node
                     && let Some(ty) = &n.ty
                     && ty.is_ptr_or_array()
                 {
-                    let size = ty.base_type().size_of();
+                    let size = Node::new_size(ty.base_type());
                     let assign_node = Some(Box::new(Node::new(
                         NodeKind::SubAssign,
                         node,
-                        Some(Box::new(Node::new_num(size as i64))),
+                        Some(Box::new(size.clone())),
                     )));
                     node = Some(Box::new(Node::new(
                         NodeKind::Add,
                         assign_node,
-                        Some(Box::new(Node::new_num(size as i64))),
+                        Some(Box::new(size.clone())),
                     )))
                 } else {
                     node = Some(Box::new(Node::new_unary(NodeKind::PostDec, node)));
@@ -505,6 +670,179 @@ impl Ast {
         }
     }
 
+    // 構造体・共用体の式からメンバーのノードを作成する
+    fn struct_member(
+        &mut self,
+        node: Option<Box<Node>>,
+    ) -> Result<Option<Box<Node>>, CompileError> {
+        let name = self
+            .consume_ident()
+            .ok_or_else(|| CompileError::InvalidExpression {
+                msg: "メンバー名が必要です".to_string(),
+            })?;
+        let Some(mut node) = node else {
+            return Err(CompileError::InvalidExpression {
+                msg: "メンバーアクセスの左辺がありません".to_string(),
+            });
+        };
+        node.assign_types()?;
+        let ty = node
+            .ty
+            .as_ref()
+            .ok_or_else(|| CompileError::InternalError {
+                msg: "メンバーアクセスの左辺の型情報が設定されていません".to_string(),
+            })?;
+        if !ty.is_struct() {
+            return Err(CompileError::InvalidExpression {
+                msg: format!(
+                    "メンバー '{}' の参照は構造体・共用体にのみ適用可能です: {}",
+                    name, ty.kind
+                ),
+            });
+        }
+        let member =
+            ty.find_struct_member(&name)
+                .ok_or_else(|| CompileError::InvalidExpression {
+                    msg: format!("{} にメンバー '{}' はありません", ty.tag_name(), name),
+                })?;
+        // 修飾された構造体・共用体のメンバーは同じ修飾を受ける
+        let mut member_ty = self.complete_type(&member.ty);
+        member_ty.qualify_as(ty);
+        let mut member_node = Node::new_unary(
+            NodeKind::Member {
+                name,
+                offset: member.offset,
+                bit_field: member.bit_field,
+            },
+            Some(node),
+        );
+        member_node.ty = Some(Box::new(member_ty));
+        Ok(Some(Box::new(member_node)))
+    }
+
+    // 関数呼び出しのノードを作成する
+    // 呼び出す式は関数指示子か関数へのポインタでなければならない
+    #[allow(clippy::vec_box)]
+    fn func_call(
+        &mut self,
+        node: Option<Box<Node>>,
+        args: Vec<Box<Node>>,
+    ) -> Result<Box<Node>, CompileError> {
+        let callee = match node.as_ref().map(|n| &n.kind) {
+            // 宣言されていない関数はintを返す関数として呼び出す
+            Some(NodeKind::Identifier { name }) if !self.is_object_ident(name) => {
+                let ty = match self.find_gvar(name) {
+                    Some(gvar) => *gvar.ty.clone(),
+                    None => Type::from(
+                        &TypeKind::Func {
+                            return_ty: Box::new(self.find_func_return_ty(name)),
+                            params: Vec::new(),
+                            variadic: false,
+                        },
+                        false,
+                    ),
+                };
+                Some(Box::new(Node::new_var(name, 0, &ty, false)))
+            }
+            _ => self.assign_identifier(node)?,
+        };
+        let mut call = Box::new(Node::new_unary(
+            NodeKind::Call {
+                args,
+                ret_buf: None,
+            },
+            callee,
+        ));
+        call.assign_types()?;
+        let callee_ty = call.lhs.as_ref().and_then(|n| n.ty.as_ref());
+        let (return_ty, params) = match callee_ty.map(|ty| (&ty.kind, ty.base_type())) {
+            Some((
+                TypeKind::Func {
+                    return_ty, params, ..
+                },
+                _,
+            )) => (return_ty.clone(), params),
+            Some((
+                TypeKind::Ptr { .. },
+                Type {
+                    kind:
+                        TypeKind::Func {
+                            return_ty, params, ..
+                        },
+                    ..
+                },
+            )) => (return_ty.clone(), params),
+            _ => {
+                return Err(CompileError::InvalidExpression {
+                    msg: format!(
+                        "関数でも関数へのポインタでもない式は呼び出せません: {:?}",
+                        callee_ty
+                    ),
+                });
+            }
+        };
+        let params = params.clone();
+        if let NodeKind::Call { args, .. } = &mut call.kind {
+            for (i, arg) in args.iter_mut().enumerate() {
+                let arg_ty = arg.ty.as_deref().cloned();
+                match params.get(i) {
+                    // 算術型の引数は仮引数の型に変換する
+                    Some(param) => {
+                        Self::check_pointer_qualifiers(&param.ty, arg, "引数");
+                        if param.ty.is_scalar()
+                            && arg_ty
+                                .as_ref()
+                                .is_some_and(|ty| ty.is_scalar() && ty.kind != param.ty.kind)
+                        {
+                            *arg = Self::cast(Some(arg.clone()), param.ty.clone())?;
+                        }
+                    }
+                    // 対応する仮引数がない場合は、既定の実引数拡張によりfloatをdoubleにする
+                    None if arg_ty.is_some_and(|ty| ty.kind == TypeKind::Float) => {
+                        let double = Type::from(&TypeKind::Double, false);
+                        *arg = Self::cast(Some(arg.clone()), Box::new(double))?;
+                    }
+                    None => {}
+                }
+            }
+        }
+        // 構造体・共用体の戻り値は、レジスタで返される場合も8バイト単位で書き込めるように
+        // 8バイトの倍数の大きさの領域で受け取る
+        let return_ty = Box::new(self.complete_type(&return_ty));
+        if return_ty.is_struct()
+            && let Ok(func) = self.get_current_func()
+        {
+            let mut buf_ty = Type::from(
+                &TypeKind::Array {
+                    base: Box::new(Type::from(&TypeKind::ULong, false)),
+                    size: Some(return_ty.size_of().div_ceil(8)),
+                },
+                false,
+            );
+            buf_ty.set_align(return_ty.align_of().max(8));
+            func.gen_lvar(Var::new("", buf_ty))?;
+            let offset = func.locals.last().map(|var| var.offset);
+            if let NodeKind::Call { ret_buf, .. } = &mut call.kind {
+                *ret_buf = offset;
+            }
+        }
+        call.ty = Some(return_ty);
+        Ok(call)
+    }
+
+    // 識別子が関数以外の変数や列挙定数を表すかどうか
+    fn is_object_ident(&mut self, name: &str) -> bool {
+        if let Ok(current_func) = self.get_current_func()
+            && current_func.find_lvar(name).is_some()
+        {
+            return true;
+        }
+        if let Some(ScopedIdent::EnumConst { .. }) = self.find_scoped_ident(name) {
+            return true;
+        }
+        self.find_gvar(name).is_some_and(|gvar| !gvar.ty.is_func())
+    }
+
     // argument_expr_list ::= assign_expr ("," assign_expr)*
     #[allow(clippy::vec_box)]
     fn argument_expr_list(&mut self) -> Result<Vec<Box<Node>>, CompileError> {
@@ -527,11 +865,184 @@ impl Ast {
         Ok(args)
     }
 
+    // generic_selection ::= "_Generic" "(" assign_expr "," generic_assoc ("," generic_assoc)* ")"
+    // generic_assoc ::= (type_name | "default") ":" assign_expr
+    // 制御式は評価せず、選ばれた選択肢の式のみを返す
+    fn generic_selection(&mut self) -> Result<Box<Node>, CompileError> {
+        self.expect_punctuator("(")?;
+        let mut control = self.assign_expr()?.ok_or(CompileError::InvalidExpression {
+            msg: "_Genericの制御式がありません".to_string(),
+        })?;
+        control.assign_types()?;
+        let control_ty = control
+            .ty
+            .map(|ty| self.complete_type(&ty).lvalue_converted())
+            .ok_or(CompileError::InternalError {
+                msg: "_Genericの制御式の型情報が設定されていません".to_string(),
+            })?;
+        let mut assoc_types: Vec<Type> = Vec::new();
+        let mut selected = None;
+        let mut default = None;
+        while self.consume_punctuator(",").is_some() {
+            let assoc_ty = if self.consume_keyword("default").is_some() {
+                if default.is_some() {
+                    return Err(CompileError::InvalidExpression {
+                        msg: "_Genericにdefaultが複数あります".to_string(),
+                    });
+                }
+                None
+            } else {
+                let ty = self.type_name()?;
+                let ty = self.complete_type(&ty);
+                if assoc_types.iter().any(|t| t.is_compatible(&ty)) {
+                    return Err(CompileError::InvalidExpression {
+                        msg: format!("_Genericに適合する型 {:?} の選択肢が複数あります", ty),
+                    });
+                }
+                assoc_types.push(ty.clone());
+                Some(ty)
+            };
+            self.expect_punctuator(":")?;
+            let expr = self.assign_expr()?.ok_or(CompileError::InvalidExpression {
+                msg: "_Genericの選択肢に式がありません".to_string(),
+            })?;
+            match assoc_ty {
+                Some(ty) if ty.is_compatible(&control_ty) => selected = Some(expr),
+                Some(_) => {}
+                None => default = Some(expr),
+            }
+        }
+        self.expect_punctuator(")")?;
+        selected.or(default).ok_or(CompileError::InvalidExpression {
+            msg: format!("_Genericに型 {:?} に適合する選択肢がありません", control_ty),
+        })
+    }
+
+    // va_listの引数を読む
+    // va_list型の変数は配列、va_list型の仮引数はポインタとなる
+    fn va_list_operand(&mut self, builtin: &str) -> Result<Box<Node>, CompileError> {
+        let node = self.assign_expr()?;
+        let mut node =
+            self.assign_identifier(node)?
+                .ok_or_else(|| CompileError::InvalidExpression {
+                    msg: format!("{}の引数がありません", builtin),
+                })?;
+        node.assign_types()?;
+        match node.ty.as_ref() {
+            Some(ty)
+                if ty.is_ptr_or_array()
+                    && matches!(ty.base_type().kind, TypeKind::Struct { id, .. } if id == VA_LIST_TAG_ID) =>
+            {
+                Ok(node)
+            }
+            ty => Err(CompileError::InvalidExpression {
+                msg: format!("{}の引数はva_list型である必要があります: {:?}", builtin, ty),
+            }),
+        }
+    }
+
+    // builtin_va ::= "__builtin_va_start" "(" assign_expr ("," assign_expr)? ")"
+    //                | "__builtin_va_arg" "(" assign_expr "," type_name ")"
+    //                | "__builtin_va_copy" "(" assign_expr "," assign_expr ")"
+    //                | "__builtin_va_end" "(" assign_expr ")"
+    // va_startの2番目の引数（最後の名前付き仮引数）は評価しない
+    fn builtin_va(&mut self, builtin: &str) -> Result<Box<Node>, CompileError> {
+        self.expect_punctuator("(")?;
+        let ap = self.va_list_operand(builtin)?;
+        let void = Box::new(Type::from(&TypeKind::Void, false));
+        let node = match builtin {
+            "__builtin_va_start" => {
+                if self.get_current_func()?.va_area.is_none() {
+                    return Err(CompileError::InvalidExpression {
+                        msg: "va_startは可変長引数の関数の中でのみ使用できます".to_string(),
+                    });
+                }
+                if self.consume_punctuator(",").is_some() {
+                    self.assign_expr()?;
+                }
+                let mut node = Node::new_unary(NodeKind::VaStart, Some(ap));
+                node.ty = Some(void);
+                Box::new(node)
+            }
+            "__builtin_va_arg" => {
+                self.expect_punctuator(",")?;
+                let ty = self.type_name()?;
+                let ty = self.complete_type(&ty);
+                // 既定の実引数拡張によりfloatはdoubleとして渡されるため、floatは取り出せない
+                if !(ty.is_scalar() || ty.is_ptr_like() || ty.is_struct())
+                    || ty.kind == TypeKind::Float
+                    || ty.is_incomplete()
+                {
+                    return Err(CompileError::InvalidExpression {
+                        msg: format!("va_argで型 {} の引数は取り出せません", ty.kind),
+                    });
+                }
+                // レジスタで渡された構造体は、8バイト単位で一時領域に並べ直す
+                let temp = if ty.is_struct() && ty.eightbyte_classes().is_some() {
+                    let mut temp_ty = Type::from(
+                        &TypeKind::Array {
+                            base: Box::new(Type::from(&TypeKind::ULong, false)),
+                            size: Some(ty.size_of().div_ceil(8)),
+                        },
+                        false,
+                    );
+                    temp_ty.set_align(ty.align_of().max(8));
+                    let func = self.get_current_func()?;
+                    func.gen_lvar(Var::new("", temp_ty))?;
+                    func.locals.last().map(|var| var.offset)
+                } else {
+                    None
+                };
+                let mut node = Node::new_unary(NodeKind::VaArg { temp }, Some(ap));
+                node.ty = Some(Box::new(Type::from(
+                    &TypeKind::Ptr { to: Box::new(ty) },
+                    false,
+                )));
+                let mut deref = Box::new(Node::new_unary(NodeKind::Deref, Some(Box::new(node))));
+                deref.assign_types()?;
+                deref
+            }
+            "__builtin_va_copy" => {
+                // *dst = *src として__va_list_tag構造体をコピーする
+                self.expect_punctuator(",")?;
+                let src = self.va_list_operand(builtin)?;
+                let mut assign = Node::new(
+                    NodeKind::Assign,
+                    Some(Box::new(Node::new_unary(NodeKind::Deref, Some(ap)))),
+                    Some(Box::new(Node::new_unary(NodeKind::Deref, Some(src)))),
+                );
+                assign.assign_types()?;
+                Self::cast(Some(Box::new(assign)), void)?
+            }
+            // va_endでは何もしない
+            _ => Self::cast(Some(ap), void)?,
+        };
+        self.expect_punctuator(")")?;
+        Ok(node)
+    }
+
     // primary_expr ::= "(" expr ")"
     //                  | identifier
     //                  | string
     //                  | number
+    //                  | generic_selection
+    //                  | builtin_va
     fn primary_expr(&mut self) -> Result<Option<Box<Node>>, CompileError> {
+        if self.consume_keyword("_Generic").is_some() {
+            return Ok(Some(self.generic_selection()?));
+        }
+
+        for builtin in [
+            "__builtin_va_start",
+            "__builtin_va_arg",
+            "__builtin_va_copy",
+            "__builtin_va_end",
+        ] {
+            if self.consume_keyword(builtin).is_some() {
+                return Ok(Some(self.builtin_va(builtin)?));
+            }
+        }
+
         // "(" expr ")"
         if self.consume_punctuator("(").is_some()
             && let Some(node) = self.expr()?
@@ -546,16 +1057,33 @@ impl Ast {
         }
 
         if let Some(string) = self.consume_string() {
-            let node = Node::from(NodeKind::String {
+            let mut node = Node::from(NodeKind::String {
                 val: string.clone(),
                 index: self.string_literals.len() as i64,
             });
+            node.ty = Some(Box::new(Type::from(
+                &TypeKind::Ptr {
+                    to: Box::new(Type::from(
+                        &TypeKind::Char {
+                            unsigned: self.unsigned_char,
+                        },
+                        false,
+                    )),
+                },
+                false,
+            )));
             self.string_literals.push(string);
             return Ok(Some(Box::new(node)));
         }
 
-        if let Some(num) = self.consume_number() {
-            return Ok(Some(Box::new(Node::new_num(num))));
+        if let Some((num, kind)) = self.consume_number() {
+            let mut node = Node::new_num(num);
+            node.ty = Some(Box::new(Type::from(&kind, false)));
+            return Ok(Some(Box::new(node)));
+        }
+
+        if let Some((val, ext, kind)) = self.consume_float_number() {
+            return Ok(Some(Box::new(Node::new_float(val, ext, &kind))));
         }
 
         Ok(None)
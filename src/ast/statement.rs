@@ -1,9 +1,7 @@
-use std::ops::Deref;
-
-use crate::ast::Ast;
+use crate::ast::{Ast, InitElem, Var};
 use crate::errors::CompileError;
 use crate::node::{Node, NodeKind};
-use crate::types::TypeKind;
+use crate::types::{Type, TypeKind};
 
 impl Ast {
     // TODO: case文, default文の実装
@@ -25,11 +23,37 @@ impl Ast {
     // compound_stmt ::= "{" declaration* stmt* "}"
     pub(super) fn compound_stmt(&mut self) -> Result<Option<Box<Node>>, CompileError> {
         if self.consume_punctuator("{").is_some() {
+            self.enter_scope();
             let mut body = Vec::new();
             while self.consume_punctuator("}").is_none() {
-                if let Some(vars) = self.declaration()? {
-                    for var in vars {
-                        self.get_current_func()?.gen_lvar(var)?;
+                if let Some(vars) = self.declaration(&mut body)? {
+                    for mut var in vars {
+                        // ブロック内のextern宣言はグローバル変数を参照する
+                        if var.is_extern {
+                            self.push_var_ident(&var.name)?;
+                            self.gen_gvar(var)?;
+                            continue;
+                        }
+                        self.check_complete(&var)?;
+                        self.push_var_ident(&var.name)?;
+                        if var.ty.is_variably_modified() {
+                            self.declare_vla_sizes(&mut var.ty, &mut body)?;
+                        }
+                        let init_whole = var.is_init_whole();
+                        let init = std::mem::take(&mut var.init);
+                        let func = self.get_current_func()?;
+                        func.gen_lvar(var)?;
+                        let lvar = func.locals.last().unwrap();
+                        if lvar.ty.is_vla() {
+                            // 変数にはスタックに確保した領域のアドレスを格納する
+                            body.push(Box::new(Node::new_unary(
+                                NodeKind::VlaAlloc {
+                                    offset: lvar.offset,
+                                },
+                                Some(Box::new(Node::new_size(&lvar.ty))),
+                            )));
+                        }
+                        body.extend(Self::local_init(lvar, init, init_whole));
                     }
                     continue;
                 } else if let Some(stmt) = self.stmt()? {
@@ -40,11 +64,93 @@ impl Ast {
                     });
                 }
             }
+            self.leave_scope();
             return Ok(Some(Box::new(Node::from(NodeKind::Block { body }))));
         }
         Ok(None)
     }
 
+    // 型に含まれる可変長配列のサイズを実行時に求める文をbodyに追加する
+    // 内側の次元から順にサイズを隠れたローカル変数に格納し、その位置を型に記録する
+    // typedefで既にサイズを求めた可変長配列は、その値をそのまま使う
+    #[allow(clippy::vec_box)]
+    pub(super) fn declare_vla_sizes(
+        &mut self,
+        ty: &mut Type,
+        body: &mut Vec<Box<Node>>,
+    ) -> Result<(), CompileError> {
+        match &mut ty.kind {
+            TypeKind::Ptr { to: base } | TypeKind::Array { base, .. } => {
+                self.declare_vla_sizes(base, body)
+            }
+            TypeKind::Vla {
+                size_offset: Some(_),
+                ..
+            } => Ok(()),
+            TypeKind::Vla {
+                base,
+                len,
+                size_offset,
+            } => {
+                self.declare_vla_sizes(base, body)?;
+                let size_ty = Type::from(&TypeKind::ULong, false);
+                let func = self.get_current_func()?;
+                func.gen_lvar(Var::new("", size_ty.clone()))?;
+                let offset = func.locals.last().unwrap().offset;
+                let mut assign = Node::new(
+                    NodeKind::Assign,
+                    Some(Box::new(Node::new_var("", offset, &size_ty, true))),
+                    Some(Box::new(Node::new(
+                        NodeKind::Mul,
+                        Some(len.clone()),
+                        Some(Box::new(Node::new_size(base))),
+                    ))),
+                );
+                assign.assign_types()?;
+                body.push(Box::new(assign));
+                *size_offset = Some(offset);
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // ローカル変数の初期化子を宣言の位置で実行する代入文に変換する
+    #[allow(clippy::vec_box)]
+    fn local_init(var: &Var, init: Vec<InitElem>, init_whole: bool) -> Vec<Box<Node>> {
+        let target = Node::new_var(&var.name, var.offset, &var.ty, true);
+        let mut stmts = Vec::new();
+        if !init.is_empty() && !init_whole {
+            // 初期化子で指定されない部分は0で初期化する
+            stmts.push(Box::new(Node::new_unary(
+                NodeKind::ZeroFill,
+                Some(Box::new(target.clone())),
+            )));
+        }
+        for elem in init {
+            let lhs = if init_whole {
+                target.clone()
+            } else {
+                let mut member = Node::new_unary(
+                    NodeKind::Member {
+                        name: String::new(),
+                        offset: elem.offset,
+                        bit_field: elem.bit_field,
+                    },
+                    Some(Box::new(target.clone())),
+                );
+                member.ty = Some(Box::new(elem.ty));
+                member
+            };
+            stmts.push(Box::new(Node::new(
+                NodeKind::Assign,
+                Some(Box::new(lhs)),
+                Some(elem.expr),
+            )));
+        }
+        stmts
+    }
+
     // TODO: switch文の実装
     // selection_stmt ::= "if" "(" expr ")" stmt ("else" stmt)?
     fn selection_stmt(&mut self) -> Result<Option<Box<Node>>, CompileError> {
@@ -149,8 +255,8 @@ impl Ast {
             if self.consume_punctuator(";").is_some() {
                 if TypeKind::Void != self.get_current_func()?.return_ty.kind {
                     return Err(CompileError::InvalidReturnType {
-                        expected: self.get_current_func()?.return_ty.clone().kind,
-                        found: TypeKind::Void,
+                        expected: Box::new(self.get_current_func()?.return_ty.clone().kind),
+                        found: Box::new(TypeKind::Void),
                     });
                 }
                 return Ok(Some(Box::new(Node::from(NodeKind::Return))));
@@ -160,15 +266,34 @@ impl Ast {
                 n.assign_types()?;
                 if let Some(ret_ty) = &n.ty {
                     let func_ret_ty = &self.get_current_func()?.return_ty;
-                    if ret_ty.deref() != func_ret_ty {
+                    // 配列や関数はポインタに変換してから比較する
+                    // 算術型同士は暗黙に変換されるため許容する
+                    // 型修飾子の違いは変換で失われる修飾子のみ警告する
+                    let convertible = ret_ty
+                        .lvalue_converted()
+                        .unqualified()
+                        .is_compatible(&func_ret_ty.unqualified())
+                        || (ret_ty.is_scalar() && func_ret_ty.is_scalar());
+                    if !convertible {
                         return Err(CompileError::InvalidReturnType {
-                            expected: func_ret_ty.kind.clone(),
-                            found: ret_ty.kind.clone(),
+                            expected: Box::new(func_ret_ty.kind.clone()),
+                            found: Box::new(ret_ty.kind.clone()),
                         });
                     }
+                    Self::check_pointer_qualifiers(func_ret_ty, n, "戻り値");
                 }
             }
             self.expect_punctuator(";")?;
+            let func_ret_ty = &self.get_current_func()?.return_ty;
+            if func_ret_ty.is_scalar()
+                && node
+                    .as_ref()
+                    .and_then(|n| n.ty.as_ref())
+                    .is_some_and(|ty| ty.kind != func_ret_ty.kind)
+            {
+                // 戻り値を関数の戻り値の型に変換する
+                node = Some(Self::cast(node, Box::new(func_ret_ty.clone()))?);
+            }
             return Ok(Some(Box::new(Node::new_unary(NodeKind::Return, node))));
         }
         Ok(None)
@@ -0,0 +1,113 @@
+use core::fmt;
+use core::str::FromStr;
+
+use encoding_rs::{EUC_JP, Encoding, SHIFT_JIS};
+
+use crate::errors::CompileError;
+
+// ソース文字集合および実行文字集合として扱える文字コード
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Charset {
+    #[default]
+    Utf8,
+    ShiftJis,
+    EucJp,
+    Latin1,
+}
+
+impl fmt::Display for Charset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Charset::Utf8 => write!(f, "UTF-8"),
+            Charset::ShiftJis => write!(f, "Shift_JIS"),
+            Charset::EucJp => write!(f, "EUC-JP"),
+            Charset::Latin1 => write!(f, "ISO-8859-1"),
+        }
+    }
+}
+
+impl FromStr for Charset {
+    type Err = CompileError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // 大文字・小文字および区切り文字の違いは無視する
+        let name = s
+            .chars()
+            .filter(|c| !matches!(c, '-' | '_'))
+            .collect::<String>()
+            .to_ascii_uppercase();
+        match name.as_str() {
+            "UTF8" => Ok(Charset::Utf8),
+            "SHIFTJIS" | "SJIS" | "CP932" | "MSKANJI" | "WINDOWS31J" => Ok(Charset::ShiftJis),
+            "EUCJP" => Ok(Charset::EucJp),
+            "LATIN1" | "ISO88591" | "L1" => Ok(Charset::Latin1),
+            _ => Err(CompileError::InvalidEncoding {
+                msg: format!("未対応の文字コードです: {}", s),
+            }),
+        }
+    }
+}
+
+impl Charset {
+    // encoding_rsで変換する文字コードの場合、そのエンコーディングを返す
+    fn encoding(&self) -> Option<&'static Encoding> {
+        match self {
+            Charset::ShiftJis => Some(SHIFT_JIS),
+            Charset::EucJp => Some(EUC_JP),
+            Charset::Utf8 | Charset::Latin1 => None,
+        }
+    }
+
+    // この文字コードのバイト列をUTF-8の文字列に変換する
+    pub fn decode(&self, bytes: &[u8]) -> Result<String, CompileError> {
+        match self {
+            Charset::Utf8 => {
+                String::from_utf8(bytes.to_vec()).map_err(|e| CompileError::InvalidEncoding {
+                    msg: format!(
+                        "{}として不正なバイト列です (offset: {})",
+                        self,
+                        e.utf8_error().valid_up_to()
+                    ),
+                })
+            }
+            // Latin-1は各バイトがそのままU+0000..U+00FFに対応する
+            Charset::Latin1 => Ok(bytes.iter().map(|&b| b as char).collect()),
+            _ => {
+                let encoding = self.encoding().unwrap();
+                encoding
+                    .decode_without_bom_handling_and_without_replacement(bytes)
+                    .map(|s| s.into_owned())
+                    .ok_or_else(|| CompileError::InvalidEncoding {
+                        msg: format!("{}として不正なバイト列です", self),
+                    })
+            }
+        }
+    }
+
+    // UTF-8の文字列をこの文字コードのバイト列に変換する
+    pub fn encode(&self, s: &str) -> Result<Vec<u8>, CompileError> {
+        let unmappable = |c: char| CompileError::InvalidEncoding {
+            msg: format!("'{}' は{}で表現できません", c, self),
+        };
+        match self {
+            Charset::Utf8 => Ok(s.as_bytes().to_vec()),
+            Charset::Latin1 => s
+                .chars()
+                .map(|c| u8::try_from(c).map_err(|_| unmappable(c)))
+                .collect(),
+            _ => {
+                // encoding_rsは表現できない文字を数値文字参照に置き換えるため、1文字ずつ変換して検出する
+                let encoding = self.encoding().unwrap();
+                let mut bytes = Vec::new();
+                let mut buf = [0; 4];
+                for c in s.chars() {
+                    let (encoded, _, had_errors) = encoding.encode(c.encode_utf8(&mut buf));
+                    if had_errors {
+                        return Err(unmappable(c));
+                    }
+                    bytes.extend_from_slice(&encoded);
+                }
+                Ok(bytes)
+            }
+        }
+    }
+}
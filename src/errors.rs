@@ -36,9 +36,23 @@ pub enum CompileError {
     InvalidInitializer {
         msg: String,
     },
+    NonConstantInitializer {
+        msg: String,
+        span: (usize, usize),
+    },
     InvalidReturnType {
-        expected: TypeKind,
-        found: TypeKind,
+        expected: Box<TypeKind>,
+        found: Box<TypeKind>,
+    },
+    InvalidEncoding {
+        msg: String,
+    },
+    InvalidDirective {
+        msg: String,
+    },
+    StaticAssertion {
+        msg: Option<String>,
+        span: (usize, usize),
     },
     UnexpectedEof,
     InternalError {
@@ -86,6 +100,9 @@ impl fmt::Display for CompileError {
             CompileError::InvalidInitializer { msg } => {
                 write!(f, "invalid initializer: {}", msg)
             }
+            CompileError::NonConstantInitializer { msg, span } => {
+                write!(f, "initializer is not a constant: {} at {:?}", msg, span)
+            }
             CompileError::InvalidReturnType { expected, found } => {
                 write!(
                     f,
@@ -93,6 +110,16 @@ impl fmt::Display for CompileError {
                     expected, found
                 )
             }
+            CompileError::InvalidEncoding { msg } => {
+                write!(f, "invalid encoding: {}", msg)
+            }
+            CompileError::InvalidDirective { msg } => {
+                write!(f, "invalid directive: {}", msg)
+            }
+            CompileError::StaticAssertion { msg, span } => match msg {
+                Some(msg) => write!(f, "static assertion failed: \"{}\" at {:?}", msg, span),
+                None => write!(f, "static assertion failed at {:?}", span),
+            },
             CompileError::UnexpectedEof => {
                 write!(f, "unexpected end of file")
             }
@@ -102,3 +129,8 @@ impl fmt::Display for CompileError {
         }
     }
 }
+
+// コンパイルは継続できるが、誤りの可能性がある箇所を報告する
+pub fn warn(msg: &str) {
+    eprintln!("Warning: {}", msg);
+}
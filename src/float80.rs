@@ -0,0 +1,308 @@
+use std::cmp::Ordering;
+
+// x87の80ビット拡張倍精度浮動小数点数（long double）のビット表現を扱う
+// 下位64ビットが整数ビットを含む仮数部、その上の16ビットが符号と指数部になる
+
+const EXP_BIAS: i32 = 16383;
+const EXP_MAX: i32 = 0x7fff;
+const INTEGER_BIT: u128 = 1 << 63;
+pub const SIGN_BIT: u128 = 1 << 79;
+
+// f64の値を拡張倍精度のビット表現に変換する（誤差なく表現できる）
+pub fn from_f64(val: f64) -> u128 {
+    let bits = val.to_bits();
+    let sign = if val.is_sign_negative() { SIGN_BIT } else { 0 };
+    let exp = ((bits >> 52) & 0x7ff) as i32;
+    let frac = bits & ((1 << 52) - 1);
+    match exp {
+        0 if frac == 0 => sign,
+        // 非正規化数は拡張倍精度では正規化数として表せる
+        0 => {
+            let shift = frac.leading_zeros() as i32;
+            let exp = 63 - 1074 - shift + EXP_BIAS;
+            sign | (exp as u128) << 64 | (frac << shift) as u128
+        }
+        // 無限大とNaN
+        0x7ff => sign | (EXP_MAX as u128) << 64 | INTEGER_BIT | (frac as u128) << 11,
+        _ => sign | ((exp - 1023 + EXP_BIAS) as u128) << 64 | INTEGER_BIT | (frac as u128) << 11,
+    }
+}
+
+// 数字の列が表す整数に基数の累乗を掛けた値を、最も近い拡張倍精度のビット表現に変換する
+// 10進数はdigits × 10^exp、16進数はdigits × 2^expを表す
+pub fn from_digits(digits: &[u32], radix: u32, exp: i32) -> u128 {
+    let mut num = Vec::new();
+    for &d in digits {
+        mul_small(&mut num, radix, d);
+    }
+    if num.is_empty() {
+        return 0;
+    }
+    // 明らかに範囲外の値は、巨大な累乗を計算せずに無限大か0にする
+    let magnitude = if radix == 16 {
+        bit_len(&num) as i64 + exp as i64
+    } else {
+        (digits.len() as i64 + exp as i64) * 10 / 3
+    };
+    if magnitude > 17000 {
+        return infinity(false);
+    }
+    if magnitude < -17000 {
+        return 0;
+    }
+
+    let (bin_exp, dec_exp) = if radix == 16 { (exp, 0) } else { (0, exp) };
+    let mut den = vec![1];
+    for _ in 0..dec_exp.unsigned_abs() {
+        mul_small(if dec_exp > 0 { &mut num } else { &mut den }, 10, 0);
+    }
+    // 丸めに使うビットを含めて商が66ビットか67ビットになるように、num / den を2^shiftで拡大する
+    let shift = 66 - (bit_len(&num) as i32 - bit_len(&den) as i32);
+    if shift >= 0 {
+        num = shl(&num, shift as usize);
+    } else {
+        den = shl(&den, (-shift) as usize);
+    }
+    let mut q: u128 = 0;
+    for bit in (0..=67).rev() {
+        let d = shl(&den, bit);
+        if cmp(&num, &d) != Ordering::Less {
+            sub_assign(&mut num, &d);
+            q |= 1 << bit;
+        }
+    }
+    // 割り切れない場合は最下位ビットを立てて丸めに反映する
+    let sticky = !num.is_empty() as u128;
+    round_pack(false, 127 - shift + bin_exp, q | sticky)
+}
+
+// 整数を拡張倍精度のビット表現に変換する（64ビットの仮数部に収まるため誤差はない）
+pub fn from_i64(val: i64) -> u128 {
+    round_pack(val < 0, 63, (val.unsigned_abs() as u128) << 64)
+}
+
+pub fn from_u64(val: u64) -> u128 {
+    round_pack(false, 63, (val as u128) << 64)
+}
+
+pub fn is_zero(bits: u128) -> bool {
+    bits & !SIGN_BIT == 0
+}
+
+// 以下の四則演算はx87の既定の丸め（最近接偶数）で結果を拡張倍精度に丸める
+
+pub fn add(a: u128, b: u128) -> u128 {
+    match (unpack(a), unpack(b)) {
+        (Class::NaN, _) => a | QUIET_BIT,
+        (_, Class::NaN) => b | QUIET_BIT,
+        (Class::Inf(s), Class::Inf(t)) if s != t => NAN,
+        (Class::Inf(_), _) => a,
+        (_, Class::Inf(_)) => b,
+        (Class::Zero(s), Class::Zero(t)) => round_pack(s && t, 0, 0),
+        (Class::Zero(_), _) => b,
+        (_, Class::Zero(_)) => a,
+        (Class::Finite(x), Class::Finite(y)) => {
+            // 指数の大きい方に揃え、仮数部の下に62ビットの余裕を持たせて加減算する
+            let (x, y) = if (x.exp, x.sig) >= (y.exp, y.sig) {
+                (x, y)
+            } else {
+                (y, x)
+            };
+            let big = (x.sig as u128) << 62;
+            let small = shr_sticky((y.sig as u128) << 62, (x.exp - y.exp) as u32);
+            if x.sign == y.sign {
+                round_pack(x.sign, x.exp + 2, big + small)
+            } else if big == small {
+                0
+            } else {
+                round_pack(x.sign, x.exp + 2, big - small)
+            }
+        }
+    }
+}
+
+pub fn sub(a: u128, b: u128) -> u128 {
+    add(a, b ^ SIGN_BIT)
+}
+
+pub fn mul(a: u128, b: u128) -> u128 {
+    let sign = (a ^ b) & SIGN_BIT != 0;
+    match (unpack(a), unpack(b)) {
+        (Class::NaN, _) => a | QUIET_BIT,
+        (_, Class::NaN) => b | QUIET_BIT,
+        (Class::Inf(_), Class::Zero(_)) | (Class::Zero(_), Class::Inf(_)) => NAN,
+        (Class::Inf(_), _) | (_, Class::Inf(_)) => infinity(sign),
+        (Class::Zero(_), _) | (_, Class::Zero(_)) => round_pack(sign, 0, 0),
+        (Class::Finite(x), Class::Finite(y)) => {
+            round_pack(sign, x.exp + y.exp + 1, x.sig as u128 * y.sig as u128)
+        }
+    }
+}
+
+pub fn div(a: u128, b: u128) -> u128 {
+    let sign = (a ^ b) & SIGN_BIT != 0;
+    match (unpack(a), unpack(b)) {
+        (Class::NaN, _) => a | QUIET_BIT,
+        (_, Class::NaN) => b | QUIET_BIT,
+        (Class::Inf(_), Class::Inf(_)) | (Class::Zero(_), Class::Zero(_)) => NAN,
+        (Class::Inf(_), _) | (_, Class::Zero(_)) => infinity(sign),
+        (Class::Zero(_), _) | (_, Class::Inf(_)) => round_pack(sign, 0, 0),
+        (Class::Finite(x), Class::Finite(y)) => {
+            // 商の上位64ビットと、余りから求めた下位64ビットをつなげ、割り切れなければ最下位ビットを立てる
+            let den = y.sig as u128;
+            let num = (x.sig as u128) << 63;
+            let (hi, rem) = (num / den, num % den);
+            let (lo, rem) = ((rem << 64) / den, (rem << 64) % den);
+            round_pack(sign, x.exp - y.exp, hi << 64 | lo | (rem != 0) as u128)
+        }
+    }
+}
+
+// 無効な演算の結果（x87の既定のNaN）
+const NAN: u128 = SIGN_BIT | (EXP_MAX as u128) << 64 | INTEGER_BIT | QUIET_BIT;
+const QUIET_BIT: u128 = 1 << 62;
+
+fn infinity(sign: bool) -> u128 {
+    let sign_bit = if sign { SIGN_BIT } else { 0 };
+    sign_bit | (EXP_MAX as u128) << 64 | INTEGER_BIT
+}
+
+// 有限の値は sig × 2^(exp - 63) を表し、sigは整数ビットが立つように正規化する
+#[derive(Clone, Copy)]
+struct Unpacked {
+    sign: bool,
+    exp: i32,
+    sig: u64,
+}
+
+enum Class {
+    Zero(bool),
+    Inf(bool),
+    NaN,
+    Finite(Unpacked),
+}
+
+fn unpack(bits: u128) -> Class {
+    let sign = bits & SIGN_BIT != 0;
+    let biased = ((bits >> 64) & EXP_MAX as u128) as i32;
+    let sig = bits as u64;
+    if biased == EXP_MAX {
+        return if sig << 1 == 0 {
+            Class::Inf(sign)
+        } else {
+            Class::NaN
+        };
+    }
+    if sig == 0 {
+        return Class::Zero(sign);
+    }
+    // 非正規化数の指数は最小の正規化数と同じ
+    let shift = sig.leading_zeros() as i32;
+    Class::Finite(Unpacked {
+        sign,
+        exp: biased.max(1) - EXP_BIAS - shift,
+        sig: sig << shift,
+    })
+}
+
+// sig × 2^(exp - 127) の値を最近接偶数に丸めて拡張倍精度のビット表現にする
+fn round_pack(sign: bool, exp: i32, sig: u128) -> u128 {
+    let sign_bit = if sign { SIGN_BIT } else { 0 };
+    if sig == 0 {
+        return sign_bit;
+    }
+    let shift = sig.leading_zeros();
+    let mut sig = sig << shift;
+    let mut biased = exp - shift as i32 + EXP_BIAS;
+    if biased <= 0 {
+        // 非正規化数は最小の指数に合わせて仮数部を右にずらす
+        sig = shr_sticky(sig, (1 - biased) as u32);
+        biased = 0;
+    }
+    let mut mant = (sig >> 64) as u64;
+    let rest = sig as u64;
+    let half = 1 << 63;
+    if rest > half || (rest == half && mant & 1 == 1) {
+        let (rounded, overflow) = mant.overflowing_add(1);
+        mant = rounded;
+        if overflow {
+            mant = 1 << 63;
+            biased += 1;
+        }
+    }
+    // 非正規化数が丸めによって最小の正規化数になった
+    if biased == 0 && mant >> 63 == 1 {
+        biased = 1;
+    }
+    if biased >= EXP_MAX {
+        return infinity(sign);
+    }
+    sign_bit | (biased as u128) << 64 | mant as u128
+}
+
+// 右シフトで失われるビットがあれば最下位ビットを立てる
+fn shr_sticky(n: u128, shift: u32) -> u128 {
+    if shift == 0 {
+        n
+    } else if shift >= 128 {
+        (n != 0) as u128
+    } else {
+        n >> shift | (n & ((1 << shift) - 1) != 0) as u128
+    }
+}
+
+// 以下は32ビットの桁を下位から並べた多倍長の自然数の演算（上位の0の桁は持たない）
+
+fn mul_small(n: &mut Vec<u32>, m: u32, add: u32) {
+    let mut carry = add as u64;
+    for limb in n.iter_mut() {
+        let v = *limb as u64 * m as u64 + carry;
+        *limb = v as u32;
+        carry = v >> 32;
+    }
+    if carry != 0 {
+        n.push(carry as u32);
+    }
+}
+
+fn shl(n: &[u32], bits: usize) -> Vec<u32> {
+    if n.is_empty() {
+        return Vec::new();
+    }
+    let (limbs, bits) = (bits / 32, bits % 32);
+    let mut result = vec![0; limbs];
+    let mut carry = 0;
+    for &limb in n {
+        let v = (limb as u64) << bits | carry;
+        result.push(v as u32);
+        carry = v >> 32;
+    }
+    if carry != 0 {
+        result.push(carry as u32);
+    }
+    result
+}
+
+fn bit_len(n: &[u32]) -> usize {
+    n.last()
+        .map_or(0, |top| n.len() * 32 - top.leading_zeros() as usize)
+}
+
+fn cmp(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+// a >= b であること
+fn sub_assign(a: &mut Vec<u32>, b: &[u32]) {
+    let mut borrow = 0;
+    for (i, limb) in a.iter_mut().enumerate() {
+        let v = *limb as i64 - b.get(i).copied().unwrap_or(0) as i64 - borrow;
+        *limb = v as u32;
+        borrow = (v < 0) as i64;
+    }
+    while a.last() == Some(&0) {
+        a.pop();
+    }
+}
@@ -1,18 +1,309 @@
+use crate::charset::Charset;
 use crate::errors::CompileError;
+use crate::float80;
 use crate::token::{KEYWORDS, PUNCTUATORS};
-use crate::token::{Token, TokenKind};
+use crate::token::{Standard, Token, TokenKind};
+use crate::types::TypeKind;
 
-pub struct Lexer {}
+// 字句解析中に変化する状態
+struct LexState {
+    bool_keywords: bool,            // bool, true, falseを予約語として扱うか
+    align_keywords: bool,           // alignas, alignofを予約語として扱うか
+    va_macros: bool,                // va_listなど<stdarg.h>の識別子を組み込みの名前に置き換えるか
+    pack: Option<usize>,            // #pragma pack で指定された現在の値
+    pack_stack: Vec<Option<usize>>, // #pragma pack(push) で退避された値
+}
+
+pub struct Lexer {
+    exec_charset: Charset, // 文字定数・文字列リテラルの実行文字集合
+    unsigned_char: bool,   // 文字定数の値をunsigned charとして扱うか
+    std: Standard,         // コンパイル対象の規格
+}
 
 impl Default for Lexer {
     fn default() -> Self {
-        Self::new()
+        Self::new(Charset::default(), false, Standard::default())
     }
 }
 
 impl Lexer {
-    pub fn new() -> Self {
-        Lexer {}
+    pub fn new(exec_charset: Charset, unsigned_char: bool, std: Standard) -> Self {
+        Lexer {
+            exec_charset,
+            unsigned_char,
+            std,
+        }
+    }
+
+    // 行頭の#から始まるプリプロセッサ指令を処理する
+    // プリプロセッサは未実装のため、標準ヘッダのうち識別子の定義だけで済むものに限って受け付ける
+    // 構文解析に影響する指令はトークンとして返す
+    fn directive(
+        &self,
+        line: &str,
+        span: (usize, usize),
+        state: &mut LexState,
+    ) -> Result<Option<TokenKind>, CompileError> {
+        let body = line.trim_start_matches('#').trim_start();
+        if let Some(args) = body.strip_prefix("pragma") {
+            return Self::pragma(args, span, state);
+        }
+        let words = body
+            .split(|c: char| c.is_whitespace() || matches!(c, '<' | '>' | '"'))
+            .filter(|w| !w.is_empty())
+            .collect::<Vec<_>>();
+        match words.as_slice() {
+            [] => Ok(None), // 空の指令
+            ["include", "stdbool.h"] => {
+                state.bool_keywords = true;
+                Ok(None)
+            }
+            ["include", "stdalign.h"] => {
+                state.align_keywords = true;
+                Ok(None)
+            }
+            ["include", "stdarg.h"] => {
+                state.va_macros = true;
+                Ok(None)
+            }
+            _ => Err(CompileError::InvalidDirective {
+                msg: format!("未対応のプリプロセッサ指令です: {} at {:?}", line, span),
+            }),
+        }
+    }
+
+    // #pragma 指令を処理する
+    // pack以外の処理系定義のプラグマは無視する
+    fn pragma(
+        args: &str,
+        span: (usize, usize),
+        state: &mut LexState,
+    ) -> Result<Option<TokenKind>, CompileError> {
+        let args = args.split_whitespace().collect::<String>();
+        let Some(params) = args
+            .strip_prefix("pack(")
+            .and_then(|rest| rest.strip_suffix(')'))
+        else {
+            return Ok(None);
+        };
+        let invalid = || CompileError::InvalidDirective {
+            msg: format!("無効な #pragma pack です: {} at {:?}", args, span),
+        };
+        // 値は2の冪で、構造体メンバーのアラインメントの上限となる
+        let parse_pack = |n: &str| match n.parse::<usize>() {
+            Ok(n) if n.is_power_of_two() && n <= 16 => Ok(Some(n)),
+            _ => Err(invalid()),
+        };
+        match params.split(',').collect::<Vec<_>>().as_slice() {
+            [""] => state.pack = None,
+            ["push"] => state.pack_stack.push(state.pack),
+            ["push", n] => {
+                state.pack_stack.push(state.pack);
+                state.pack = parse_pack(n)?;
+            }
+            ["pop"] => state.pack = state.pack_stack.pop().ok_or_else(invalid)?,
+            [n] => state.pack = parse_pack(n)?,
+            _ => return Err(invalid()),
+        }
+        Ok(Some(TokenKind::PragmaPack(state.pack)))
+    }
+
+    // 文字定数中のエスケープシーケンスを読み取り、そのバイト値を返す
+    // posはバックスラッシュの次の文字を指している
+    fn read_escape(chars: &[char], pos: &mut usize) -> Result<u8, CompileError> {
+        let c = *chars.get(*pos).ok_or(CompileError::UnexpectedEof)?;
+        *pos += 1;
+        let val = match c {
+            'n' => b'\n',
+            't' => b'\t',
+            'r' => b'\r',
+            'a' => 0x07,
+            'b' => 0x08,
+            'f' => 0x0c,
+            'v' => 0x0b,
+            '\\' | '\'' | '"' | '?' => c as u8,
+            '0'..='7' => {
+                // 8進エスケープ（最大3桁）
+                let mut val = c.to_digit(8).unwrap();
+                for _ in 0..2 {
+                    match chars.get(*pos).and_then(|c| c.to_digit(8)) {
+                        Some(d) => {
+                            val = val * 8 + d;
+                            *pos += 1;
+                        }
+                        None => break,
+                    }
+                }
+                val as u8
+            }
+            'x' => {
+                // 16進エスケープ
+                let start = *pos;
+                let mut val: u32 = 0;
+                while let Some(d) = chars.get(*pos).and_then(|c| c.to_digit(16)) {
+                    val = (val << 4) | d;
+                    *pos += 1;
+                }
+                if *pos == start {
+                    return Err(CompileError::MissingToken {
+                        found: "\\x".to_string(),
+                        span: (start - 2, start),
+                    });
+                }
+                val as u8
+            }
+            _ => {
+                return Err(CompileError::MissingToken {
+                    found: format!("\\{}", c),
+                    span: (*pos - 2, *pos),
+                });
+            }
+        };
+        Ok(val)
+    }
+
+    // 浮動小数点定数を読み取る（C11 6.4.4.2）
+    // 値はf64と80ビット拡張倍精度のビット表現で返す（long doubleは拡張倍精度の精度で変換する）
+    // 小数点も指数部もない場合は整数定数なので、位置を進めずにNoneを返す
+    fn read_float(
+        chars: &[char],
+        pos: &mut usize,
+    ) -> Result<Option<(f64, u128, TypeKind)>, CompileError> {
+        let start = *pos;
+        let hex = chars[start] == '0' && matches!(chars.get(start + 1), Some('x' | 'X'));
+        let radix = if hex { 16 } else { 10 };
+        let is_digit = |c: &char| c.is_digit(radix);
+        let mantissa_start = if hex { start + 2 } else { start };
+        let mut i = mantissa_start;
+        while chars.get(i).is_some_and(is_digit) {
+            i += 1;
+        }
+        let has_dot = chars.get(i) == Some(&'.');
+        if has_dot {
+            i += 1;
+            while chars.get(i).is_some_and(is_digit) {
+                i += 1;
+            }
+        }
+        let mantissa_end = i;
+        // 指数部（16進数では必須で、2の累乗を表す）
+        let exp_chars = if hex { ['p', 'P'] } else { ['e', 'E'] };
+        let mut exp = None;
+        if chars.get(i).is_some_and(|c| exp_chars.contains(c)) {
+            let mut j = i + 1;
+            if matches!(chars.get(j), Some('+' | '-')) {
+                j += 1;
+            }
+            let digits_start = j;
+            while chars.get(j).is_some_and(char::is_ascii_digit) {
+                j += 1;
+            }
+            if j > digits_start {
+                let exp_str = chars[i + 1..j].iter().collect::<String>();
+                exp = Some(exp_str.parse::<i32>().unwrap_or(i32::MAX));
+                i = j;
+            }
+        }
+        if !has_dot && exp.is_none() {
+            return Ok(None);
+        }
+        let invalid = || CompileError::MissingToken {
+            found: chars[start..i].iter().collect(),
+            span: (start, i),
+        };
+        let val = if hex {
+            let exp = exp.ok_or_else(invalid)?;
+            let mut mantissa = 0.0;
+            let mut scale = 0;
+            let mut after_dot = false;
+            for c in &chars[mantissa_start..mantissa_end] {
+                match c.to_digit(16) {
+                    Some(d) => {
+                        mantissa = mantissa * 16.0 + d as f64;
+                        if after_dot {
+                            scale -= 4;
+                        }
+                    }
+                    None => after_dot = true,
+                }
+            }
+            mantissa * 2f64.powi(exp.saturating_add(scale))
+        } else {
+            chars[start..i]
+                .iter()
+                .collect::<String>()
+                .parse::<f64>()
+                .map_err(|_| invalid())?
+        };
+        *pos = i;
+        // 接尾辞がなければdouble、fならfloat、lならlong double
+        let ty = match chars.get(*pos) {
+            Some('f' | 'F') => {
+                *pos += 1;
+                TypeKind::Float
+            }
+            Some('l' | 'L') => {
+                *pos += 1;
+                TypeKind::LongDouble
+            }
+            _ => TypeKind::Double,
+        };
+        let ext = if ty == TypeKind::LongDouble {
+            // 仮数部の数字を整数として読み、小数点以下の桁数だけ指数をずらす
+            let mantissa = &chars[mantissa_start..mantissa_end];
+            let digits = mantissa
+                .iter()
+                .filter_map(|c| c.to_digit(radix))
+                .collect::<Vec<_>>();
+            let frac_digits = mantissa
+                .iter()
+                .skip_while(|c| **c != '.')
+                .filter(|c| c.is_digit(radix))
+                .count() as i32;
+            let scale = if hex { 4 * frac_digits } else { frac_digits };
+            let exp = exp.unwrap_or(0).saturating_sub(scale);
+            float80::from_digits(&digits, radix, exp)
+        } else {
+            float80::from_f64(val)
+        };
+        Ok(Some((val, ext, ty)))
+    }
+
+    // 整数定数の接尾辞を読み取り、C11 6.4.4.1に従って定数の型を決定する
+    fn read_int_suffix(
+        chars: &[char],
+        pos: &mut usize,
+        val: u64,
+        decimal: bool,
+    ) -> Result<TypeKind, CompileError> {
+        let start = *pos;
+        while *pos < chars.len() && matches!(chars[*pos], 'u' | 'U' | 'l' | 'L') {
+            *pos += 1;
+        }
+        let suffix = chars[start..*pos].iter().collect::<String>().to_lowercase();
+        let (unsigned, long) = match suffix.as_str() {
+            "" => (false, false),
+            "u" => (true, false),
+            "l" | "ll" => (false, true),
+            "ul" | "lu" | "ull" | "llu" => (true, true),
+            _ => {
+                return Err(CompileError::MissingToken {
+                    found: suffix,
+                    span: (start, *pos),
+                });
+            }
+        };
+        // 10進定数は符号付きの型のみ、8進・16進定数は符号なしの型も候補になる
+        let kind = if !unsigned && !long && val <= i32::MAX as u64 {
+            TypeKind::Int
+        } else if !long && val <= u32::MAX as u64 && (unsigned || !decimal) {
+            TypeKind::UInt
+        } else if !unsigned && val <= i64::MAX as u64 {
+            TypeKind::Long
+        } else {
+            TypeKind::ULong
+        };
+        Ok(kind)
     }
 
     pub fn tokenize(&self, input: &str) -> Result<Vec<Token>, CompileError> {
@@ -23,10 +314,36 @@ impl Lexer {
         let mut tokens = Vec::new();
         let chars = input.chars().collect::<Vec<char>>();
         let mut pos = 0;
+        let mut state = LexState {
+            bool_keywords: self.std >= Standard::C23,
+            align_keywords: self.std >= Standard::C23,
+            va_macros: false,
+            pack: None,
+            pack_stack: Vec::new(),
+        };
 
         while pos < chars.len() {
             let c = chars[pos];
 
+            // プリプロセッサ指令（行頭の#から行末まで）
+            if c == '#'
+                && chars[..pos]
+                    .iter()
+                    .rev()
+                    .take_while(|c| **c != '\n')
+                    .all(|c| matches!(c, ' ' | '\t' | '\r'))
+            {
+                let start = pos;
+                while pos < chars.len() && chars[pos] != '\n' {
+                    pos += 1;
+                }
+                let line = chars[start..pos].iter().collect::<String>();
+                if let Some(kind) = self.directive(&line, (start, pos), &mut state)? {
+                    tokens.push(Token::new(kind, (start, pos)));
+                }
+                continue;
+            }
+
             // 空白文字をスキップ
             if matches!(c, ' ' | '\t' | '\n' | '\r') {
                 pos += 1;
@@ -61,6 +378,20 @@ impl Lexer {
                 continue;
             }
 
+            // 浮動小数点数トークン（"."で始まるものがあるため演算子より先に調べる）
+            if c.is_ascii_digit()
+                || c == '.' && chars.get(pos + 1).is_some_and(char::is_ascii_digit)
+            {
+                let start = pos;
+                if let Some((val, ext, ty)) = Self::read_float(&chars, &mut pos)? {
+                    tokens.push(Token::new(
+                        TokenKind::FloatNumber(val.to_bits(), ext, Box::new(ty)),
+                        (start, pos),
+                    ));
+                    continue;
+                }
+            }
+
             // 演算子トークン
             let mut matched = false;
             for symbol in &sorted_punctuators {
@@ -86,27 +417,74 @@ impl Lexer {
             if c == '"' {
                 pos += 1; // 開始の"をスキップ
                 let mut str_lit = String::new();
+                let mut bytes = Vec::new();
                 while pos < chars.len() {
                     let next_c = chars[pos];
                     if next_c == '"' {
                         pos += 1; // 終了の"をスキップ
                         break;
+                    } else if next_c == '\\' {
+                        // エスケープシーケンスは表記のまま残し、値はバイト列に加える
+                        let start = pos;
+                        pos += 1;
+                        bytes.push(Self::read_escape(&chars, &mut pos)?);
+                        str_lit.extend(&chars[start..pos]);
                     } else {
+                        // 実行文字集合で表現できない文字はここでエラーになる
+                        bytes.extend(self.exec_charset.encode(&next_c.to_string())?);
                         str_lit.push(next_c);
                         pos += 1;
                     }
                 }
                 tokens.push(Token::new(
-                    TokenKind::String(str_lit.clone()),
-                    (pos - str_lit.len() - 2, pos),
+                    TokenKind::String(str_lit.clone(), bytes),
+                    (pos - str_lit.chars().count() - 2, pos),
+                ));
+                continue;
+            }
+
+            // 文字定数トークン
+            if c == '\'' {
+                let start = pos;
+                pos += 1; // 開始の'をスキップ
+                let mut bytes = Vec::new();
+                while pos < chars.len() && chars[pos] != '\'' {
+                    if chars[pos] == '\\' {
+                        pos += 1;
+                        bytes.push(Self::read_escape(&chars, &mut pos)?);
+                    } else {
+                        // 実行文字集合に変換した値を用いる
+                        bytes.extend(self.exec_charset.encode(&chars[pos].to_string())?);
+                        pos += 1;
+                    }
+                }
+                if pos >= chars.len() || bytes.is_empty() {
+                    return Err(CompileError::MissingToken {
+                        found: chars[start..pos.min(chars.len())].iter().collect(),
+                        span: (start, pos),
+                    });
+                }
+                pos += 1; // 終了の'をスキップ
+                // 1バイトの場合はcharの値とし、複数バイトの場合はGCCと同様にint値にまとめる
+                let val = if bytes.len() == 1 && self.unsigned_char {
+                    bytes[0] as i64
+                } else if bytes.len() == 1 {
+                    bytes[0] as i8 as i64
+                } else {
+                    bytes.iter().fold(0i32, |acc, &b| (acc << 8) | b as i32) as i64
+                };
+                tokens.push(Token::new(
+                    TokenKind::Number(val, Box::new(TypeKind::Int)),
+                    (start, pos),
                 ));
                 continue;
             }
 
             // 数字トークン
             if c.is_ascii_digit() {
+                let start = pos;
                 let mut num_str = String::new();
-                if c != '0' {
+                let radix = if c != '0' {
                     // decimal constant
                     num_str.push(c);
                     pos += 1;
@@ -119,12 +497,7 @@ impl Lexer {
                             break;
                         }
                     }
-                    let val = num_str.parse::<i64>().unwrap();
-                    tokens.push(Token::new(
-                        TokenKind::Number(val),
-                        (pos - num_str.len(), pos),
-                    ));
-                    continue;
+                    10
                 } else {
                     // hexadecimal constant or octal constant
                     if pos < chars.len() - 1 && (chars[pos + 1] == 'x' || chars[pos + 1] == 'X') {
@@ -139,15 +512,7 @@ impl Lexer {
                                 break;
                             }
                         }
-                        if num_str.is_empty() {
-                            num_str.push('0');
-                        }
-                        let val = i64::from_str_radix(&num_str, 16).unwrap();
-                        tokens.push(Token::new(
-                            TokenKind::Number(val),
-                            (pos - num_str.len() - 2, pos),
-                        ));
-                        continue;
+                        16
                     } else {
                         // octal constant
                         pos += 1; // skip '0'
@@ -160,17 +525,24 @@ impl Lexer {
                                 break;
                             }
                         }
-                        if num_str.is_empty() {
-                            num_str.push('0');
-                        }
-                        let val = i64::from_str_radix(&num_str, 8).unwrap();
-                        tokens.push(Token::new(
-                            TokenKind::Number(val),
-                            (pos - num_str.len() - 1, pos),
-                        ));
-                        continue;
+                        8
                     }
+                };
+                if num_str.is_empty() {
+                    num_str.push('0');
                 }
+                let val = u64::from_str_radix(&num_str, radix).map_err(|_| {
+                    CompileError::MissingToken {
+                        found: chars[start..pos].iter().collect(),
+                        span: (start, pos),
+                    }
+                })?;
+                let ty = Self::read_int_suffix(&chars, &mut pos, val, radix == 10)?;
+                tokens.push(Token::new(
+                    TokenKind::Number(val as i64, Box::new(ty)),
+                    (start, pos),
+                ));
+                continue;
             }
 
             // 識別子トークン
@@ -186,6 +558,65 @@ impl Lexer {
                         break;
                     }
                 }
+                if state.bool_keywords && matches!(ident.as_str(), "true" | "false") {
+                    // C23では_Bool型の定数、<stdbool.h>ではintの1, 0に展開されるマクロ
+                    let ty = if self.std >= Standard::C23 {
+                        TypeKind::Bool
+                    } else {
+                        TypeKind::Int
+                    };
+                    tokens.push(Token::new(
+                        TokenKind::Number((ident == "true") as i64, Box::new(ty)),
+                        (pos - ident.len(), pos),
+                    ));
+                    continue;
+                }
+                if state.bool_keywords && ident == "bool" {
+                    // boolは_Boolの別名
+                    tokens.push(Token::new(
+                        TokenKind::Keyword("_Bool".to_string()),
+                        (pos - ident.len(), pos),
+                    ));
+                    continue;
+                }
+                if state.align_keywords && matches!(ident.as_str(), "alignas" | "alignof") {
+                    // C23および<stdalign.h>では_Alignas, _Alignofの別名
+                    let keyword = if ident == "alignas" {
+                        "_Alignas"
+                    } else {
+                        "_Alignof"
+                    };
+                    tokens.push(Token::new(
+                        TokenKind::Keyword(keyword.to_string()),
+                        (pos - ident.len(), pos),
+                    ));
+                    continue;
+                }
+                if state.va_macros
+                    && matches!(
+                        ident.as_str(),
+                        "va_list" | "va_start" | "va_arg" | "va_copy" | "va_end"
+                    )
+                {
+                    // <stdarg.h>ではva_listは__builtin_va_list型のtypedef名、
+                    // その他は組み込み関数に展開されるマクロ
+                    let name = format!("__builtin_{}", ident);
+                    let kind = if ident == "va_list" {
+                        TokenKind::Identifier(name)
+                    } else {
+                        TokenKind::Keyword(name)
+                    };
+                    tokens.push(Token::new(kind, (pos - ident.len(), pos)));
+                    continue;
+                }
+                if self.std >= Standard::C23 && ident == "static_assert" {
+                    // C23では_Static_assertの別名
+                    tokens.push(Token::new(
+                        TokenKind::Keyword("_Static_assert".to_string()),
+                        (pos - ident.len(), pos),
+                    ));
+                    continue;
+                }
                 if KEYWORDS.contains(&ident.as_str()) {
                     // 予約語はKeywordトークンとして扱う
                     tokens.push(Token::new(
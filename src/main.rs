@@ -3,7 +3,9 @@ use clap_derive::Parser;
 
 pub mod asm_builder;
 pub mod ast;
+pub mod charset;
 pub mod errors;
+pub mod float80;
 pub mod lexer;
 pub mod node;
 pub mod token;
@@ -11,9 +13,16 @@ pub mod types;
 pub mod x86;
 
 use crate::ast::Ast;
+use crate::charset::Charset;
 use crate::lexer::Lexer;
+use crate::token::Standard;
 use crate::x86::Generator;
 
+// GCC形式の -fXXX オプションとして受け付けるもの
+// clapでは -f が --file の短縮形になっているため、--XXX に読み替えてから解析する
+// -std= も同様に --std= に読み替える
+const F_OPTIONS: [&str; 3] = ["input-charset", "exec-charset", "unsigned-char"];
+
 #[derive(Parser, Debug)]
 struct Args {
     #[arg(short, long)]
@@ -27,13 +36,49 @@ struct Args {
 
     #[arg(short, long, default_value = "")]
     file: String,
+
+    // ソースファイルの文字コード (-finput-charset=)
+    #[arg(long, default_value = "UTF-8")]
+    input_charset: Charset,
+
+    // 文字定数・文字列リテラルの文字コード (-fexec-charset=)
+    #[arg(long, default_value = "UTF-8")]
+    exec_charset: Charset,
+
+    // 符号指定のないcharをunsigned charとして扱う (-funsigned-char)
+    #[arg(long)]
+    unsigned_char: bool,
+
+    // コンパイル対象の規格 (-std=c11, -std=c23 など)
+    #[arg(long, default_value = "c17")]
+    std: Standard,
+}
+
+// -fXXX[=VALUE], -std=VALUE 形式の引数を --XXX[=VALUE], --std=VALUE に読み替える
+fn normalize_args(args: impl Iterator<Item = String>) -> Vec<String> {
+    args.map(|arg| {
+        if arg.starts_with("-std=") {
+            return format!("-{}", arg);
+        }
+        if let Some(opt) = arg.strip_prefix("-f") {
+            let name = opt.split('=').next().unwrap_or_default();
+            if F_OPTIONS.contains(&name) {
+                return format!("--{}", opt);
+            }
+        }
+        arg
+    })
+    .collect()
 }
 
 fn main() {
-    let mut args = Args::parse();
+    let mut args = Args::parse_from(normalize_args(std::env::args()));
 
     if !args.file.is_empty() {
-        match std::fs::read_to_string(&args.file) {
+        let content = std::fs::read(&args.file)
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| args.input_charset.decode(&bytes).map_err(|e| e.to_string()));
+        match content {
             Ok(content) => {
                 if !args.input.is_empty() {
                     eprintln!("Warning: Both input string and file provided. Using file content.");
@@ -47,7 +92,7 @@ fn main() {
         }
     }
 
-    let lexer = Lexer::default();
+    let lexer = Lexer::new(args.exec_charset, args.unsigned_char, args.std);
     let tokens = match lexer.tokenize(&args.input) {
         Ok(tokens) => tokens,
         Err(e) => {
@@ -55,13 +100,13 @@ fn main() {
             return;
         }
     };
-    let mut ast = Ast::new(&tokens);
+    let mut ast = Ast::new(&tokens, args.unsigned_char);
     if let Err(e) = ast.translation_unit() {
         eprintln!("Parser Error: {}", e);
         return;
     }
 
-    let mut generator = Generator::default();
+    let mut generator = Generator::new(args.exec_charset);
     generator.gen_asm(&ast);
 
     if args.debug {
@@ -1,7 +1,81 @@
 use core::{fmt, str};
 
 use crate::errors::CompileError;
-use crate::types::{Type, TypeKind};
+use crate::float80;
+use crate::types::{BitField, Type, TypeKind};
+
+// 整数変換の順位（C11 6.3.1.1）
+// long longはlongと同じ型として扱うため、longの順位に含まれる
+fn integer_rank(ty: &Type) -> usize {
+    match ty.kind {
+        TypeKind::Bool => 1,
+        TypeKind::Char { .. } | TypeKind::SChar | TypeKind::UChar => 2,
+        TypeKind::Short | TypeKind::UShort => 3,
+        TypeKind::Int | TypeKind::UInt => 4,
+        TypeKind::Long | TypeKind::ULong => 5,
+        _ => 0,
+    }
+}
+
+// 浮動小数点型の順位（浮動小数点型でない場合は0）
+fn floating_rank(ty: &Type) -> usize {
+    match ty.kind {
+        TypeKind::Float => 1,
+        TypeKind::Double => 2,
+        TypeKind::LongDouble => 3,
+        _ => 0,
+    }
+}
+
+// 通常の算術型変換後の型を返す（C11 6.3.1.8）
+// 両オペランドは整数拡張済みであること
+fn common_type(lhs: &Type, rhs: &Type) -> Type {
+    let kind = if lhs.is_floating_point() || rhs.is_floating_point() {
+        // 浮動小数点型があれば、順位の高い方の浮動小数点型に合わせる
+        if floating_rank(lhs) >= floating_rank(rhs) {
+            &lhs.kind
+        } else {
+            &rhs.kind
+        }
+    } else if lhs.is_unsigned() == rhs.is_unsigned() {
+        // 符号の有無が同じ場合は順位の高い方に合わせる
+        if integer_rank(lhs) >= integer_rank(rhs) {
+            &lhs.kind
+        } else {
+            &rhs.kind
+        }
+    } else {
+        let (unsigned, signed) = if lhs.is_unsigned() {
+            (lhs, rhs)
+        } else {
+            (rhs, lhs)
+        };
+        if integer_rank(unsigned) >= integer_rank(signed) {
+            // 符号なし整数型の順位が高いか等しい場合は符号なし整数型に合わせる
+            &unsigned.kind
+        } else if signed.size_of() > unsigned.size_of() {
+            // 符号付き整数型が符号なし整数型の全ての値を表現できる場合は符号付き整数型に合わせる
+            &signed.kind
+        } else {
+            return Type::from(&signed.kind.to_unsigned(), false);
+        }
+    };
+    Type::from(kind, false)
+}
+
+// 式を指定した型に変換する暗黙の型変換ノードで包む
+// すでに同じ型の場合は何もしない
+fn convert(node: &mut Option<Box<Node>>, ty: &Type) {
+    if let Some(n) = node.take() {
+        if n.ty.as_ref().is_some_and(|t| t.kind == ty.kind) {
+            *node = Some(n);
+            return;
+        }
+        let mut cast = Node::new_unary(NodeKind::Cast, Some(n));
+        cast.ty = Some(Box::new(ty.clone()));
+        *node = Some(Box::new(cast));
+    }
+}
 
 #[derive(PartialEq, Eq, Clone, Debug)]
 pub enum NodeKind {
@@ -16,6 +90,7 @@ pub enum NodeKind {
     BitXor,       // ^
     BitOr,        // |
     BitNot,       // ~
+    Neg,          // 浮動小数点数の符号反転（0からの減算では-0.0を表せないため）
     LogicalNot,   // !
     LogicalAnd,   // &&
     LogicalOr,    // ||
@@ -40,6 +115,7 @@ pub enum NodeKind {
     PostDec,      // post--
     Addr,         // &
     Deref,        // *
+    Cast,         // (type) expr（型はノードのtyに設定される）
     If {
         cond: Option<Box<Node>>,
         then: Option<Box<Node>>,
@@ -68,34 +144,51 @@ pub enum NodeKind {
         body: Vec<Box<Node>>,
     }, // {}
     Call {
-        name: String,
         args: Vec<Box<Node>>,
-    }, // 関数呼び出し
+        ret_buf: Option<usize>,
+    }, // 関数呼び出し（lhsは関数指示子もしくは関数へのポインタの式、ret_bufは構造体・共用体の戻り値を受け取るローカル変数のオフセット）
     Label {
         name: String,
     }, // ラベル
     Goto {
         name: String,
     }, // goto
-    Break,        // break
-    Continue,     // continue
+    Break,    // break
+    Continue, // continue
     Var {
         name: String,
         offset: usize,
         is_local: bool,
     }, // 変数
+    Member {
+        name: String,
+        offset: usize,
+        bit_field: Option<BitField>,
+    }, // 構造体・共用体のメンバー（lhsは構造体・共用体の式、ビットフィールドのoffsetは記憶単位の位置）
     Identifier {
         name: String,
     }, // 識別子（変数名など）
-    Return,       // return
+    Return, // return
     Number {
         val: i64,
     }, // 整数
+    FloatNumber {
+        bits: u64,
+        ext: u128,
+    }, // 浮動小数点数（f64と80ビット拡張倍精度のビット表現）
     String {
         val: String,
         index: i64,
     }, // 文字列リテラル
-    Nop,          // 空命令
+    ZeroFill, // lhsのオブジェクト全体を0で埋める
+    VlaAlloc {
+        offset: usize,
+    }, // lhsのバイト数の領域をスタックに確保し、そのアドレスをoffsetの位置のローカル変数に格納する
+    VaStart, // lhsのva_listを可変長引数の先頭を指すように初期化する
+    VaArg {
+        temp: Option<usize>,
+    }, // lhsのva_listから次の引数を取り出し、そのアドレスを返す（レジスタで渡された構造体はtempの位置のローカル変数にコピーする）
+    Nop, // 空命令
 }
 
 impl str::FromStr for NodeKind {
@@ -140,6 +233,9 @@ impl fmt::Debug for Node {
             NodeKind::Number { val } => {
                 write!(f, ", val: {}", val)?;
             }
+            NodeKind::FloatNumber { bits, .. } => {
+                write!(f, ", val: {}", f64::from_bits(bits))?;
+            }
             NodeKind::Var {
                 ref name,
                 offset,
@@ -151,12 +247,22 @@ impl fmt::Debug for Node {
                     name, offset, is_local
                 )?;
             }
-            NodeKind::Call { ref name, ref args } => {
-                write!(f, ", name: {}, args: {:?}", name, args)?;
+            NodeKind::Call { ref args, .. } => {
+                write!(f, ", args: {:?}", args)?;
             }
             NodeKind::Label { ref name } => {
                 write!(f, ", name: {}", name)?;
             }
+            NodeKind::Member {
+                ref name,
+                offset,
+                bit_field,
+            } => {
+                write!(f, ", name: {}, offset: {}", name, offset)?;
+                if let Some(bit_field) = bit_field {
+                    write!(f, ", bit_field: {:?}", bit_field)?;
+                }
+            }
             _ => {}
         }
         write!(f, " }}")
@@ -198,6 +304,19 @@ impl Node {
         node
     }
 
+    pub fn new_float(val: f64, ext: u128, kind: &TypeKind) -> Self {
+        let mut node = Node::new(
+            NodeKind::FloatNumber {
+                bits: val.to_bits(),
+                ext,
+            },
+            None,
+            None,
+        );
+        node.ty = Some(Box::new(Type::from(kind, false)));
+        node
+    }
+
     pub fn new_var(name: &str, offset: usize, ty: &Type, is_local: bool) -> Self {
         let mut node = Node::new(
             NodeKind::Var {
@@ -212,6 +331,85 @@ impl Node {
         node
     }
 
+    // 型のサイズを表すノードを作成する
+    // 可変長配列のサイズは実行時に求める
+    pub fn new_size(ty: &Type) -> Self {
+        let size_ty = Type::from(&TypeKind::ULong, false);
+        match &ty.kind {
+            // 宣言済みの可変長配列は、宣言時に求めたサイズを使う
+            TypeKind::Vla {
+                size_offset: Some(offset),
+                ..
+            } => Node::new_var("", *offset, &size_ty, true),
+            // 型名中の可変長配列は、その場で要素数の式から求める
+            TypeKind::Vla { base, len, .. } => {
+                let mut node = Node::new(
+                    NodeKind::Mul,
+                    Some(len.clone()),
+                    Some(Box::new(Node::new_size(base))),
+                );
+                node.ty = Some(Box::new(size_ty));
+                node
+            }
+            _ => Node::new_num(ty.size_of() as i64),
+        }
+    }
+
+    // 整数拡張後の型を返す（C11 6.3.1.1）
+    // intより順位の低い整数型と、intより幅の狭いビットフィールドはintに昇格する
+    pub fn promoted_type(&self) -> Option<Type> {
+        let ty = self.ty.as_ref()?;
+        let int = Type::from(&TypeKind::Int, false);
+        let narrow_bit_field = matches!(
+            self.kind,
+            NodeKind::Member {
+                bit_field: Some(BitField { width, .. }),
+                ..
+            } if width < int.size_of() * 8
+        );
+        if ty.is_integer() && (integer_rank(ty) < integer_rank(&int) || narrow_bit_field) {
+            Some(int)
+        } else {
+            let mut ty = (**ty).clone();
+            ty.is_const = false;
+            Some(ty)
+        }
+    }
+
+    // 複合代入演算子で演算を行う型を返す
+    // シフト演算は左辺の整数拡張後の型、それ以外の算術演算は通常の算術型変換後の型で行う
+    pub fn compound_op_type(&self) -> Option<Type> {
+        let lhs_ty = self.lhs.as_ref()?.promoted_type()?;
+        let rhs_ty = self.rhs.as_ref()?.promoted_type()?;
+        match self.kind {
+            NodeKind::ShlAssign | NodeKind::ShrAssign => Some(lhs_ty),
+            _ if lhs_ty.is_scalar() && rhs_ty.is_scalar() => Some(common_type(&lhs_ty, &rhs_ty)),
+            _ => Some(lhs_ty),
+        }
+    }
+
+    // 両オペランドに通常の算術型変換を適用し、変換後の型を返す
+    fn apply_arith_conv(&mut self) -> Type {
+        let ty = common_type(
+            &self.lhs.as_ref().unwrap().promoted_type().unwrap(),
+            &self.rhs.as_ref().unwrap().promoted_type().unwrap(),
+        );
+        convert(&mut self.lhs, &ty);
+        convert(&mut self.rhs, &ty);
+        ty
+    }
+
+    // ビットフィールドのメンバーを表すノードかどうか
+    pub fn is_bit_field(&self) -> bool {
+        matches!(
+            self.kind,
+            NodeKind::Member {
+                bit_field: Some(_),
+                ..
+            }
+        )
+    }
+
     pub fn is_expr(&self) -> bool {
         match self.kind {
             // 値を返さない文
@@ -225,6 +423,8 @@ impl Node {
             | NodeKind::Goto { .. }
             | NodeKind::Label { .. }
             | NodeKind::Return
+            | NodeKind::ZeroFill
+            | NodeKind::VlaAlloc { .. }
             | NodeKind::Nop => false,
             _ => true, // 値を返す式
         }
@@ -236,12 +436,11 @@ impl Node {
     ) -> Result<Option<Box<Node>>, CompileError> {
         if let Some(ty) = &self.ty {
             if ty.is_ptr_or_array() {
-                let base_size = ty.base_type().size_of();
                 // ポインタ加算の場合、右辺をスケーリングする
                 rhs = Some(Box::new(Node::new(
                     NodeKind::Mul,
                     rhs,
-                    Some(Box::new(Node::new_num(base_size as i64))),
+                    Some(Box::new(Node::new_size(ty.base_type()))),
                 )));
             }
             Ok(Some(Box::new(Node::new(
@@ -262,12 +461,11 @@ impl Node {
     ) -> Result<Option<Box<Node>>, CompileError> {
         if let Some(ty) = &self.ty {
             if ty.is_ptr_or_array() {
-                let base_size = ty.base_type().size_of();
                 // ポインタ減算の場合、右辺をスケーリングする
                 rhs = Some(Box::new(Node::new(
                     NodeKind::Mul,
                     rhs,
-                    Some(Box::new(Node::new_num(base_size as i64))),
+                    Some(Box::new(Node::new_size(ty.base_type()))),
                 )));
             }
             Ok(Some(Box::new(Node::new(
@@ -282,6 +480,211 @@ impl Node {
         }
     }
 
+    // 演算を符号なし整数として行うかどうか
+    pub fn is_unsigned_op(&self) -> bool {
+        match self.kind {
+            NodeKind::Eq | NodeKind::Ne | NodeKind::Lt | NodeKind::Le => {
+                // 比較演算はオペランドの型で決まる
+                // 算術型のオペランドは通常の算術型変換により共通の型に変換済み
+                [&self.lhs, &self.rhs].into_iter().any(|operand| {
+                    operand
+                        .as_ref()
+                        .and_then(|n| n.ty.as_ref())
+                        .is_some_and(|ty| ty.is_ptr_like() || ty.is_unsigned())
+                })
+            }
+            NodeKind::AddAssign
+            | NodeKind::SubAssign
+            | NodeKind::MulAssign
+            | NodeKind::DivAssign
+            | NodeKind::RemAssign
+            | NodeKind::ShlAssign
+            | NodeKind::ShrAssign
+            | NodeKind::BitAndAssign
+            | NodeKind::BitOrAssign
+            | NodeKind::BitXorAssign => {
+                // 複合代入演算は演算を行う型で決まる
+                self.compound_op_type().is_some_and(|ty| ty.is_unsigned())
+            }
+            _ => self.ty.as_ref().is_some_and(|ty| ty.is_unsigned()),
+        }
+    }
+
+    // 整数定数式を評価する
+    pub fn eval_const(&self) -> Result<i64, CompileError> {
+        let not_const = || CompileError::InvalidExpression {
+            msg: "整数定数式ではありません".to_string(),
+        };
+        let eval = |node: &Option<Box<Node>>| node.as_ref().ok_or_else(not_const)?.eval_const();
+        let unsigned = self.is_unsigned_op();
+        let val = match &self.kind {
+            NodeKind::Number { val } => *val,
+            NodeKind::Add => eval(&self.lhs)?.wrapping_add(eval(&self.rhs)?),
+            NodeKind::Sub => eval(&self.lhs)?.wrapping_sub(eval(&self.rhs)?),
+            NodeKind::Mul => eval(&self.lhs)?.wrapping_mul(eval(&self.rhs)?),
+            NodeKind::Div | NodeKind::Rem => {
+                let (lhs, rhs) = (eval(&self.lhs)?, eval(&self.rhs)?);
+                if rhs == 0 {
+                    return Err(CompileError::InvalidExpression {
+                        msg: "定数式中でゼロ除算が行われています".to_string(),
+                    });
+                }
+                match (&self.kind, unsigned) {
+                    (NodeKind::Div, true) => ((lhs as u64) / (rhs as u64)) as i64,
+                    (NodeKind::Div, false) => lhs.wrapping_div(rhs),
+                    (_, true) => ((lhs as u64) % (rhs as u64)) as i64,
+                    (_, false) => lhs.wrapping_rem(rhs),
+                }
+            }
+            NodeKind::Shl => eval(&self.lhs)?.wrapping_shl(eval(&self.rhs)? as u32),
+            NodeKind::Shr if unsigned => {
+                ((eval(&self.lhs)? as u64).wrapping_shr(eval(&self.rhs)? as u32)) as i64
+            }
+            NodeKind::Shr => eval(&self.lhs)?.wrapping_shr(eval(&self.rhs)? as u32),
+            NodeKind::BitAnd => eval(&self.lhs)? & eval(&self.rhs)?,
+            NodeKind::BitOr => eval(&self.lhs)? | eval(&self.rhs)?,
+            NodeKind::BitXor => eval(&self.lhs)? ^ eval(&self.rhs)?,
+            NodeKind::BitNot => !eval(&self.lhs)?,
+            NodeKind::LogicalNot => (eval(&self.lhs)? == 0) as i64,
+            NodeKind::LogicalAnd => (eval(&self.lhs)? != 0 && eval(&self.rhs)? != 0) as i64,
+            NodeKind::LogicalOr => (eval(&self.lhs)? != 0 || eval(&self.rhs)? != 0) as i64,
+            NodeKind::Eq => (eval(&self.lhs)? == eval(&self.rhs)?) as i64,
+            NodeKind::Ne => (eval(&self.lhs)? != eval(&self.rhs)?) as i64,
+            NodeKind::Lt if unsigned => {
+                ((eval(&self.lhs)? as u64) < eval(&self.rhs)? as u64) as i64
+            }
+            NodeKind::Lt => (eval(&self.lhs)? < eval(&self.rhs)?) as i64,
+            NodeKind::Le if unsigned => {
+                ((eval(&self.lhs)? as u64) <= eval(&self.rhs)? as u64) as i64
+            }
+            NodeKind::Le => (eval(&self.lhs)? <= eval(&self.rhs)?) as i64,
+            NodeKind::Ternary { cond, then, els } => {
+                if eval(cond)? != 0 {
+                    eval(then)?
+                } else {
+                    eval(els)?
+                }
+            }
+            NodeKind::Cast if self.ty.as_ref().is_some_and(|ty| ty.is_bool()) => {
+                (eval(&self.lhs)? != 0) as i64
+            }
+            // 浮動小数点数から整数へのキャストは0方向に切り捨てる
+            NodeKind::Cast
+                if self.ty.as_ref().is_some_and(|ty| ty.is_integer())
+                    && self.lhs.as_ref().is_some_and(|lhs| lhs.is_floating_point()) =>
+            {
+                let val = self.lhs.as_ref().unwrap().eval_float()?;
+                if self.is_unsigned_op() {
+                    val as u64 as i64
+                } else {
+                    val as i64
+                }
+            }
+            NodeKind::Cast if self.ty.as_ref().is_some_and(|ty| ty.is_integer()) => {
+                eval(&self.lhs)?
+            }
+            _ => return Err(not_const()),
+        };
+        // 結果の型の幅に切り詰める
+        Ok(
+            match self.ty.as_ref().map(|ty| (ty.size_of(), ty.is_unsigned())) {
+                Some((1, true)) => val as u8 as i64,
+                Some((1, false)) => val as i8 as i64,
+                Some((2, true)) => val as u16 as i64,
+                Some((2, false)) => val as i16 as i64,
+                Some((4, true)) => val as u32 as i64,
+                Some((4, false)) => val as i32 as i64,
+                _ => val,
+            },
+        )
+    }
+
+    // 浮動小数点型の式かどうか
+    pub fn is_floating_point(&self) -> bool {
+        self.ty.as_ref().is_some_and(|ty| ty.is_floating_point())
+    }
+
+    // 算術定数式を浮動小数点数として評価する
+    // 静的な初期化子の浮動小数点数の値を求めるために使う
+    pub fn eval_float(&self) -> Result<f64, CompileError> {
+        let not_const = || CompileError::InvalidExpression {
+            msg: "算術定数式ではありません".to_string(),
+        };
+        let eval = |node: &Option<Box<Node>>| node.as_ref().ok_or_else(not_const)?.eval_float();
+        if !self.is_floating_point() {
+            let val = self.eval_const()?;
+            return Ok(if self.ty.as_ref().is_some_and(|ty| ty.is_unsigned()) {
+                val as u64 as f64
+            } else {
+                val as f64
+            });
+        }
+        let val = match &self.kind {
+            NodeKind::FloatNumber { bits, .. } => f64::from_bits(*bits),
+            NodeKind::Add => eval(&self.lhs)? + eval(&self.rhs)?,
+            NodeKind::Sub => eval(&self.lhs)? - eval(&self.rhs)?,
+            NodeKind::Mul => eval(&self.lhs)? * eval(&self.rhs)?,
+            NodeKind::Div => eval(&self.lhs)? / eval(&self.rhs)?,
+            NodeKind::Neg => -eval(&self.lhs)?,
+            NodeKind::Cast => eval(&self.lhs)?,
+            NodeKind::Ternary { cond, then, els } => {
+                if cond.as_ref().ok_or_else(not_const)?.eval_float()? != 0.0 {
+                    eval(then)?
+                } else {
+                    eval(els)?
+                }
+            }
+            _ => return Err(not_const()),
+        };
+        // floatの値はその精度に丸める
+        Ok(match self.ty.as_ref().map(|ty| &ty.kind) {
+            Some(TypeKind::Float) => val as f32 as f64,
+            _ => val,
+        })
+    }
+
+    // long doubleの静的な初期化子のビット表現を求めるために使う
+    // long doubleの演算は拡張倍精度で畳み込み、doubleやfloatの部分式はその精度で評価する
+    pub fn eval_long_double(&self) -> Result<u128, CompileError> {
+        let not_const = || CompileError::InvalidExpression {
+            msg: "算術定数式ではありません".to_string(),
+        };
+        let eval =
+            |node: &Option<Box<Node>>| node.as_ref().ok_or_else(not_const)?.eval_long_double();
+        if !self.is_floating_point() {
+            let val = self.eval_const()?;
+            return Ok(if self.ty.as_ref().is_some_and(|ty| ty.is_unsigned()) {
+                float80::from_u64(val as u64)
+            } else {
+                float80::from_i64(val)
+            });
+        }
+        if self
+            .ty
+            .as_ref()
+            .is_none_or(|ty| ty.kind != TypeKind::LongDouble)
+        {
+            return Ok(float80::from_f64(self.eval_float()?));
+        }
+        match &self.kind {
+            NodeKind::FloatNumber { ext, .. } => Ok(*ext),
+            NodeKind::Add => Ok(float80::add(eval(&self.lhs)?, eval(&self.rhs)?)),
+            NodeKind::Sub => Ok(float80::sub(eval(&self.lhs)?, eval(&self.rhs)?)),
+            NodeKind::Mul => Ok(float80::mul(eval(&self.lhs)?, eval(&self.rhs)?)),
+            NodeKind::Div => Ok(float80::div(eval(&self.lhs)?, eval(&self.rhs)?)),
+            NodeKind::Neg => Ok(eval(&self.lhs)? ^ float80::SIGN_BIT),
+            NodeKind::Cast => eval(&self.lhs),
+            NodeKind::Ternary { cond, then, els } => {
+                if float80::is_zero(eval(cond)?) {
+                    eval(els)
+                } else {
+                    eval(then)
+                }
+            }
+            _ => Err(not_const()),
+        }
+    }
+
     pub fn assign_types(&mut self) -> Result<(), CompileError> {
         if let Some(ref mut lhs) = self.lhs {
             lhs.assign_types()?;
@@ -291,23 +694,76 @@ impl Node {
         }
 
         match self.kind {
-            NodeKind::Number { .. } => {
+            NodeKind::If {
+                ref mut cond,
+                ref mut then,
+                ref mut els,
+            } => {
+                for node in [cond, then, els].into_iter().flatten() {
+                    node.assign_types()?;
+                }
+            }
+            NodeKind::While {
+                ref mut cond,
+                ref mut then,
+            }
+            | NodeKind::Do {
+                ref mut cond,
+                ref mut then,
+            } => {
+                for node in [cond, then].into_iter().flatten() {
+                    node.assign_types()?;
+                }
+            }
+            NodeKind::For {
+                ref mut init,
+                ref mut cond,
+                ref mut inc,
+                ref mut then,
+            } => {
+                for node in [init, cond, inc, then].into_iter().flatten() {
+                    node.assign_types()?;
+                }
+            }
+            NodeKind::Block { ref mut body } => {
+                for node in body.iter_mut() {
+                    node.assign_types()?;
+                }
+            }
+            NodeKind::Call { ref mut args, .. } => {
+                // 戻り値の型は構文解析時に設定済み
+                for arg in args.iter_mut() {
+                    arg.assign_types()?;
+                }
+            }
+            NodeKind::Number { .. } | NodeKind::FloatNumber { .. } => {
                 // 数値リテラルの型はすでに設定されているはず
             }
-            NodeKind::Var { .. } => {
-                // 変数の型はすでに設定されているはず
+            NodeKind::Neg => {
+                let lhs_ty = self.lhs.as_ref().unwrap().ty.as_ref().unwrap();
+
+                if lhs_ty.is_floating_point() {
+                    let mut ty = (**lhs_ty).clone();
+                    ty.is_const = false;
+                    ty.is_volatile = false;
+                    self.ty = Some(Box::new(ty));
+                } else {
+                    return Err(CompileError::InvalidExpression {
+                        msg: format!("符号反転は浮動小数点型にのみ適用されます: {:?}", lhs_ty),
+                    });
+                }
+            }
+            NodeKind::Var { .. } | NodeKind::Member { .. } => {
+                // 変数やメンバーの型はすでに設定されているはず
             }
             NodeKind::Add | NodeKind::Sub | NodeKind::Mul | NodeKind::Div => {
                 let lhs_ty = self.lhs.as_ref().unwrap().ty.as_ref().unwrap();
                 let rhs_ty = self.rhs.as_ref().unwrap().ty.as_ref().unwrap();
 
                 if lhs_ty.is_scalar() && rhs_ty.is_scalar() {
-                    // 両方ともスカラー型の場合、大きい方の型に合わせる
-                    if lhs_ty.size_of() >= rhs_ty.size_of() {
-                        self.ty = Some(lhs_ty.clone());
-                    } else {
-                        self.ty = Some(rhs_ty.clone());
-                    }
+                    // 両方ともスカラー型の場合、通常の算術型変換を行う
+                    let ty = self.apply_arith_conv();
+                    self.ty = Some(Box::new(ty));
                 } else if lhs_ty.is_ptr_or_array() && rhs_ty.is_scalar() {
                     // 左辺がポインタ/配列型、右辺がスカラー型の場合、左辺の型を結果型とする
                     self.ty = Some(lhs_ty.clone());
@@ -328,12 +784,9 @@ impl Node {
                 let rhs_ty = self.rhs.as_ref().unwrap().ty.as_ref().unwrap();
 
                 if lhs_ty.is_integer() && rhs_ty.is_integer() {
-                    // 両方とも整数型の場合、大きい方の型に合わせる
-                    if lhs_ty.size_of() >= rhs_ty.size_of() {
-                        self.ty = Some(lhs_ty.clone());
-                    } else {
-                        self.ty = Some(rhs_ty.clone());
-                    }
+                    // 両方とも整数型の場合、通常の算術型変換を行う
+                    let ty = self.apply_arith_conv();
+                    self.ty = Some(Box::new(ty));
                 } else {
                     return Err(CompileError::InvalidExpression {
                         msg: format!(
@@ -348,12 +801,9 @@ impl Node {
                 let rhs_ty = self.rhs.as_ref().unwrap().ty.as_ref().unwrap();
 
                 if lhs_ty.is_integer() && rhs_ty.is_integer() {
-                    // 両方とも整数型の場合、大きい方の型に合わせる
-                    if lhs_ty.size_of() >= rhs_ty.size_of() {
-                        self.ty = Some(lhs_ty.clone());
-                    } else {
-                        self.ty = Some(rhs_ty.clone());
-                    }
+                    // 両方とも整数型の場合、通常の算術型変換を行う
+                    let ty = self.apply_arith_conv();
+                    self.ty = Some(Box::new(ty));
                 } else {
                     return Err(CompileError::InvalidExpression {
                         msg: format!(
@@ -368,8 +818,12 @@ impl Node {
                 let rhs_ty = self.rhs.as_ref().unwrap().ty.as_ref().unwrap();
 
                 if lhs_ty.is_integer() && rhs_ty.is_integer() {
-                    // 両方とも整数型の場合、昇格後の型を結果型とする
-                    self.ty = Some(Box::new(Type::from(&TypeKind::Int, false)));
+                    // 両オペランドは個別に整数拡張し、左辺の昇格後の型を結果型とする
+                    let ty = self.lhs.as_ref().unwrap().promoted_type().unwrap();
+                    let rhs_ty = self.rhs.as_ref().unwrap().promoted_type().unwrap();
+                    convert(&mut self.lhs, &ty);
+                    convert(&mut self.rhs, &rhs_ty);
+                    self.ty = Some(Box::new(ty));
                 } else {
                     return Err(CompileError::InvalidExpression {
                         msg: format!(
@@ -383,10 +837,12 @@ impl Node {
                 let lhs_ty = self.lhs.as_ref().unwrap().ty.as_ref().unwrap();
                 let rhs_ty = self.rhs.as_ref().unwrap().ty.as_ref().unwrap();
 
-                if lhs_ty.is_scalar() && rhs_ty.is_scalar()
-                    || lhs_ty.is_ptr_or_array() && rhs_ty.is_ptr_or_array()
-                {
-                    // 両方ともスカラー型の場合、結果型はint型とする
+                if lhs_ty.is_scalar() && rhs_ty.is_scalar() {
+                    // 両方ともスカラー型の場合、通常の算術型変換を行ってから比較する
+                    self.apply_arith_conv();
+                    self.ty = Some(Box::new(Type::from(&TypeKind::Int, false)));
+                } else if lhs_ty.is_ptr_like() && rhs_ty.is_ptr_like() {
+                    // 結果型はint型とする
                     self.ty = Some(Box::new(Type::from(&TypeKind::Int, false)));
                 } else {
                     return Err(CompileError::InvalidExpression {
@@ -402,7 +858,7 @@ impl Node {
                 let rhs_ty = self.rhs.as_ref().unwrap().ty.as_ref().unwrap();
 
                 if lhs_ty.is_scalar() && rhs_ty.is_scalar()
-                    || lhs_ty.is_ptr_or_array() && rhs_ty.is_ptr_or_array()
+                    || lhs_ty.is_ptr_like() && rhs_ty.is_ptr_like()
                 {
                     // 両方ともスカラー型の場合、結果型はint型とする
                     self.ty = Some(Box::new(Type::from(&TypeKind::Int, false)));
@@ -428,17 +884,19 @@ impl Node {
                 let then_ty = then.as_ref().unwrap().ty.as_ref().unwrap();
                 let els_ty = els.as_ref().unwrap().ty.as_ref().unwrap();
 
-                if cond_ty.is_scalar() || cond_ty.is_ptr_or_array() {
-                    if then_ty == els_ty {
-                        // then節とelse節の型が同じ場合、その型を結果型とする
+                if cond_ty.is_scalar() || cond_ty.is_ptr_like() {
+                    if then_ty.is_scalar() && els_ty.is_scalar() {
+                        // 両方ともスカラー型の場合、通常の算術型変換を行う
+                        let ty = common_type(
+                            &then.as_ref().unwrap().promoted_type().unwrap(),
+                            &els.as_ref().unwrap().promoted_type().unwrap(),
+                        );
+                        convert(then, &ty);
+                        convert(els, &ty);
+                        self.ty = Some(Box::new(ty));
+                    } else if then_ty.unqualified() == els_ty.unqualified() {
+                        // then節とelse節の型が修飾子を除いて同じ場合、その型を結果型とする
                         self.ty = Some(then_ty.clone());
-                    } else if then_ty.is_scalar() && els_ty.is_scalar() {
-                        // 両方ともスカラー型の場合、大きい方の型に合わせる
-                        if then_ty.size_of() >= els_ty.size_of() {
-                            self.ty = Some(then_ty.clone());
-                        } else {
-                            self.ty = Some(els_ty.clone());
-                        }
                     } else {
                         return Err(CompileError::InvalidExpression {
                             msg: format!(
@@ -467,16 +925,40 @@ impl Node {
             | NodeKind::BitAndAssign
             | NodeKind::BitOrAssign
             | NodeKind::BitXorAssign => {
-                let lhs_ty = self.lhs.as_ref().unwrap().ty.as_ref().unwrap();
+                let lhs_ty = self.lhs.as_ref().unwrap().ty.clone().unwrap();
+                let rhs_ty = self.rhs.as_ref().unwrap().ty.as_ref().unwrap();
 
+                if rhs_ty.is_scalar() {
+                    if self.kind == NodeKind::Assign {
+                        // 右辺の値を左辺の型に変換してから代入する
+                        if lhs_ty.is_scalar() {
+                            convert(&mut self.rhs, &lhs_ty);
+                        }
+                    } else if let Some(op_ty) = self.compound_op_type() {
+                        // 右辺は演算を行う型に変換する（シフト演算では右辺を個別に整数拡張する）
+                        let rhs_ty = match self.kind {
+                            NodeKind::ShlAssign | NodeKind::ShrAssign => {
+                                self.rhs.as_ref().unwrap().promoted_type()
+                            }
+                            _ if op_ty.is_scalar() => Some(op_ty),
+                            _ => None,
+                        };
+                        if let Some(ty) = rhs_ty {
+                            convert(&mut self.rhs, &ty);
+                        }
+                    }
+                }
                 // 代入演算子の型は左辺の型とする
-                self.ty = Some(lhs_ty.clone());
+                self.ty = Some(lhs_ty);
             }
             NodeKind::BitNot => {
                 let lhs_ty = self.lhs.as_ref().unwrap().ty.as_ref().unwrap();
 
                 if lhs_ty.is_integer() {
-                    self.ty = Some(Box::new(Type::from(&TypeKind::Int, false))); // 整数拡張
+                    // 整数拡張後の型を結果型とする
+                    let ty = self.lhs.as_ref().unwrap().promoted_type().unwrap();
+                    convert(&mut self.lhs, &ty);
+                    self.ty = Some(Box::new(ty));
                 } else {
                     return Err(CompileError::InvalidExpression {
                         msg: format!("ビット否定演算子は整数型にのみ適用可能です: {:?}", lhs_ty),
@@ -486,7 +968,7 @@ impl Node {
             NodeKind::LogicalNot => {
                 let lhs_ty = self.lhs.as_ref().unwrap().ty.as_ref().unwrap();
 
-                if lhs_ty.is_scalar() || lhs_ty.is_ptr_or_array() {
+                if lhs_ty.is_scalar() || lhs_ty.is_ptr_like() {
                     self.ty = Some(Box::new(Type::from(&TypeKind::Int, false))); // 結果型はint型
                 } else {
                     return Err(CompileError::InvalidExpression {
@@ -509,6 +991,11 @@ impl Node {
             NodeKind::Deref => {
                 let lhs_ty = self.lhs.as_ref().unwrap().ty.as_ref().unwrap();
 
+                // 関数指示子に適用した場合は関数指示子のままとする
+                if lhs_ty.is_func() {
+                    self.ty = Some(lhs_ty.clone());
+                    return Ok(());
+                }
                 // デリファレンス演算子の型はポインタの指す型にする
                 if !lhs_ty.is_ptr_or_array() {
                     return Err(CompileError::InvalidExpression {
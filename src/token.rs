@@ -1,4 +1,6 @@
-use core::fmt;
+use core::{fmt, str};
+
+use crate::types::TypeKind;
 
 pub const PUNCTUATORS: [&str; 54] = [
     "[", "]", "(", ")", "{", "}", ".", "->", "++", "--", "&", "*", "+", "-", "~", "!", "/", "%",
@@ -7,21 +9,86 @@ pub const PUNCTUATORS: [&str; 54] = [
     "%>", "%:", "%:%:",
 ];
 
-pub const KEYWORDS: [&str; 34] = [
-    "auto", "break", "case", "char", "const", "continue", "default", "do", "double", "else",
-    "enum", "extern", "float", "for", "goto", "if", "inline", "int", "long", "register",
-    "restrict", "return", "short", "signed", "sizeof", "static", "struct", "switch", "typedef",
-    "union", "unsigned", "void", "volatile", "while",
+pub const KEYWORDS: [&str; 44] = [
+    "auto",
+    "break",
+    "case",
+    "char",
+    "const",
+    "continue",
+    "default",
+    "do",
+    "double",
+    "else",
+    "enum",
+    "extern",
+    "float",
+    "for",
+    "goto",
+    "if",
+    "inline",
+    "int",
+    "long",
+    "register",
+    "restrict",
+    "return",
+    "short",
+    "signed",
+    "sizeof",
+    "static",
+    "struct",
+    "switch",
+    "typedef",
+    "union",
+    "unsigned",
+    "void",
+    "volatile",
+    "while",
+    "_Alignas",
+    "_Alignof",
+    "_Bool",
+    "_Generic",
+    "_Static_assert",
+    "__attribute__",
+    "__builtin_va_start",
+    "__builtin_va_arg",
+    "__builtin_va_copy",
+    "__builtin_va_end",
 ];
 
+// コンパイル対象のC言語規格
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Standard {
+    C99,
+    C11,
+    #[default]
+    C17,
+    C23,
+}
+
+impl str::FromStr for Standard {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix("gnu").or_else(|| s.strip_prefix('c')) {
+            Some("99" | "9x") => Ok(Standard::C99),
+            Some("11" | "1x") => Ok(Standard::C11),
+            Some("17" | "18") => Ok(Standard::C17),
+            Some("23" | "2x") => Ok(Standard::C23),
+            _ => Err(format!("未対応の規格です: {}", s)),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum TokenKind {
-    Punctuator(String), // 記号トークン
-    Keyword(String),    // キーワード
-    Identifier(String), // 識別子
-    Number(i64),        // 整数トークン
-    String(String),     // 文字列リテラルトークン
-    EOF,                // 入力の終わりを表すトークン
+    Punctuator(String),                    // 記号トークン
+    Keyword(String),                       // キーワード
+    Identifier(String),                    // 識別子
+    Number(i64, Box<TypeKind>),            // 整数トークン（値と型）
+    FloatNumber(u64, u128, Box<TypeKind>), // 浮動小数点数トークン（f64と80ビット拡張倍精度のビット表現と型）
+    String(String, Vec<u8>), // 文字列リテラルトークン（ソース上の表記と実行文字集合でのバイト列）
+    PragmaPack(Option<usize>), // #pragma pack で指定された構造体メンバーの最大アラインメント
+    EOF,                     // 入力の終わりを表すトークン
 }
 
 #[derive(Clone, PartialEq, Eq)]
@@ -36,8 +103,18 @@ impl fmt::Debug for Token {
             TokenKind::Punctuator(s) => write!(f, "Punctuator('{}') {:?}", s, self.span),
             TokenKind::Keyword(s) => write!(f, "Keyword('{}') {:?}", s, self.span),
             TokenKind::Identifier(s) => write!(f, "Identifier('{}') {:?}", s, self.span),
-            TokenKind::Number(n) => write!(f, "Num({}) {:?}", n, self.span),
-            TokenKind::String(s) => write!(f, "StringLiteral(\"{}\") {:?}", s, self.span),
+            TokenKind::Number(n, ty) => write!(f, "Num({}: {:?}) {:?}", n, ty, self.span),
+            TokenKind::FloatNumber(bits, _, ty) => {
+                write!(
+                    f,
+                    "Float({}: {:?}) {:?}",
+                    f64::from_bits(*bits),
+                    ty,
+                    self.span
+                )
+            }
+            TokenKind::String(s, _) => write!(f, "StringLiteral(\"{}\") {:?}", s, self.span),
+            TokenKind::PragmaPack(pack) => write!(f, "PragmaPack({:?}) {:?}", pack, self.span),
             TokenKind::EOF => write!(f, "EOF {:?}", self.span),
         }
     }
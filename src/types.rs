@@ -1,6 +1,8 @@
 use core::fmt;
 
 use crate::ast::Var;
+use crate::errors::CompileError;
+use crate::node::Node;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum DeclarationSpecifier {
@@ -11,8 +13,141 @@ pub enum DeclarationSpecifier {
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum TypeSpecifierQualifier {
-    TypeSpecifier(TypeKind),
+    TypeSpecifier(TypeSpecifierKind),
     TypeQualifier(TypeQualifierKind),
+    Attributes(Attributes), // _Alignas や __attribute__ による指定
+}
+
+// _Alignas や __attribute__ で指定される配置の属性
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Attributes {
+    pub aligned: Option<usize>, // 指定されたアラインメント
+    pub packed: bool,           // メンバーを詰めて配置するか
+}
+
+impl Attributes {
+    // 他の指定と合わせる（アラインメントは大きい方を優先する）
+    pub fn merge(&mut self, other: Attributes) {
+        self.aligned = self.aligned.max(other.aligned);
+        self.packed |= other.packed;
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TypeSpecifierKind {
+    Void,
+    Char,
+    Short,
+    Int,
+    Long,
+    Float,
+    Double,
+    Signed,
+    Unsigned,
+    Bool,
+    Struct(TypeKind), // struct_or_union_specifier
+    Enum(TypeKind),   // enum_specifier（列挙型の基底型）
+    TypedefName(String, Type),
+}
+
+impl fmt::Display for TypeSpecifierKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeSpecifierKind::Void => write!(f, "void"),
+            TypeSpecifierKind::Char => write!(f, "char"),
+            TypeSpecifierKind::Short => write!(f, "short"),
+            TypeSpecifierKind::Int => write!(f, "int"),
+            TypeSpecifierKind::Long => write!(f, "long"),
+            TypeSpecifierKind::Float => write!(f, "float"),
+            TypeSpecifierKind::Double => write!(f, "double"),
+            TypeSpecifierKind::Signed => write!(f, "signed"),
+            TypeSpecifierKind::Unsigned => write!(f, "unsigned"),
+            TypeSpecifierKind::Bool => write!(f, "_Bool"),
+            TypeSpecifierKind::Struct(kind) => write!(f, "{}", kind),
+            TypeSpecifierKind::Enum(_) => write!(f, "enum"),
+            TypeSpecifierKind::TypedefName(name, _) => write!(f, "{}", name),
+        }
+    }
+}
+
+// 型指定子キーワードの種類数
+const SPECIFIER_KEYWORDS: usize = 10;
+
+// C11 6.7.2 で許される型指定子の組み合わせと、それが表す型
+// 各キーワードの個数を void, char, short, int, long, float, double, signed, unsigned, _Bool の順に並べる
+#[rustfmt::skip]
+const SPECIFIER_COMBINATIONS: [([u8; SPECIFIER_KEYWORDS], TypeKind); 31] = [
+    // v  c  s  i  l  f  d  sg us b
+    ([1, 0, 0, 0, 0, 0, 0, 0, 0, 0], TypeKind::Void),                     // void
+    ([0, 1, 0, 0, 0, 0, 0, 0, 0, 0], TypeKind::Char { unsigned: false }), // char
+    ([0, 1, 0, 0, 0, 0, 0, 1, 0, 0], TypeKind::SChar),                    // signed char
+    ([0, 1, 0, 0, 0, 0, 0, 0, 1, 0], TypeKind::UChar),                    // unsigned char
+    ([0, 0, 1, 0, 0, 0, 0, 0, 0, 0], TypeKind::Short),                    // short
+    ([0, 0, 1, 0, 0, 0, 0, 1, 0, 0], TypeKind::Short),                    // signed short
+    ([0, 0, 1, 1, 0, 0, 0, 0, 0, 0], TypeKind::Short),                    // short int
+    ([0, 0, 1, 1, 0, 0, 0, 1, 0, 0], TypeKind::Short),                    // signed short int
+    ([0, 0, 1, 0, 0, 0, 0, 0, 1, 0], TypeKind::UShort),                   // unsigned short
+    ([0, 0, 1, 1, 0, 0, 0, 0, 1, 0], TypeKind::UShort),                   // unsigned short int
+    ([0, 0, 0, 1, 0, 0, 0, 0, 0, 0], TypeKind::Int),                      // int
+    ([0, 0, 0, 0, 0, 0, 0, 1, 0, 0], TypeKind::Int),                      // signed
+    ([0, 0, 0, 1, 0, 0, 0, 1, 0, 0], TypeKind::Int),                      // signed int
+    ([0, 0, 0, 0, 0, 0, 0, 0, 1, 0], TypeKind::UInt),                     // unsigned
+    ([0, 0, 0, 1, 0, 0, 0, 0, 1, 0], TypeKind::UInt),                     // unsigned int
+    ([0, 0, 0, 0, 1, 0, 0, 0, 0, 0], TypeKind::Long),                     // long
+    ([0, 0, 0, 0, 1, 0, 0, 1, 0, 0], TypeKind::Long),                     // signed long
+    ([0, 0, 0, 1, 1, 0, 0, 0, 0, 0], TypeKind::Long),                     // long int
+    ([0, 0, 0, 1, 1, 0, 0, 1, 0, 0], TypeKind::Long),                     // signed long int
+    ([0, 0, 0, 0, 1, 0, 0, 0, 1, 0], TypeKind::ULong),                    // unsigned long
+    ([0, 0, 0, 1, 1, 0, 0, 0, 1, 0], TypeKind::ULong),                    // unsigned long int
+    ([0, 0, 0, 0, 2, 0, 0, 0, 0, 0], TypeKind::Long),                     // long long
+    ([0, 0, 0, 0, 2, 0, 0, 1, 0, 0], TypeKind::Long),                     // signed long long
+    ([0, 0, 0, 1, 2, 0, 0, 0, 0, 0], TypeKind::Long),                     // long long int
+    ([0, 0, 0, 1, 2, 0, 0, 1, 0, 0], TypeKind::Long),                     // signed long long int
+    ([0, 0, 0, 0, 2, 0, 0, 0, 1, 0], TypeKind::ULong),                    // unsigned long long
+    ([0, 0, 0, 1, 2, 0, 0, 0, 1, 0], TypeKind::ULong),                    // unsigned long long int
+    ([0, 0, 0, 0, 0, 1, 0, 0, 0, 0], TypeKind::Float),                    // float
+    ([0, 0, 0, 0, 0, 0, 1, 0, 0, 0], TypeKind::Double),                   // double
+    ([0, 0, 0, 0, 1, 0, 1, 0, 0, 0], TypeKind::LongDouble),               // long double
+    ([0, 0, 0, 0, 0, 0, 0, 0, 0, 1], TypeKind::Bool),                     // _Bool
+];
+
+impl TypeSpecifierKind {
+    // SPECIFIER_COMBINATIONSにおけるキーワードの位置
+    fn keyword_index(&self) -> usize {
+        match self {
+            TypeSpecifierKind::Void => 0,
+            TypeSpecifierKind::Char => 1,
+            TypeSpecifierKind::Short => 2,
+            TypeSpecifierKind::Int => 3,
+            TypeSpecifierKind::Long => 4,
+            TypeSpecifierKind::Float => 5,
+            TypeSpecifierKind::Double => 6,
+            TypeSpecifierKind::Signed => 7,
+            TypeSpecifierKind::Unsigned => 8,
+            TypeSpecifierKind::Bool => 9,
+            TypeSpecifierKind::Struct(_)
+            | TypeSpecifierKind::Enum(_)
+            | TypeSpecifierKind::TypedefName(..) => {
+                unreachable!("構造体や列挙型、typedef名はキーワードではありません")
+            }
+        }
+    }
+
+    // キーワード1語で表される型指定子
+    pub fn all() -> Vec<TypeSpecifierKind> {
+        vec![
+            TypeSpecifierKind::Void,
+            TypeSpecifierKind::Char,
+            TypeSpecifierKind::Short,
+            TypeSpecifierKind::Int,
+            TypeSpecifierKind::Long,
+            TypeSpecifierKind::Float,
+            TypeSpecifierKind::Double,
+            TypeSpecifierKind::Signed,
+            TypeSpecifierKind::Unsigned,
+            TypeSpecifierKind::Bool,
+        ]
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -97,44 +232,108 @@ impl TypeQualifierKind {
 #[derive(Clone, PartialEq, Eq)]
 pub enum TypeKind {
     Void,
-    Char,
+    Bool,
+    Char {
+        unsigned: bool,
+    }, // 符号指定のないchar（unsigned: -funsigned-charにより符号なしとして扱うか）
+    SChar,
+    UChar,
     Short,
+    UShort,
     Int,
+    UInt,
     Long,
+    ULong,
     Float,
     Double,
+    LongDouble,
     Ptr {
         to: Box<Type>,
     }, // to: ポインタの指す型
     Array {
         base: Box<Type>,
-        size: usize,
-    }, // base: 配列の要素型, size: 要素数
+        size: Option<usize>,
+    }, // base: 配列の要素型, size: 要素数（省略された場合はNone）
+    Vla {
+        base: Box<Type>,
+        len: Box<Node>,
+        size_offset: Option<usize>,
+    }, // base: 配列の要素型, len: 要素数の式, size_offset: 実行時のサイズを保持するローカル変数のオフセット（宣言時に割り当てる）
     Struct {
         name: String,
         members: Vec<Var>,
-    }, // name: 構造体名, members: メンバーリスト
+        id: usize,
+        complete: bool,
+        align: Option<usize>,
+    }, // name: 構造体名, members: メンバーリスト, id: 構造体の識別番号, complete: 完全型かどうか, align: 属性で指定されたアラインメント
+    Union {
+        name: String,
+        members: Vec<Var>,
+        id: usize,
+        complete: bool,
+        align: Option<usize>,
+    }, // name: 共用体名, members: メンバーリスト, id: 共用体の識別番号, complete: 完全型かどうか, align: 属性で指定されたアラインメント
     Func {
         return_ty: Box<Type>,
         params: Vec<Var>,
-    }, // return_ty: 戻り値の型, params: パラメータリスト
+        variadic: bool,
+    }, // return_ty: 戻り値の型, params: パラメータリスト, variadic: 可変長引数（...）を取るか
+}
+
+// System V ABIでの8バイトごとの値の分類
+// 整数は汎用レジスタ、SSEはXMMレジスタで受け渡す
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArgClass {
+    Integer,
+    Sse,
 }
 
 impl fmt::Debug for TypeKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             TypeKind::Void => write!(f, "void"),
-            TypeKind::Char => write!(f, "char"),
+            TypeKind::Bool => write!(f, "_Bool"),
+            TypeKind::Char { .. } => write!(f, "char"),
+            TypeKind::SChar => write!(f, "signed char"),
+            TypeKind::UChar => write!(f, "unsigned char"),
             TypeKind::Short => write!(f, "short"),
+            TypeKind::UShort => write!(f, "unsigned short"),
             TypeKind::Int => write!(f, "int"),
+            TypeKind::UInt => write!(f, "unsigned int"),
             TypeKind::Long => write!(f, "long"),
+            TypeKind::ULong => write!(f, "unsigned long"),
             TypeKind::Float => write!(f, "float"),
             TypeKind::Double => write!(f, "double"),
+            TypeKind::LongDouble => write!(f, "long double"),
             // ポインタや配列は再帰的に*をつけて表示
             TypeKind::Ptr { to } => write!(f, "{:?}*", to),
-            TypeKind::Array { base, size } => write!(f, "[{:?}; {}]", base, size),
-            TypeKind::Struct { name, members } => write!(f, "struct {} {{ {:?} }}", name, members),
-            TypeKind::Func { return_ty, params } => {
+            TypeKind::Array {
+                base,
+                size: Some(size),
+            } => write!(f, "[{:?}; {}]", base, size),
+            TypeKind::Array { base, size: None } => write!(f, "[{:?}]", base),
+            TypeKind::Vla { base, .. } => write!(f, "[{:?}; *]", base),
+            TypeKind::Struct {
+                name,
+                complete: false,
+                ..
+            } => write!(f, "struct {} (incomplete)", name),
+            TypeKind::Union {
+                name,
+                complete: false,
+                ..
+            } => write!(f, "union {} (incomplete)", name),
+            TypeKind::Struct { name, members, .. } => {
+                write!(f, "struct {} {{ {:?} }}", name, members)
+            }
+            TypeKind::Union { name, members, .. } => {
+                write!(f, "union {} {{ {:?} }}", name, members)
+            }
+            TypeKind::Func {
+                return_ty,
+                params,
+                variadic,
+            } => {
                 write!(f, "func(")?;
                 for (i, param) in params.iter().enumerate() {
                     if i > 0 {
@@ -142,6 +341,9 @@ impl fmt::Debug for TypeKind {
                     }
                     write!(f, "{:?}", param)?;
                 }
+                if *variadic {
+                    write!(f, "{}...", if params.is_empty() { "" } else { ", " })?;
+                }
                 write!(f, ") -> {:?}", return_ty)
             }
         }
@@ -152,35 +354,54 @@ impl fmt::Display for TypeKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             TypeKind::Void => write!(f, "void"),
-            TypeKind::Char => write!(f, "char"),
+            TypeKind::Bool => write!(f, "_Bool"),
+            TypeKind::Char { .. } => write!(f, "char"),
+            TypeKind::SChar => write!(f, "signed char"),
+            TypeKind::UChar => write!(f, "unsigned char"),
             TypeKind::Short => write!(f, "short"),
+            TypeKind::UShort => write!(f, "unsigned short"),
             TypeKind::Int => write!(f, "int"),
+            TypeKind::UInt => write!(f, "unsigned int"),
             TypeKind::Long => write!(f, "long"),
+            TypeKind::ULong => write!(f, "unsigned long"),
             TypeKind::Float => write!(f, "float"),
             TypeKind::Double => write!(f, "double"),
+            TypeKind::LongDouble => write!(f, "long double"),
             TypeKind::Ptr { to } => write!(f, "ptr to {:?}", to),
-            TypeKind::Array { base, size } => write!(f, "array[{}] of {:?}", size, base),
-            TypeKind::Struct { name, members } => {
+            TypeKind::Array {
+                base,
+                size: Some(size),
+            } => write!(f, "array[{}] of {:?}", size, base),
+            TypeKind::Array { base, size: None } => write!(f, "array[] of {:?}", base),
+            TypeKind::Vla { base, .. } => write!(f, "variable length array of {:?}", base),
+            TypeKind::Struct { name, members, .. } => {
                 write!(f, "struct {} {{ {:?} }}", name, members)
             }
-            TypeKind::Func { return_ty, params } => {
-                write!(f, "func({:?}) -> {:?}", params, return_ty)
+            TypeKind::Union { name, members, .. } => {
+                write!(f, "union {} {{ {:?} }}", name, members)
+            }
+            TypeKind::Func {
+                return_ty,
+                params,
+                variadic,
+            } => {
+                let dots = if *variadic { ", ..." } else { "" };
+                write!(f, "func({:?}{}) -> {:?}", params, dots, return_ty)
             }
         }
     }
 }
 
 impl TypeKind {
-    pub fn all() -> Vec<TypeKind> {
-        vec![
-            TypeKind::Void,
-            TypeKind::Char,
-            TypeKind::Short,
-            TypeKind::Int,
-            TypeKind::Long,
-            TypeKind::Float,
-            TypeKind::Double,
-        ]
+    // 整数型を対応する符号なし整数型に変換する
+    pub fn to_unsigned(&self) -> TypeKind {
+        match self {
+            TypeKind::Char { .. } | TypeKind::SChar => TypeKind::UChar,
+            TypeKind::Short => TypeKind::UShort,
+            TypeKind::Int => TypeKind::UInt,
+            TypeKind::Long => TypeKind::ULong,
+            _ => self.clone(),
+        }
     }
 }
 
@@ -195,21 +416,34 @@ impl AlignUp for usize {
     }
 }
 
+// ビットフィールドの幅と、記憶単位の先頭からのビット位置
+// 記憶単位はビットフィールドの宣言された型の大きさを持つ
+// packedの場合は記憶単位を持たず、先頭のバイトからのビット位置となる
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BitField {
+    pub width: usize,
+    pub bit_offset: usize,
+    pub packed: bool, // packed属性や#pragma packにより、記憶単位をまたいで詰めて配置する
+}
+
 #[derive(Clone, PartialEq, Eq)]
 pub struct Type {
     pub kind: TypeKind,
     size: usize,
     align: usize,
     pub is_const: bool,
+    pub is_volatile: bool,
 }
 
 impl fmt::Debug for Type {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if self.is_const {
-            write!(f, "const {:?}", self.kind)
-        } else {
-            write!(f, "{:?}", self.kind)
+            write!(f, "const ")?;
+        }
+        if self.is_volatile {
+            write!(f, "volatile ")?;
         }
+        write!(f, "{:?}", self.kind)
     }
 }
 
@@ -221,151 +455,620 @@ impl Type {
                 size: 0,
                 align: 0,
                 is_const,
+                is_volatile: false,
+            },
+            TypeKind::Bool => Type {
+                kind: TypeKind::Bool,
+                size: 1,
+                align: 1,
+                is_const,
+                is_volatile: false,
+            },
+            TypeKind::Char { unsigned } => Type {
+                kind: TypeKind::Char { unsigned },
+                size: 1,
+                align: 1,
+                is_const,
+                is_volatile: false,
             },
-            TypeKind::Char => Type {
-                kind: TypeKind::Char,
+            TypeKind::SChar => Type {
+                kind: TypeKind::SChar,
                 size: 1,
                 align: 1,
                 is_const,
+                is_volatile: false,
+            },
+            TypeKind::UChar => Type {
+                kind: TypeKind::UChar,
+                size: 1,
+                align: 1,
+                is_const,
+                is_volatile: false,
             },
             TypeKind::Short => Type {
                 kind: TypeKind::Short,
                 size: 2,
                 align: 2,
                 is_const,
+                is_volatile: false,
+            },
+            TypeKind::UShort => Type {
+                kind: TypeKind::UShort,
+                size: 2,
+                align: 2,
+                is_const,
+                is_volatile: false,
             },
             TypeKind::Int => Type {
                 kind: TypeKind::Int,
                 size: 4,
                 align: 4,
                 is_const,
+                is_volatile: false,
+            },
+            TypeKind::UInt => Type {
+                kind: TypeKind::UInt,
+                size: 4,
+                align: 4,
+                is_const,
+                is_volatile: false,
             },
             TypeKind::Long => Type {
                 kind: TypeKind::Long,
                 size: 8,
                 align: 8,
                 is_const,
+                is_volatile: false,
+            },
+            TypeKind::ULong => Type {
+                kind: TypeKind::ULong,
+                size: 8,
+                align: 8,
+                is_const,
+                is_volatile: false,
             },
             TypeKind::Float => Type {
                 kind: TypeKind::Float,
                 size: 4,
                 align: 4,
                 is_const,
+                is_volatile: false,
             },
             TypeKind::Double => Type {
                 kind: TypeKind::Double,
                 size: 8,
                 align: 8,
                 is_const,
+                is_volatile: false,
+            },
+            TypeKind::LongDouble => Type {
+                kind: TypeKind::LongDouble,
+                size: 16,
+                align: 16,
+                is_const,
+                is_volatile: false,
             },
             TypeKind::Ptr { ref to } => Type {
                 kind: TypeKind::Ptr { to: to.clone() },
                 size: 8,
                 align: 8,
                 is_const,
+                is_volatile: false,
             },
+            // 要素数が省略された配列は不完全型で、サイズは0になる
             TypeKind::Array { ref base, size } => Type {
                 kind: TypeKind::Array {
                     base: base.clone(),
                     size,
                 },
-                size: base.size * size,
+                size: base.size * size.unwrap_or(0),
+                align: base.align,
+                is_const,
+                is_volatile: false,
+            },
+            // 可変長配列のサイズは実行時に決まるため、静的なサイズは持たない
+            TypeKind::Vla {
+                ref base,
+                ref len,
+                size_offset,
+            } => Type {
+                kind: TypeKind::Vla {
+                    base: base.clone(),
+                    len: len.clone(),
+                    size_offset,
+                },
+                size: 0,
                 align: base.align,
                 is_const,
+                is_volatile: false,
             },
             TypeKind::Struct {
                 ref name,
                 ref members,
+                id,
+                complete,
+                align: attr_align,
             } => {
-                let mut offset = 0;
-                let mut max_align = 1;
+                // 不完全型はメンバーを持たず、サイズは0になる
+                // ビットフィールドを詰めるため、配置はビット単位で進める
+                // 属性でアラインメントが指定されている場合は、それより小さくしない
+                let mut bits = 0;
+                let mut max_align = attr_align.unwrap_or(1);
                 let mut members = members.clone();
                 for member in members.iter_mut() {
-                    let a = member.ty.align_of();
-                    offset = offset.align_up(a); // メンバーのアラインメントに合わせてオフセットを調整
-                    offset += member.ty.size_of(); // メンバーのサイズ分オフセットを進める
-                    member.offset = offset; // メンバーのオフセットを設定
+                    let (size, a) = (member.ty.size_of(), member.ty.align_of());
+                    if let Some(bit_field) = &mut member.bit_field {
+                        // System V ABIに従い、宣言された型の記憶単位をまたがないように配置する
+                        // packedの場合は直前のビットに続けて配置する
+                        // 幅0のビットフィールドはpackedでも次の記憶単位の境界まで進める
+                        let unit_bits = size * 8;
+                        if bit_field.width == 0
+                            || !bit_field.packed
+                                && bits / unit_bits != (bits + bit_field.width - 1) / unit_bits
+                        {
+                            bits = bits.align_up(unit_bits);
+                        }
+                        member.offset = if bit_field.packed {
+                            bits / 8 // 先頭のビットを含むバイトのオフセット
+                        } else {
+                            bits / unit_bits * size // 記憶単位の先頭オフセット
+                        };
+                        bit_field.bit_offset = bits - member.offset * 8;
+                        bits += bit_field.width;
+                        // 名前のないビットフィールドは構造体のアラインメントに影響しない
+                        if member.name.is_empty() {
+                            continue;
+                        }
+                    } else {
+                        let offset = bits.div_ceil(8).align_up(a); // メンバーのアラインメントに合わせてオフセットを調整
+                        member.offset = offset; // メンバーの先頭オフセットを設定
+                        bits = (offset + size) * 8; // メンバーのサイズ分オフセットを進める
+                    }
                     // 構造体全体のアラインメントを更新
                     if a > max_align {
                         max_align = a;
                     }
                 }
+                let offset = bits.div_ceil(8);
                 Type {
                     kind: TypeKind::Struct {
                         name: name.to_string(),
                         members,
+                        id,
+                        complete,
+                        align: attr_align,
                     },
                     size: offset.align_up(max_align), // 構造体全体のサイズをアラインメントに合わせて調整
                     align: max_align, // メンバーの最大アラインメントを構造体のアラインメントとする
                     is_const,
+                    is_volatile: false,
+                }
+            }
+            TypeKind::Union {
+                ref name,
+                ref members,
+                id,
+                complete,
+                align: attr_align,
+            } => {
+                // すべてのメンバーはオフセット0に配置される
+                let mut members = members.clone();
+                for member in members.iter_mut() {
+                    member.offset = 0;
+                    if let Some(bit_field) = &mut member.bit_field {
+                        bit_field.bit_offset = 0;
+                    }
+                }
+                let max_size = members
+                    .iter()
+                    .map(|m| match m.bit_field {
+                        Some(bit_field) => bit_field.width.div_ceil(8),
+                        None => m.ty.size_of(),
+                    })
+                    .max()
+                    .unwrap_or(0);
+                // 名前のないビットフィールドは共用体のアラインメントに影響しない
+                let max_align = members
+                    .iter()
+                    .filter(|m| m.bit_field.is_none() || !m.name.is_empty())
+                    .map(|m| m.ty.align_of())
+                    .chain(attr_align)
+                    .max()
+                    .unwrap_or(1);
+                Type {
+                    kind: TypeKind::Union {
+                        name: name.to_string(),
+                        members,
+                        id,
+                        complete,
+                        align: attr_align,
+                    },
+                    size: max_size.align_up(max_align), // 最大のメンバーのサイズをアラインメントに合わせて調整
+                    align: max_align,
+                    is_const,
+                    is_volatile: false,
                 }
             }
             TypeKind::Func {
                 ref return_ty,
                 ref params,
+                variadic,
             } => Type {
                 kind: TypeKind::Func {
                     return_ty: return_ty.clone(),
                     params: params.clone(),
+                    variadic,
                 },
                 size: 8,
                 align: 8,
                 is_const,
+                is_volatile: false,
             },
         }
     }
 
-    // TODO: constやvolatileの情報も扱う
-    pub fn from_ds(declaration_specifiers: &Vec<DeclarationSpecifier>) -> Option<Self> {
-        for specifier in declaration_specifiers {
-            if let DeclarationSpecifier::TypeSpecifierQualifier(tsq) = specifier
-                && let TypeSpecifierQualifier::TypeSpecifier(ty) = tsq
-            {
-                return Some(Type::from(ty, false));
+    pub fn from_ds(
+        declaration_specifiers: &[DeclarationSpecifier],
+        unsigned_char: bool,
+    ) -> Result<Self, CompileError> {
+        let type_specifier_qualifiers = declaration_specifiers
+            .iter()
+            .filter_map(|specifier| match specifier {
+                DeclarationSpecifier::TypeSpecifierQualifier(tsq) => Some(tsq.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        Type::from_tsq(&type_specifier_qualifiers, unsigned_char)
+    }
+
+    pub fn from_tsq(
+        type_specifier_qualifiers: &[TypeSpecifierQualifier],
+        unsigned_char: bool,
+    ) -> Result<Self, CompileError> {
+        let specifiers = type_specifier_qualifiers
+            .iter()
+            .filter_map(|specifier| match specifier {
+                TypeSpecifierQualifier::TypeSpecifier(ts) => Some(ts),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let qualifiers = type_specifier_qualifiers
+            .iter()
+            .filter_map(|specifier| match specifier {
+                TypeSpecifierQualifier::TypeQualifier(tq) => Some(tq.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let mut ty = Type::from_specifiers(&specifiers, unsigned_char)?;
+        ty.qualify(&qualifiers);
+        Ok(ty)
+    }
+
+    // 型修飾子を型に追加する
+    // 配列型を修飾した場合は要素型が修飾される
+    pub fn qualify(&mut self, qualifiers: &[TypeQualifierKind]) {
+        if let TypeKind::Array { base, .. } | TypeKind::Vla { base, .. } = &mut self.kind {
+            base.qualify(qualifiers);
+            return;
+        }
+        self.qualify_outermost(qualifiers);
+    }
+
+    // 配列型であっても要素型ではなく型自体に型修飾子を追加する
+    // 仮引数の配列宣言子の "[" の後の型修飾子は、調整後のポインタを修飾するために配列型自体に持たせる
+    pub fn qualify_outermost(&mut self, qualifiers: &[TypeQualifierKind]) {
+        for qualifier in qualifiers {
+            match qualifier {
+                TypeQualifierKind::Const => self.is_const = true,
+                TypeQualifierKind::Volatile => self.is_volatile = true,
+                TypeQualifierKind::Restrict => {} // restrictは最適化のためのヒントなので無視する
             }
         }
-        None
     }
 
-    pub fn from_tsq(type_specifier_qualifiers: &Vec<TypeSpecifierQualifier>) -> Option<Self> {
-        for specifier in type_specifier_qualifiers {
-            if let TypeSpecifierQualifier::TypeSpecifier(ty) = specifier {
-                return Some(Type::from(ty, false));
+    // オブジェクトのアラインメントを変更する
+    // 型のサイズは変わらない
+    pub fn set_align(&mut self, align: usize) {
+        self.align = align;
+    }
+
+    // 要素数が省略された配列の要素数を確定する
+    // 型のアラインメントは変わらない
+    pub fn set_array_len(&mut self, len: usize) {
+        if let TypeKind::Array { base, size } = &mut self.kind {
+            *size = Some(len);
+            self.size = base.size * len;
+        }
+    }
+
+    // 他の型の型修飾子を型に追加する
+    pub fn qualify_as(&mut self, other: &Type) {
+        if let TypeKind::Array { base, .. } | TypeKind::Vla { base, .. } = &mut self.kind {
+            base.qualify_as(other);
+            return;
+        }
+        self.is_const |= other.is_const;
+        self.is_volatile |= other.is_volatile;
+    }
+
+    // 型修飾子を全ての階層で取り除いた型
+    // ポインタの指す型の修飾子のみが異なる型を比較するために使う
+    pub fn unqualified(&self) -> Type {
+        let mut ty = match &self.kind {
+            TypeKind::Ptr { to } => Type::from(
+                &TypeKind::Ptr {
+                    to: Box::new(to.unqualified()),
+                },
+                false,
+            ),
+            TypeKind::Array { base, size } => Type::from(
+                &TypeKind::Array {
+                    base: Box::new(base.unqualified()),
+                    size: *size,
+                },
+                false,
+            ),
+            _ => self.clone(),
+        };
+        ty.is_const = false;
+        ty.is_volatile = false;
+        ty
+    }
+
+    // 左辺値変換後の型
+    // 配列は先頭要素へのポインタに、関数は関数へのポインタになり、最上位の型修飾子は取り除かれる
+    pub fn lvalue_converted(&self) -> Type {
+        match &self.kind {
+            TypeKind::Array { base, .. } | TypeKind::Vla { base, .. } => {
+                Type::from(&TypeKind::Ptr { to: base.clone() }, false)
             }
+            TypeKind::Func { .. } => Type::from(
+                &TypeKind::Ptr {
+                    to: Box::new(self.clone()),
+                },
+                false,
+            ),
+            _ => Type::from(&self.kind, false),
+        }
+    }
+
+    // 仮引数として宣言された型を調整した型
+    // 配列は要素型へのポインタに、関数は関数へのポインタになり、配列型自体の型修飾子はポインタに移る
+    pub fn param_adjusted(&self) -> Type {
+        match &self.kind {
+            TypeKind::Array { base, .. } | TypeKind::Vla { base, .. } => {
+                let mut ty = Type::from(&TypeKind::Ptr { to: base.clone() }, self.is_const);
+                ty.is_volatile = self.is_volatile;
+                ty
+            }
+            TypeKind::Func { .. } => Type::from(
+                &TypeKind::Ptr {
+                    to: Box::new(self.clone()),
+                },
+                false,
+            ),
+            _ => self.clone(),
+        }
+    }
+
+    // 2つの型が適合するかどうか
+    // 構造体・共用体は同じ宣言によるものだけが適合し、アラインメントの指定は区別しない
+    pub fn is_compatible(&self, other: &Type) -> bool {
+        if self.is_const != other.is_const || self.is_volatile != other.is_volatile {
+            return false;
+        }
+        match (&self.kind, &other.kind) {
+            (TypeKind::Ptr { to: a }, TypeKind::Ptr { to: b }) => a.is_compatible(b),
+            // 要素数が省略された配列は、要素型が適合すればどの要素数の配列とも適合する
+            (TypeKind::Array { base: a, size: n }, TypeKind::Array { base: b, size: m }) => {
+                (n == m || n.is_none() || m.is_none()) && a.is_compatible(b)
+            }
+            // 可変長配列の要素数は実行時にしか分からないため、要素型のみで判断する
+            (
+                TypeKind::Vla { base: a, .. },
+                TypeKind::Array { base: b, .. } | TypeKind::Vla { base: b, .. },
+            )
+            | (TypeKind::Array { base: a, .. }, TypeKind::Vla { base: b, .. }) => {
+                a.is_compatible(b)
+            }
+            (TypeKind::Struct { id: a, .. }, TypeKind::Struct { id: b, .. })
+            | (TypeKind::Union { id: a, .. }, TypeKind::Union { id: b, .. }) => a == b,
+            (
+                TypeKind::Func {
+                    return_ty: r1,
+                    params: p1,
+                    variadic: v1,
+                },
+                TypeKind::Func {
+                    return_ty: r2,
+                    params: p2,
+                    variadic: v2,
+                },
+            ) => {
+                // 仮引数の型修飾子は関数の型に含まれない
+                r1.is_compatible(r2)
+                    && v1 == v2
+                    && p1.len() == p2.len()
+                    && p1
+                        .iter()
+                        .zip(p2)
+                        .all(|(a, b)| a.ty.unqualified().is_compatible(&b.ty.unqualified()))
+            }
+            (a, b) => a == b,
+        }
+    }
+
+    // const修飾されたオブジェクトを含むかどうか
+    // constなメンバーを持つ構造体・共用体は変更可能な左辺値にならない
+    pub fn contains_const(&self) -> bool {
+        match &self.kind {
+            _ if self.is_const => true,
+            TypeKind::Array { base, .. } | TypeKind::Vla { base, .. } => base.contains_const(),
+            TypeKind::Struct { members, .. } | TypeKind::Union { members, .. } => {
+                members.iter().any(|member| member.ty.contains_const())
+            }
+            _ => false,
         }
-        None
+    }
+
+    // 型指定子の並びから型を決定する
+    // 符号指定のないcharはsigned char、unsigned charとは別の型で、unsigned_charが真の場合は符号なしとして扱う
+    fn from_specifiers(
+        specifiers: &[&TypeSpecifierKind],
+        unsigned_char: bool,
+    ) -> Result<Self, CompileError> {
+        let invalid = |specifier: &TypeSpecifierKind, seen: &[&TypeSpecifierKind]| {
+            CompileError::InvalidTypeSpecifier {
+                msg: format!(
+                    "'{}' は '{}' と組み合わせられません",
+                    specifier,
+                    seen.iter()
+                        .map(|s| s.to_string())
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                ),
+            }
+        };
+
+        // 構造体や列挙型、typedef名はそれ単体でのみ型指定子となる
+        if let Some(i) = specifiers.iter().position(|s| {
+            matches!(
+                s,
+                TypeSpecifierKind::Struct(_)
+                    | TypeSpecifierKind::Enum(_)
+                    | TypeSpecifierKind::TypedefName(..)
+            )
+        }) {
+            if specifiers.len() > 1 {
+                let offending = if i == 0 { 1 } else { i };
+                return Err(invalid(specifiers[offending], &specifiers[..offending]));
+            }
+            match specifiers[0] {
+                TypeSpecifierKind::Struct(kind) | TypeSpecifierKind::Enum(kind) => {
+                    return Ok(Type::from(kind, false));
+                }
+                TypeSpecifierKind::TypedefName(_, ty) => return Ok(ty.clone()),
+                _ => {}
+            }
+        }
+
+        // 各キーワードの個数を数え、どの組み合わせにも含まれなくなった時点でエラーにする
+        // 許される組み合わせの部分集合はそれ自体も許される組み合わせになっている
+        let mut counts = [0u8; SPECIFIER_KEYWORDS];
+        for (i, specifier) in specifiers.iter().enumerate() {
+            counts[specifier.keyword_index()] += 1;
+            let possible = SPECIFIER_COMBINATIONS.iter().any(|(combination, _)| {
+                counts
+                    .iter()
+                    .zip(combination)
+                    .all(|(count, max)| count <= max)
+            });
+            if !possible {
+                return Err(invalid(specifier, &specifiers[..i]));
+            }
+        }
+
+        let kind = SPECIFIER_COMBINATIONS
+            .iter()
+            .find(|(combination, _)| *combination == counts)
+            .map(|(_, kind)| kind.clone())
+            .ok_or_else(|| CompileError::InvalidTypeSpecifier {
+                msg: "型指定子がありません".to_string(),
+            })?;
+        // 符号指定のないcharの符号はオプションで決まる
+        let kind = match kind {
+            TypeKind::Char { .. } => TypeKind::Char {
+                unsigned: unsigned_char,
+            },
+            kind => kind,
+        };
+        Ok(Type::from(&kind, false))
     }
 
     // ポインタもしくは配列の指している型を取得
     pub fn base_type(&self) -> &Type {
         match &self.kind {
             TypeKind::Ptr { to } => to,
-            TypeKind::Array { base, .. } => base,
+            TypeKind::Array { base, .. } | TypeKind::Vla { base, .. } => base,
             _ => self,
         }
     }
 
-    // 型が配列かどうか
+    // 型が配列（可変長配列を含む）かどうか
     pub fn is_array(&self) -> bool {
-        matches!(&self.kind, TypeKind::Array { .. })
+        matches!(&self.kind, TypeKind::Array { .. } | TypeKind::Vla { .. })
+    }
+
+    // 型が文字型の配列（文字列リテラルで初期化できる配列）かどうか
+    pub fn is_char_array(&self) -> bool {
+        matches!(&self.kind, TypeKind::Array { base, .. } if matches!(base.kind, TypeKind::Char { .. } | TypeKind::SChar | TypeKind::UChar))
+    }
+
+    // 型が可変長配列かどうか
+    pub fn is_vla(&self) -> bool {
+        matches!(&self.kind, TypeKind::Vla { .. })
+    }
+
+    // 型が可変長配列を含む（可変修飾型である）かどうか
+    pub fn is_variably_modified(&self) -> bool {
+        match &self.kind {
+            TypeKind::Vla { .. } => true,
+            TypeKind::Ptr { to: base } | TypeKind::Array { base, .. } => {
+                base.is_variably_modified()
+            }
+            _ => false,
+        }
     }
 
     // 型がポインタもしくは配列かどうか
     pub fn is_ptr_or_array(&self) -> bool {
-        matches!(&self.kind, TypeKind::Ptr { .. } | TypeKind::Array { .. })
+        matches!(
+            &self.kind,
+            TypeKind::Ptr { .. } | TypeKind::Array { .. } | TypeKind::Vla { .. }
+        )
     }
 
     // 型が整数型かどうか
     pub fn is_integer(&self) -> bool {
         matches!(
             &self.kind,
-            TypeKind::Char | TypeKind::Short | TypeKind::Int | TypeKind::Long
+            TypeKind::Bool
+                | TypeKind::Char { .. }
+                | TypeKind::SChar
+                | TypeKind::UChar
+                | TypeKind::Short
+                | TypeKind::UShort
+                | TypeKind::Int
+                | TypeKind::UInt
+                | TypeKind::Long
+                | TypeKind::ULong
         )
     }
 
+    // 型が符号なし整数型かどうか（_Boolも符号なし整数型に含まれる）
+    pub fn is_unsigned(&self) -> bool {
+        matches!(
+            &self.kind,
+            TypeKind::Bool
+                | TypeKind::Char { unsigned: true }
+                | TypeKind::UChar
+                | TypeKind::UShort
+                | TypeKind::UInt
+                | TypeKind::ULong
+        )
+    }
+
+    // 型が_Boolかどうか
+    pub fn is_bool(&self) -> bool {
+        matches!(&self.kind, TypeKind::Bool)
+    }
+
     // 型が浮動小数点型かどうか
     pub fn is_floating_point(&self) -> bool {
-        matches!(&self.kind, TypeKind::Float | TypeKind::Double)
+        matches!(
+            &self.kind,
+            TypeKind::Float | TypeKind::Double | TypeKind::LongDouble
+        )
     }
 
     // 型がスカラー型かどうか（整数型または浮動小数点型）
@@ -373,23 +1076,191 @@ impl Type {
         self.is_integer() || self.is_floating_point()
     }
 
-    // 型が構造体かどうか
+    // 整数値をこの整数型で表現できるかどうか
+    pub fn can_represent(&self, val: i64) -> bool {
+        let bits = self.size_of() * 8;
+        if self.is_bool() {
+            (0..=1).contains(&val)
+        } else if bits >= 64 {
+            // 64ビットの符号なし整数型は負の値をそのまま格納できないため除外する
+            !self.is_unsigned() || val >= 0
+        } else if self.is_unsigned() {
+            (0..1 << bits).contains(&val)
+        } else {
+            (-(1 << (bits - 1))..1 << (bits - 1)).contains(&val)
+        }
+    }
+
+    // 値として使うとポインタになる型かどうか
+    // 配列は先頭要素へのポインタに、関数指示子は関数へのポインタに変換される
+    pub fn is_ptr_like(&self) -> bool {
+        self.is_ptr_or_array() || self.is_func()
+    }
+
+    // 型が関数かどうか
+    pub fn is_func(&self) -> bool {
+        matches!(&self.kind, TypeKind::Func { .. })
+    }
+
+    // 型が不完全型の構造体・共用体、要素数が省略された配列、もしくはそれらの配列かどうか
+    pub fn is_incomplete(&self) -> bool {
+        match &self.kind {
+            TypeKind::Struct { complete, .. } | TypeKind::Union { complete, .. } => !complete,
+            TypeKind::Array { size: None, .. } => true,
+            TypeKind::Array { base, .. } | TypeKind::Vla { base, .. } => base.is_incomplete(),
+            _ => false,
+        }
+    }
+
+    // 型が構造体もしくは共用体かどうか
     pub fn is_struct(&self) -> bool {
-        matches!(&self.kind, TypeKind::Struct { .. })
+        matches!(&self.kind, TypeKind::Struct { .. } | TypeKind::Union { .. })
     }
 
-    // 構造体メンバーの検索
-    pub fn find_struct_member(&self, name: &str) -> Option<&Var> {
-        if let TypeKind::Struct { members, .. } = &self.kind {
+    // 構造体もしくは共用体のメンバーの検索
+    // 無名の構造体・共用体メンバーの中も探し、オフセットはこの型の先頭からの値にする
+    pub fn find_struct_member(&self, name: &str) -> Option<Var> {
+        if let TypeKind::Struct { members, .. } | TypeKind::Union { members, .. } = &self.kind {
             for member in members {
                 if member.name == name {
-                    return Some(member);
+                    return Some(member.clone());
+                }
+                if member.name.is_empty()
+                    && let Some(mut inner) = member.ty.find_struct_member(name)
+                {
+                    inner.offset += member.offset;
+                    return Some(inner);
                 }
             }
         }
         None
     }
 
+    // エラーメッセージ用の構造体・共用体の名前（例: struct foo）
+    pub fn tag_name(&self) -> String {
+        match &self.kind {
+            TypeKind::Struct { name, .. } if !name.is_empty() => format!("struct {}", name),
+            TypeKind::Struct { .. } => "無名の構造体".to_string(),
+            TypeKind::Union { name, .. } if !name.is_empty() => format!("union {}", name),
+            TypeKind::Union { .. } => "無名の共用体".to_string(),
+            kind => kind.to_string(),
+        }
+    }
+
+    // 配列・構造体・共用体の要素数
+    // 要素数が省略された配列は初期化子によって要素数が決まるため、上限を設けない
+    pub fn element_count(&self) -> usize {
+        match &self.kind {
+            TypeKind::Array { size, .. } => size.unwrap_or(usize::MAX),
+            TypeKind::Struct { members, .. } | TypeKind::Union { members, .. } => members.len(),
+            _ => 0,
+        }
+    }
+
+    // 配列・構造体・共用体のindex番目の要素の型と先頭からのオフセット、ビットフィールドの情報
+    pub fn element(&self, index: usize) -> Option<(&Type, usize, Option<BitField>)> {
+        match &self.kind {
+            TypeKind::Array { base, size } if index < size.unwrap_or(usize::MAX) => {
+                Some((base, index * base.size_of(), None))
+            }
+            TypeKind::Struct { members, .. } | TypeKind::Union { members, .. } => members
+                .get(index)
+                .map(|member| (&*member.ty, member.offset, member.bit_field)),
+            _ => None,
+        }
+    }
+
+    // index番目の要素が名前のないビットフィールドかどうか
+    // 名前のないビットフィールドは初期化子の対象にならない
+    pub fn is_unnamed_bit_field(&self, index: usize) -> bool {
+        match &self.kind {
+            TypeKind::Struct { members, .. } | TypeKind::Union { members, .. } => members
+                .get(index)
+                .is_some_and(|member| member.name.is_empty() && member.bit_field.is_some()),
+            _ => false,
+        }
+    }
+
+    // System V ABIでレジスタを使って受け渡す場合の、8バイトごとの分類
+    // 16バイトを超える型やlong doubleを含む型、境界に揃っていないメンバーを含む型は
+    // メモリで受け渡すためNoneを返す
+    pub fn eightbyte_classes(&self) -> Option<Vec<ArgClass>> {
+        if self.size_of() > 16 {
+            return None;
+        }
+        let mut classes = vec![None; self.size_of().div_ceil(8)];
+        self.classify(0, &mut classes)?;
+        // メンバーを含まない8バイト（パディングのみ）はSSEとして扱う
+        Some(
+            classes
+                .into_iter()
+                .map(|class| class.unwrap_or(ArgClass::Sse))
+                .collect(),
+        )
+    }
+
+    // long double 1つだけからなる構造体・共用体かどうか
+    // System V ABIでは、引数はメモリで渡すが、戻り値はst(0)で返す
+    pub fn is_x87_aggregate(&self) -> bool {
+        self.is_struct() && self.size_of() == 16 && self.is_x87_only()
+    }
+
+    fn is_x87_only(&self) -> bool {
+        match &self.kind {
+            TypeKind::LongDouble => true,
+            TypeKind::Array {
+                base,
+                size: Some(1),
+            } => base.is_x87_only(),
+            TypeKind::Struct { members, .. } | TypeKind::Union { members, .. } => {
+                !members.is_empty()
+                    && members.iter().all(|member| {
+                        member.offset == 0 && member.bit_field.is_none() && member.ty.is_x87_only()
+                    })
+            }
+            _ => false,
+        }
+    }
+
+    // offsetの位置にあるこの型の値を分類し、classesに反映する
+    // 同じ8バイトに整数とSSEの値が混在する場合は整数とする
+    // #pragma packなどで本来の境界に揃っていないスカラー型のメンバーがあればメモリで受け渡す
+    fn classify(&self, offset: usize, classes: &mut [Option<ArgClass>]) -> Option<()> {
+        let is_leaf = self.is_scalar() || matches!(self.kind, TypeKind::Ptr { .. });
+        if is_leaf && !offset.is_multiple_of(self.size_of().max(1)) {
+            return None;
+        }
+        let class = match &self.kind {
+            TypeKind::LongDouble => return None,
+            // フレキシブル配列メンバーは値の受け渡しに含まれない
+            TypeKind::Array { size: None, .. } => return Some(()),
+            TypeKind::Float | TypeKind::Double => ArgClass::Sse,
+            TypeKind::Array { .. } | TypeKind::Struct { .. } | TypeKind::Union { .. } => {
+                for i in 0..self.element_count() {
+                    let (ty, elem_offset, bit_field) = self.element(i)?;
+                    if let Some(bit_field) = bit_field {
+                        // ビットフィールドは値を含む8バイトを整数として扱う
+                        // 幅0のビットフィールドは値を持たない
+                        let start = (offset + elem_offset) * 8 + bit_field.bit_offset;
+                        let end = start + bit_field.width;
+                        for slot in &mut classes[start / 64..end.div_ceil(64)] {
+                            *slot = Some(ArgClass::Integer);
+                        }
+                    } else {
+                        ty.classify(offset + elem_offset, classes)?;
+                    }
+                }
+                return Some(());
+            }
+            _ => ArgClass::Integer,
+        };
+        let slot = &mut classes[offset / 8];
+        if *slot != Some(ArgClass::Integer) {
+            *slot = Some(class);
+        }
+        Some(())
+    }
+
     // 型の実際のサイズ
     pub fn size_of(&self) -> usize {
         self.size
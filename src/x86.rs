@@ -1,9 +1,39 @@
+use std::collections::BTreeMap;
+
 use crate::asm_builder::AsmBuilder;
-use crate::ast::Ast;
+use crate::ast::{Ast, InitElem};
+use crate::charset::Charset;
 use crate::node::{Node, NodeKind};
-use crate::types::Type;
+use crate::types::{ArgClass, BitField, Type, TypeKind};
 
 const ARG_REGS: [Reg; 6] = [Reg::Rdi, Reg::Rsi, Reg::Rdx, Reg::Rcx, Reg::R8, Reg::R9];
+const RET_REGS: [Reg; 2] = [Reg::Rax, Reg::Rdx];
+
+// 関数呼び出しで引数を渡す場所
+enum ArgLoc {
+    Reg(usize),   // 汎用レジスタ（ARG_REGSの番号）
+    Xmm(usize),   // XMMレジスタの番号
+    Stack(usize), // スタックで渡す引数の領域での位置
+}
+
+// メモリオペランドのサイズ指定
+fn ptr_name(size: usize) -> &'static str {
+    match size {
+        1 => "BYTE",
+        2 => "WORD",
+        4 => "DWORD",
+        _ => "QWORD",
+    }
+}
+
+// 可変長引数の関数で、va_startが可変長引数の先頭を求めるための情報
+#[derive(Clone, Copy)]
+struct VaInfo {
+    area: usize,  // 引数レジスタの値を保存した領域のオフセット
+    gp: usize,    // 名前付きの仮引数が使う汎用レジスタの数
+    fp: usize,    // 名前付きの仮引数が使うXMMレジスタの数
+    stack: usize, // 名前付きの仮引数が使うスタックの領域のバイト数
+}
 
 #[allow(dead_code)]
 #[derive(Hash, Eq, PartialEq, Clone)]
@@ -92,22 +122,36 @@ pub struct Generator {
     break_seq: usize,
     continue_seq: usize,
     func_name: String,
+    stack_size: usize,                      // 関数のローカル変数の領域のサイズ
+    vla_stack: Vec<usize>,                  // 確保済みの可変長配列の変数のオフセット（確保した順）
+    loop_vla_depth: usize,                  // break・continueの飛び先で確保済みの可変長配列の数
+    label_vla_depths: Vec<(String, usize)>, // 各ラベルの位置で確保済みの可変長配列の数
+    va_info: Option<VaInfo>,                // 可変長引数の関数の場合に設定される
+    sret: Option<usize>, // 戻り値をメモリで返す関数で、書き込み先のアドレスを保持するローカル変数のオフセット
+    exec_charset: Charset,
     pub builder: AsmBuilder,
 }
 
 impl Default for Generator {
     fn default() -> Self {
-        Self::new()
+        Self::new(Charset::default())
     }
 }
 
 impl Generator {
-    pub fn new() -> Self {
+    pub fn new(exec_charset: Charset) -> Self {
         Generator {
             label_seq: 1,
             break_seq: 0,
             continue_seq: 0,
             func_name: String::new(),
+            stack_size: 0,
+            vla_stack: Vec::new(),
+            loop_vla_depth: 0,
+            label_vla_depths: Vec::new(),
+            va_info: None,
+            sret: None,
+            exec_charset,
             builder: AsmBuilder::new(),
         }
     }
@@ -123,18 +167,41 @@ impl Generator {
         }
         self.builder.add_row(".section .rodata", true);
         for (i, string) in ast.string_literals.iter().enumerate() {
+            // ASCII以外の文字は実行文字集合に変換し、全てのバイトを8進エスケープで出力する
+            // (SHIFT_JISの2バイト目が'\\'などASCIIと同じ値になる場合があるため)
+            // 表現できない文字は字句解析の時点でエラーになっている
+            let mut escaped = String::new();
+            let mut buf = [0; 4];
+            for c in string.chars() {
+                if c.is_ascii() {
+                    escaped.push(c);
+                    continue;
+                }
+                let bytes = self
+                    .exec_charset
+                    .encode(c.encode_utf8(&mut buf))
+                    .expect("文字列リテラルを実行文字集合に変換できません");
+                for b in bytes {
+                    escaped.push_str(&format!("\\{:03o}", b));
+                }
+            }
             self.builder.add_row(&format!(".L.str.{}:", i), false);
             self.builder
-                .add_row(&format!(".string \"{}\"", string), true);
+                .add_row(&format!(".string \"{}\"", escaped), true);
         }
     }
 
     fn emit_data(&mut self, ast: &Ast) {
-        if ast.globals.is_empty() {
+        // 関数の宣言やextern宣言はデータを持たない
+        let gvars = ast
+            .globals
+            .iter()
+            .filter(|gvar| !gvar.ty.is_func() && !gvar.is_extern);
+        if gvars.clone().next().is_none() {
             return;
         }
         self.builder.add_row(".data", true);
-        for gvar in ast.globals.iter() {
+        for gvar in gvars {
             self.builder.add_row(&format!(".globl {}", gvar.name), true);
             self.builder
                 .add_row(&format!(".align {}", gvar.ty.align_of()), true);
@@ -143,68 +210,153 @@ impl Generator {
             self.builder
                 .add_row(&format!(".size {}, {}", gvar.name, gvar.ty.size_of()), true);
             self.builder.add_row(&format!("{}:", gvar.name), false);
-            if !gvar.init.is_empty() {
-                if gvar.init.len() == 1 {
-                    if let Some(init) = &gvar.init[0] {
-                        match init.kind {
-                            NodeKind::Number { val } => match gvar.ty.size_of() {
-                                1 => {
-                                    self.builder.add_row(&format!(".byte {}", val), true);
-                                }
-                                2 => {
-                                    self.builder.add_row(&format!(".word {}", val), true);
-                                }
-                                4 => {
-                                    self.builder.add_row(&format!(".long {}", val), true);
-                                }
-                                8 => {
-                                    self.builder.add_row(&format!(".quad {}", val), true);
-                                }
-                                _ => {
-                                    panic!(
-                                        "未対応のグローバル変数初期化サイズ: {}",
-                                        gvar.ty.size_of()
-                                    )
-                                }
-                            },
-                            NodeKind::Addr => {
-                                if let Some(lhs) = &init.lhs {
-                                    match &lhs.kind {
-                                        NodeKind::Var { name, is_local, .. } => {
-                                            if !*is_local {
-                                                self.builder
-                                                    .add_row(&format!(".quad {}", name), true);
-                                            } else {
-                                                panic!(
-                                                    "グローバル変数の初期化式にローカル変数のアドレスは使用できません: {}",
-                                                    name
-                                                );
-                                            }
-                                        }
-                                        _ => {
-                                            panic!(
-                                                "未対応のグローバル変数初期化式のアドレス指定: {:?}",
-                                                lhs.kind
-                                            );
-                                        }
-                                    }
-                                }
-                            }
-                            NodeKind::String { index, .. } => {
-                                self.builder
-                                    .add_row(&format!(".quad .L.str.{}", index), true);
+            // 初期化子の要素をオフセット順に出力し、初期化されない部分は0で埋める
+            // 同じ位置を複数回初期化している場合は後のものを優先する
+            let mut elems = gvar.init.iter().rev().collect::<Vec<_>>();
+            elems.sort_by_key(|elem| (elem.offset, elem.bit_field.map(|bf| bf.bit_offset)));
+            elems.dedup_by_key(|elem| (elem.offset, elem.bit_field.map(|bf| bf.bit_offset)));
+            // ビットフィールドは記憶単位が重なることがあるため、バイト単位にまとめて出力する
+            let mut bit_bytes = BTreeMap::new();
+            for elem in elems.iter().filter(|elem| elem.bit_field.is_some()) {
+                let bit_field = elem.bit_field.unwrap();
+                let val = elem
+                    .expr
+                    .eval_const()
+                    .expect("ビットフィールドの初期化式は定数式である必要があります");
+                // packedのビットフィールドは64ビットを超える範囲にまたがることがある
+                let bits = ((val as u64 & (u64::MAX >> (64 - bit_field.width))) as u128)
+                    << bit_field.bit_offset;
+                let end = (bit_field.bit_offset + bit_field.width).div_ceil(8);
+                for i in bit_field.bit_offset / 8..end {
+                    *bit_bytes.entry(elem.offset + i).or_insert(0u8) |= (bits >> (i * 8)) as u8;
+                }
+            }
+            let mut items = elems
+                .into_iter()
+                .filter(|elem| elem.bit_field.is_none())
+                .map(|elem| (elem.offset, elem.ty.size_of(), Some(elem), 0))
+                .chain(
+                    bit_bytes
+                        .into_iter()
+                        .map(|(offset, byte)| (offset, 1, None, byte)),
+                )
+                .collect::<Vec<_>>();
+            items.sort_by_key(|(offset, ..)| *offset);
+            let mut pos = 0;
+            for (offset, size, elem, byte) in items {
+                if offset < pos {
+                    continue; // 共用体などで既に初期化された領域
+                }
+                if offset > pos {
+                    self.builder
+                        .add_row(&format!(".zero {}", offset - pos), true);
+                }
+                match elem {
+                    Some(elem) => self.emit_data_elem(elem),
+                    None => self.builder.add_row(&format!(".byte {}", byte), true),
+                }
+                pos = offset + size;
+            }
+            if pos < gvar.ty.size_of() {
+                self.builder
+                    .add_row(&format!(".zero {}", gvar.ty.size_of() - pos), true);
+            }
+        }
+    }
+
+    // グローバル変数の初期化子の要素を出力する
+    fn emit_data_elem(&mut self, elem: &InitElem) {
+        // long doubleは拡張倍精度の10バイトを16バイトの領域に出力する
+        if elem.ty.kind == TypeKind::LongDouble {
+            let bits = elem
+                .expr
+                .eval_long_double()
+                .expect("浮動小数点数の初期化式は算術定数式である必要があります");
+            self.builder
+                .add_row(&format!(".quad {}", bits as u64), true);
+            self.builder
+                .add_row(&format!(".quad {}", (bits >> 64) as u64), true);
+            return;
+        }
+        let directive = match elem.ty.size_of() {
+            1 => ".byte",
+            2 => ".word",
+            4 => ".long",
+            8 => ".quad",
+            _ => panic!("未対応のグローバル変数初期化サイズ: {}", elem.ty.size_of()),
+        };
+        // 浮動小数点数はビット表現を出力する
+        if elem.ty.is_floating_point() {
+            let val = elem
+                .expr
+                .eval_float()
+                .expect("浮動小数点数の初期化式は算術定数式である必要があります");
+            let bits = match elem.ty.kind {
+                TypeKind::Float => (val as f32).to_bits() as u64,
+                _ => val.to_bits(),
+            };
+            self.builder
+                .add_row(&format!("{} {}", directive, bits), true);
+            return;
+        }
+        // 浮動小数点数の初期化式は0方向に切り捨てて整数にする
+        let val = if elem.expr.is_floating_point() {
+            elem.expr.eval_float().map(|val| {
+                if elem.ty.is_unsigned() {
+                    val as u64 as i64
+                } else {
+                    val as i64
+                }
+            })
+        } else {
+            elem.expr.eval_const()
+        };
+        if let Ok(val) = val {
+            self.builder
+                .add_row(&format!("{} {}", directive, val), true);
+            return;
+        }
+        match &elem.expr.kind {
+            NodeKind::Addr => {
+                if let Some(lhs) = &elem.expr.lhs {
+                    match &lhs.kind {
+                        NodeKind::Var { name, is_local, .. } => {
+                            if !*is_local {
+                                self.builder.add_row(&format!(".quad {}", name), true);
+                            } else {
+                                panic!(
+                                    "グローバル変数の初期化式にローカル変数のアドレスは使用できません: {}",
+                                    name
+                                );
                             }
-                            _ => panic!("未対応のグローバル変数初期化式: {:?}", init.kind),
+                        }
+                        _ => {
+                            panic!(
+                                "未対応のグローバル変数初期化式のアドレス指定: {:?}",
+                                lhs.kind
+                            );
                         }
                     }
-                } else {
-                    // TODO: 配列や構造体の初期化
-                    unimplemented!("配列や構造体のグローバル変数初期化には未対応です");
                 }
-            } else {
+            }
+            // 配列や関数はその先頭のアドレスに変換される
+            NodeKind::Var {
+                name,
+                is_local: false,
+                ..
+            } if elem
+                .expr
+                .ty
+                .as_ref()
+                .is_some_and(|ty| ty.is_array() || ty.is_func()) =>
+            {
+                self.builder.add_row(&format!(".quad {}", name), true);
+            }
+            NodeKind::String { index, .. } => {
                 self.builder
-                    .add_row(&format!(".zero {}", gvar.ty.size_of()), true);
+                    .add_row(&format!(".quad .L.str.{}", index), true);
             }
+            _ => panic!("未対応のグローバル変数初期化式: {:?}", elem.expr.kind),
         }
     }
 
@@ -241,43 +393,109 @@ impl Generator {
                 self.builder
                     .add_row(&format!("sub rsp, {}", stack_size), true);
             }
+            self.stack_size = stack_size;
+            self.vla_stack.clear();
+            self.label_vla_depths.clear();
+            let mut depth = 0;
+            for node in func.body.iter() {
+                Self::collect_label_vla_depths(node, &mut depth, &mut self.label_vla_depths);
+            }
 
-            // ローカル変数をスタックから読み出し
-            for (i, arg) in func.locals.iter().enumerate() {
-                self.builder.add_row(
-                    &format!(
-                        "  mov [rbp-{}], {}",
-                        arg.offset,
-                        ARG_REGS[i].by_size(arg.ty.align_of())
-                    ),
-                    true,
-                );
+            // 可変長引数の関数では、引数レジスタの値をすべて保存領域に書き出す
+            // 汎用レジスタ6個の後にXMMレジスタ8個を16バイト間隔で並べる
+            // XMMレジスタはalに使用数が設定されている場合のみ保存する
+            if let Some(area) = func.va_area {
+                for (i, reg) in ARG_REGS.iter().enumerate() {
+                    self.builder.add_row(
+                        &format!("  mov [rbp-{}], {}", area - i * 8, reg.qword()),
+                        true,
+                    );
+                }
+                let seq = self.label_seq;
+                self.label_seq += 1;
+                self.builder.add_row("  test al, al", true);
+                self.builder.add_row(&format!("  je .L.end.{}", seq), true);
+                for i in 0..8 {
+                    self.builder.add_row(
+                        &format!("  movsd QWORD PTR [rbp-{}], xmm{}", area - 48 - i * 16, i),
+                        true,
+                    );
+                }
+                self.builder.add_row(&format!(".L.end.{}:", seq), false);
+            }
 
-                // initializerがある場合、初期化コードを生成
-                if !arg.init.is_empty() {
-                    if arg.init.len() == 1 {
-                        self.gen_addr(&Some(Box::new(Node {
-                            kind: NodeKind::Var {
-                                name: arg.name.clone(),
-                                offset: arg.offset,
-                                is_local: true,
-                            },
-                            ..Default::default()
-                        }))); // 変数のアドレスをスタックに積む
-                        self.gen_expr(&arg.init[0]); // 初期化式のコードを生成し、スタックに値を積む
-                        self.store(&Some(arg.ty.clone())); // スタックトップの値を変数に格納
-                    } else {
-                        // TODO: 配列や構造体の初期化
-                        unimplemented!("配列や構造体のローカル変数初期化には未対応です");
+            // 引数をレジスタからスタックに書き出し
+            // 戻り値をメモリで返す関数では、書き込み先のアドレスが隠れた第1引数としてrdiで渡される
+            // レジスタが足りない引数などは呼び出し元のスタックで渡されるので、ローカル変数の領域にコピーする
+            // ローカル変数の初期化は宣言の位置で行う
+            self.sret = func.sret;
+            if let Some(offset) = func.sret {
+                self.builder
+                    .add_row(&format!("  mov [rbp-{}], rdi", offset), true);
+            }
+            let (mut gp, mut fp) = (func.sret.is_some() as usize, 0);
+            let mut stack_offset: usize = 0;
+            let mut stack_structs = Vec::new();
+            for arg in func.locals.iter().take(func.param_count) {
+                let locs = Self::arg_locs(&arg.ty, &mut gp, &mut fp, &mut stack_offset);
+                let size = arg.ty.size_of();
+                if let [ArgLoc::Stack(pos)] = locs.as_slice()
+                    && arg.ty.is_struct()
+                {
+                    // スタックで渡された引数は戻りアドレスと退避したrbpの上に並ぶ
+                    // 構造体・共用体のコピーにはrcxを使うため、レジスタの引数をすべて書き出した後に行う
+                    stack_structs.push(((16 + pos) as isize, arg));
+                    continue;
+                }
+                // 引数レジスタと重ならないr11をアドレスの計算に使う
+                let dst = self.local_addr(arg.offset, &arg.ty, "r11");
+                match locs.as_slice() {
+                    [ArgLoc::Stack(pos)] if arg.ty.kind == TypeKind::LongDouble => {
+                        self.builder
+                            .add_row(&format!("  fld TBYTE PTR [rbp+{}]", 16 + pos), true);
+                        self.builder
+                            .add_row(&format!("  fstp TBYTE PTR [{}{:+}]", dst.0, dst.1), true);
+                    }
+                    [ArgLoc::Stack(pos)] => {
+                        self.builder
+                            .add_row(&format!("  mov rax, [rbp+{}]", 16 + pos), true);
+                        self.builder.add_row(
+                            &format!("  mov [{}{:+}], {}", dst.0, dst.1, Reg::Rax.by_size(size)),
+                            true,
+                        );
+                    }
+                    // レジスタで渡された引数（構造体・共用体は8バイトごとに別のレジスタで渡される）
+                    locs => {
+                        for (i, loc) in locs.iter().enumerate() {
+                            let dst = (dst.0, dst.1 + i as isize * 8);
+                            let size = (size - i * 8).min(8);
+                            match loc {
+                                ArgLoc::Reg(r) => self.store_gp(&ARG_REGS[*r], dst, size),
+                                ArgLoc::Xmm(x) => self.store_xmm(*x, dst, size),
+                                ArgLoc::Stack(_) => unreachable!(),
+                            }
+                        }
                     }
                 }
             }
+            for (src, arg) in stack_structs {
+                let dst = self.local_addr(arg.offset, &arg.ty, "r11");
+                self.copy_mem(("rbp", src), dst, arg.ty.size_of());
+            }
+
+            // va_startで名前付きの仮引数の後ろを指せるように、使った引数の位置を記録しておく
+            self.va_info = func.va_area.map(|area| VaInfo {
+                area,
+                gp,
+                fp,
+                stack: stack_offset,
+            });
 
             // 関数本体のコード生成
             for node in func.body.iter() {
                 if node.is_expr() {
                     self.gen_expr(&Some(node.clone()));
-                    self.builder.add_row("pop rax", true); // 式の結果を捨てる
+                    self.discard(&Some(node.clone())); // 式の結果を捨てる
                 } else {
                     self.gen_stmt(&Some(node.clone()));
                 }
@@ -292,6 +510,72 @@ impl Generator {
         self.emit_epilogue();
     }
 
+    // 各ラベルの位置で確保済みの可変長配列の数を求める
+    // gotoで前方のラベルに飛ぶ場合にも、飛び先で解放すべき領域が分かるようにする
+    fn collect_label_vla_depths(node: &Node, depth: &mut usize, depths: &mut Vec<(String, usize)>) {
+        match &node.kind {
+            NodeKind::Block { body } => {
+                let outer = *depth;
+                for node in body.iter() {
+                    Self::collect_label_vla_depths(node, depth, depths);
+                }
+                *depth = outer;
+            }
+            NodeKind::VlaAlloc { .. } => *depth += 1,
+            NodeKind::Label { name } => {
+                depths.push((name.clone(), *depth));
+                if let Some(stmt) = &node.lhs {
+                    Self::collect_label_vla_depths(stmt, depth, depths);
+                }
+            }
+            NodeKind::If { then, els, .. } => {
+                for stmt in [then, els].into_iter().flatten() {
+                    Self::collect_label_vla_depths(stmt, depth, depths);
+                }
+            }
+            NodeKind::While { then, .. }
+            | NodeKind::Do { then, .. }
+            | NodeKind::For { then, .. } => {
+                if let Some(stmt) = then {
+                    Self::collect_label_vla_depths(stmt, depth, depths);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // 可変長配列の領域を、確保した順にdepth個を残して解放する
+    // スタックポインタを残す中で最後に確保した領域の先頭に戻す
+    fn restore_vla_stack(&mut self, depth: usize) {
+        if self.vla_stack.len() <= depth {
+            return;
+        }
+        match depth.checked_sub(1).map(|i| self.vla_stack[i]) {
+            Some(offset) => self
+                .builder
+                .add_row(&format!("mov rsp, [rbp-{}]", offset), true),
+            None => self
+                .builder
+                .add_row(&format!("lea rsp, [rbp-{}]", self.stack_size), true),
+        }
+    }
+
+    // ローカル変数の領域を指すベースレジスタと変位を返す
+    // 16バイトを超えるアラインメントの変数は、確保した領域の中で境界に揃えたアドレスを実行時にregに求める
+    fn local_addr(&mut self, offset: usize, ty: &Type, reg: &'static str) -> (&'static str, isize) {
+        let align = ty.align_of();
+        if align <= 16 {
+            return ("rbp", -(offset as isize));
+        }
+        self.builder.add_row(
+            &format!("lea {}, [rbp-{}]", reg, offset - (align - 16)),
+            true,
+        );
+        self.builder
+            .add_row(&format!("and {}, -{}", reg, align), true);
+        (reg, 0)
+    }
+
     // 変数やデリファレンスのアドレスをスタックに積む
     fn gen_addr(&mut self, n: &Option<Box<Node>>) {
         if let Some(node) = n {
@@ -305,38 +589,88 @@ impl Generator {
                     is_local,
                     ..
                 } => {
-                    if *is_local {
+                    if *is_local && node.ty.as_ref().is_some_and(|ty| ty.is_vla()) {
+                        // 可変長配列の変数は確保した領域のアドレスを保持している
+                        self.builder
+                            .add_row(&format!("mov rax, [rbp-{}]", offset), true);
+                    } else if *is_local {
+                        // ローカル変数のアドレスを計算して取得
+                        let ty = node.ty.as_ref().expect("ローカル変数の型情報がありません");
+                        let (base, disp) = self.local_addr(*offset, ty, "rax");
+                        if base != "rax" {
+                            self.builder
+                                .add_row(&format!("lea rax, [{}{:+}]", base, disp), true);
+                        }
+                    } else if node.ty.as_ref().is_some_and(|ty| ty.is_func()) {
+                        // 他のオブジェクトで定義された関数もあるため、GOTからアドレスを取得
                         self.builder
-                            .add_row(&format!("lea rax, [rbp-{}]", offset), true); // ローカル変数のアドレスを計算して取得
+                            .add_row(&format!("mov rax, QWORD PTR {}@GOTPCREL[rip]", name), true);
                     } else {
                         self.builder
                             .add_row(&format!("lea rax, {}[rip]", name), true); // グローバル変数のアドレスを計算して取得
                     }
                     self.builder.add_row("push rax", true); // 変数のアドレスをスタックに積む
                 }
+                NodeKind::Member { offset, .. } => {
+                    self.gen_addr(&node.lhs); // 構造体・共用体の先頭アドレス
+                    if *offset > 0 {
+                        self.builder
+                            .add_row(&format!("add QWORD PTR [rsp], {}", offset), true);
+                    }
+                }
+                // 代入や関数呼び出しの結果の構造体・共用体は、値としてアドレスを持つ
+                _ if node.ty.as_ref().is_some_and(|ty| ty.is_struct()) => {
+                    self.gen_expr(&Some(node.clone()));
+                }
                 _ => panic!("代入の左辺値が変数ではありません: {:?}", node.kind),
             }
         }
     }
 
+    // メモリにアクセスする命令を追加する
+    // volatileなオブジェクトへのアクセスは最適化で削除・統合されないようにする
+    fn add_access_row(&mut self, row: &str, ty: &Type) {
+        if ty.is_volatile {
+            self.builder.add_volatile_row(row, true);
+        } else {
+            self.builder.add_row(row, true);
+        }
+    }
+
     // スタックトップのアドレスから値を読み出してスタックに積む
     fn load(&mut self, ty: &Option<Box<Type>>) {
         self.builder.add_row("pop rax", true); // ロード先のアドレス
         if let Some(ty) = ty {
-            match ty.align_of() {
+            // long doubleはx87のスタックを経由して16バイトの領域に積む
+            if ty.kind == TypeKind::LongDouble {
+                self.add_access_row("fld TBYTE PTR [rax]", ty);
+                self.push_st0();
+                return;
+            }
+            // 符号なし整数型はゼロ拡張、それ以外は符号拡張する
+            match ty.size_of() {
+                1 if ty.is_unsigned() => {
+                    self.add_access_row("movzx rax, BYTE PTR [rax]", ty); // 1バイト
+                }
                 1 => {
-                    self.builder.add_row("movsx rax, BYTE PTR [rax]", true); // 1バイト
+                    self.add_access_row("movsx rax, BYTE PTR [rax]", ty); // 1バイト
+                }
+                2 if ty.is_unsigned() => {
+                    self.add_access_row("movzx rax, WORD PTR [rax]", ty); // 2バイト
                 }
                 2 => {
-                    self.builder.add_row("movsx rax, WORD PTR [rax]", true); // 2バイト
+                    self.add_access_row("movsx rax, WORD PTR [rax]", ty); // 2バイト
+                }
+                4 if ty.is_unsigned() || ty.is_floating_point() => {
+                    self.add_access_row("mov eax, DWORD PTR [rax]", ty); // 4バイト（上位32ビットはゼロクリアされる）
                 }
                 4 => {
-                    self.builder.add_row("movsxd rax, DWORD PTR [rax]", true); // 4バイト
+                    self.add_access_row("movsxd rax, DWORD PTR [rax]", ty); // 4バイト
                 }
                 8 => {
-                    self.builder.add_row("mov rax, QWORD PTR [rax]", true); // 8バイト
+                    self.add_access_row("mov rax, QWORD PTR [rax]", ty); // 8バイト
                 }
-                _ => panic!("未対応のロードサイズ: {}", ty.align_of()),
+                _ => panic!("未対応のロードサイズ: {}", ty.size_of()),
             }
         } else {
             panic!("load先の型情報がありません: {:?}", ty);
@@ -346,23 +680,50 @@ impl Generator {
 
     // スタックトップの値をアドレスに格納する
     fn store(&mut self, ty: &Option<Box<Type>>) {
+        // long doubleは値をx87のスタックに読み込み、その複製をストアする
+        if let Some(ty) = ty
+            && ty.kind == TypeKind::LongDouble
+        {
+            self.pop_st0();
+            self.builder.add_row("pop rax", true); // ストア先のアドレス
+            self.builder.add_row("fld st(0)", true);
+            self.add_access_row("fstp TBYTE PTR [rax]", ty);
+            self.push_st0(); // ストアした値をスタックに戻す
+            return;
+        }
         self.builder.add_row("pop rdi", true); // ストアする値
         self.builder.add_row("pop rax", true); // ストア先のアドレス
         if let Some(ty) = ty {
-            match ty.align_of() {
+            if ty.is_struct() {
+                // 構造体・共用体は値の指す領域からストア先へコピーする
+                self.builder.add_row("mov rsi, rdi", true);
+                self.builder.add_row("mov rdi, rax", true);
+                self.builder
+                    .add_row(&format!("mov rcx, {}", ty.size_of()), true);
+                self.add_access_row("rep movsb", ty);
+                self.builder.add_row("push rax", true); // ストア先のアドレスを値として戻す
+                return;
+            }
+            if ty.is_bool() {
+                // _Boolへの格納は値を0か1に正規化する
+                self.builder.add_row("cmp rdi, 0", true);
+                self.builder.add_row("setne dil", true);
+                self.builder.add_row("movzx edi, dil", true);
+            }
+            match ty.size_of() {
                 1 => {
-                    self.builder.add_row("mov BYTE PTR [rax], dil", true);
+                    self.add_access_row("mov BYTE PTR [rax], dil", ty);
                 }
                 2 => {
-                    self.builder.add_row("mov WORD PTR [rax], di", true);
+                    self.add_access_row("mov WORD PTR [rax], di", ty);
                 }
                 4 => {
-                    self.builder.add_row("mov DWORD PTR [rax], edi", true);
+                    self.add_access_row("mov DWORD PTR [rax], edi", ty);
                 }
                 8 => {
-                    self.builder.add_row("mov QWORD PTR [rax], rdi", true);
+                    self.add_access_row("mov QWORD PTR [rax], rdi", ty);
                 }
-                _ => panic!("未対応のストアサイズ: {}", ty.align_of()),
+                _ => panic!("未対応のストアサイズ: {}", ty.size_of()),
             }
         } else {
             panic!("store先の型情報がありません: {:?}", ty);
@@ -370,14 +731,677 @@ impl Generator {
         self.builder.add_row("push rdi", true); // ストアした値をスタックに戻す
     }
 
-    // int を 1 加算
-    fn inc(&mut self) {
-        self.builder.add_row("inc QWORD PTR [rsp]", true);
+    // 左辺値のノードがビットフィールドであればその情報を返す
+    fn bit_field_of(n: &Option<Box<Node>>) -> Option<BitField> {
+        match n.as_ref().map(|node| &node.kind) {
+            Some(NodeKind::Member { bit_field, .. }) => *bit_field,
+            _ => None,
+        }
+    }
+
+    // スタックトップのアドレスから左辺値の値を読み出してスタックに積む
+    fn load_lvalue(&mut self, n: &Option<Box<Node>>) {
+        let ty = &n.as_ref().unwrap().ty;
+        let Some(bit_field) = Self::bit_field_of(n) else {
+            self.load(ty);
+            return;
+        };
+        let ty = ty.as_ref().expect("load先の型情報がありません");
+        // 該当するビットをraxの上位に寄せ、宣言された型に従って拡張する
+        let shift = if bit_field.packed {
+            self.builder.add_row("pop rax", true);
+            self.load_packed_bits(bit_field, ty);
+            self.builder.add_row("mov rax, rdx", true);
+            64 - bit_field.width
+        } else {
+            self.load(&Some(ty.clone()));
+            self.builder.add_row("pop rax", true);
+            64 - bit_field.bit_offset - bit_field.width
+        };
+        self.builder.add_row(&format!("shl rax, {}", shift), true);
+        let ext = if ty.is_unsigned() { "shr" } else { "sar" };
+        self.builder
+            .add_row(&format!("{} rax, {}", ext, 64 - bit_field.width), true);
+        self.builder.add_row("push rax", true);
+    }
+
+    // packedのビットフィールドが含まれるバイトを1バイトずつ読み出し、
+    // raxのアドレスから始まるビット列をrdxの下位ビットに集める
+    fn load_packed_bits(&mut self, bit_field: BitField, ty: &Type) {
+        self.builder.add_row("xor edx, edx", true);
+        for i in 0..(bit_field.bit_offset + bit_field.width).div_ceil(8) {
+            self.add_access_row(&format!("movzx ecx, BYTE PTR [rax+{}]", i), ty);
+            if i == 0 {
+                self.builder
+                    .add_row(&format!("shr ecx, {}", bit_field.bit_offset), true);
+            } else {
+                self.builder
+                    .add_row(&format!("shl rcx, {}", i * 8 - bit_field.bit_offset), true);
+            }
+            self.builder.add_row("or rdx, rcx", true);
+        }
+    }
+
+    // rdiの値をraxのアドレスから始まるpackedのビットフィールドに1バイトずつ書き込む
+    fn store_packed_bits(&mut self, bit_field: BitField, ty: &Type) {
+        let (start, end) = (bit_field.bit_offset, bit_field.bit_offset + bit_field.width);
+        for i in 0..end.div_ceil(8) {
+            // このバイトに含まれるビットフィールドのビット
+            let mask = (0..8)
+                .filter(|bit| (start..end).contains(&(i * 8 + bit)))
+                .fold(0u8, |mask, bit| mask | 1 << bit);
+            self.builder.add_row("mov rcx, rdi", true);
+            if i == 0 {
+                self.builder
+                    .add_row(&format!("shl rcx, {}", bit_field.bit_offset), true);
+            } else {
+                self.builder
+                    .add_row(&format!("shr rcx, {}", i * 8 - bit_field.bit_offset), true);
+            }
+            self.builder.add_row(&format!("and ecx, {}", mask), true);
+            self.add_access_row(&format!("movzx edx, BYTE PTR [rax+{}]", i), ty);
+            self.builder.add_row(&format!("and edx, {}", !mask), true);
+            self.builder.add_row("or edx, ecx", true);
+            self.add_access_row(&format!("mov BYTE PTR [rax+{}], dl", i), ty);
+        }
+    }
+
+    // スタックトップの値を左辺値に格納する
+    fn store_lvalue(&mut self, n: &Option<Box<Node>>) {
+        let ty = &n.as_ref().unwrap().ty;
+        let Some(bit_field) = Self::bit_field_of(n) else {
+            self.store(ty);
+            return;
+        };
+        let ty = ty.as_ref().expect("store先の型情報がありません");
+        let size = ty.size_of();
+        self.builder.add_row("pop rdi", true); // ストアする値
+        self.builder.add_row("pop rax", true); // 記憶単位のアドレス
+        if ty.is_bool() {
+            // _Boolへの格納は値を0か1に正規化する
+            self.builder.add_row("cmp rdi, 0", true);
+            self.builder.add_row("setne dil", true);
+            self.builder.add_row("movzx edi, dil", true);
+        }
+        self.builder.add_row("mov rsi, rdi", true);
+        if bit_field.packed {
+            self.store_packed_bits(bit_field, ty);
+        } else {
+            // 記憶単位を読み出し、ビットフィールドの部分だけを書き換える
+            match size {
+                1 => self.add_access_row("movzx edx, BYTE PTR [rax]", ty),
+                2 => self.add_access_row("movzx edx, WORD PTR [rax]", ty),
+                4 => self.add_access_row("mov edx, DWORD PTR [rax]", ty),
+                8 => self.add_access_row("mov rdx, QWORD PTR [rax]", ty),
+                _ => panic!("未対応のビットフィールドの記憶単位: {}", size),
+            }
+            let mask = (u64::MAX >> (64 - bit_field.width)) << bit_field.bit_offset;
+            self.builder
+                .add_row(&format!("movabs rcx, {:#x}", mask), true);
+            self.builder
+                .add_row(&format!("shl rdi, {}", bit_field.bit_offset), true);
+            self.builder.add_row("and rdi, rcx", true);
+            self.builder.add_row("not rcx", true);
+            self.builder.add_row("and rdx, rcx", true);
+            self.builder.add_row("or rdx, rdi", true);
+            let ptr = match size {
+                1 => "BYTE",
+                2 => "WORD",
+                4 => "DWORD",
+                _ => "QWORD",
+            };
+            self.add_access_row(
+                &format!("mov {} PTR [rax], {}", ptr, Reg::Rdx.by_size(size)),
+                ty,
+            );
+        }
+        // 式の値はビットフィールドに格納された値とする
+        let shift = if ty.is_unsigned() { "shr" } else { "sar" };
+        self.builder
+            .add_row(&format!("shl rsi, {}", 64 - bit_field.width), true);
+        self.builder
+            .add_row(&format!("{} rsi, {}", shift, 64 - bit_field.width), true);
+        self.builder.add_row("push rsi", true);
+    }
+
+    // スタックトップの値を指定した型に変換する
+    // 整数はレジスタ全体に符号拡張もしくはゼロ拡張された状態で扱う
+    fn cast(&mut self, to: &Type) {
+        // voidへのキャストでは値を使わないため、変換は行わない
+        // ポインタや64ビット整数への変換は値をそのまま使う
+        if to.kind == TypeKind::Void || to.size_of() == 8 {
+            return;
+        }
+        self.builder.add_row("pop rax", true);
+        if to.is_bool() {
+            self.builder.add_row("cmp rax, 0", true);
+            self.builder.add_row("setne al", true);
+            self.builder.add_row("movzx eax, al", true);
+        } else {
+            match (to.size_of(), to.is_unsigned()) {
+                (1, true) => self.builder.add_row("movzx eax, al", true),
+                (1, false) => self.builder.add_row("movsx rax, al", true),
+                (2, true) => self.builder.add_row("movzx eax, ax", true),
+                (2, false) => self.builder.add_row("movsx rax, ax", true),
+                (4, true) => self.builder.add_row("mov eax, eax", true),
+                (4, false) => self.builder.add_row("movsxd rax, eax", true),
+                (size, _) => panic!("未対応のキャスト先のサイズ: {}", size),
+            }
+        }
+        self.builder.add_row("push rax", true);
+    }
+
+    // 整数型の値をその型の幅に切り詰める
+    // 整数型以外の値はそのまま使う
+    fn cast_to(&mut self, ty: &Option<Box<Type>>) {
+        if let Some(ty) = ty
+            && ty.is_integer()
+        {
+            self.cast(ty);
+        }
+    }
+
+    // スタックトップの値をfrom型からto型に変換する
+    // 浮動小数点型が関わる変換はSSE命令で行い、整数同士の変換はcastに任せる
+    fn convert(&mut self, from: &Type, to: &Type) {
+        if from.kind == TypeKind::LongDouble || to.kind == TypeKind::LongDouble {
+            self.convert_long_double(from, to);
+            return;
+        }
+        match (from.is_floating_point(), to.is_floating_point()) {
+            (false, false) => self.cast(to),
+            (true, true) => {
+                if from.kind == to.kind {
+                    return;
+                }
+                let op = match to.kind {
+                    TypeKind::Float => "cvtsd2ss",
+                    _ => "cvtss2sd",
+                };
+                self.pop_xmm(0);
+                self.builder.add_row(&format!("{} xmm0, xmm0", op), true);
+                self.push_xmm(0, to);
+            }
+            (false, true) => {
+                let s = Self::sse_suffix(to);
+                self.builder.add_row("pop rax", true);
+                if from.kind == TypeKind::ULong {
+                    // 符号付きとして変換できない値は半分にしてから変換して2倍する
+                    // 最下位ビットは丸めのために残す
+                    let seq = self.label_seq;
+                    self.label_seq += 1;
+                    self.builder.add_row("test rax, rax", true);
+                    self.builder.add_row(&format!("js .L.else.{}", seq), true);
+                    self.builder
+                        .add_row(&format!("cvtsi2s{} xmm0, rax", s), true);
+                    self.builder.add_row(&format!("jmp .L.end.{}", seq), true);
+                    self.builder.add_row(&format!(".L.else.{}:", seq), false);
+                    self.builder.add_row("mov rdi, rax", true);
+                    self.builder.add_row("shr rdi, 1", true);
+                    self.builder.add_row("and eax, 1", true);
+                    self.builder.add_row("or rdi, rax", true);
+                    self.builder
+                        .add_row(&format!("cvtsi2s{} xmm0, rdi", s), true);
+                    self.builder.add_row(&format!("adds{} xmm0, xmm0", s), true);
+                    self.builder.add_row(&format!(".L.end.{}:", seq), false);
+                } else {
+                    // それ以外の整数はレジスタ全体に拡張済みなので、64ビットの符号付き整数として変換する
+                    self.builder
+                        .add_row(&format!("cvtsi2s{} xmm0, rax", s), true);
+                }
+                self.push_xmm(0, to);
+            }
+            (true, false) => {
+                let s = Self::sse_suffix(from);
+                self.pop_xmm(0);
+                if to.is_bool() {
+                    // 0.0と-0.0は偽、NaNを含むそれ以外の値は真とする
+                    self.builder.add_row("xorps xmm1, xmm1", true);
+                    self.builder
+                        .add_row(&format!("ucomis{} xmm0, xmm1", s), true);
+                    self.builder.add_row("setne al", true);
+                    self.builder.add_row("setp dl", true);
+                    self.builder.add_row("or al, dl", true);
+                    self.builder.add_row("movzx eax, al", true);
+                    self.builder.add_row("push rax", true);
+                    return;
+                }
+                if to.kind == TypeKind::ULong {
+                    // 2^63以上の値は2^63を引いてから変換し、最上位ビットを立てる
+                    let seq = self.label_seq;
+                    self.label_seq += 1;
+                    let bound = match from.kind {
+                        TypeKind::Float => 2f32.powi(63).to_bits() as u64,
+                        _ => 2f64.powi(63).to_bits(),
+                    };
+                    self.builder
+                        .add_row(&format!("movabs rax, {}", bound), true);
+                    self.builder.add_row("movq xmm1, rax", true);
+                    self.builder
+                        .add_row(&format!("ucomis{} xmm0, xmm1", s), true);
+                    self.builder.add_row(&format!("jae .L.else.{}", seq), true);
+                    self.builder
+                        .add_row(&format!("cvtts{}2si rax, xmm0", s), true);
+                    self.builder.add_row(&format!("jmp .L.end.{}", seq), true);
+                    self.builder.add_row(&format!(".L.else.{}:", seq), false);
+                    self.builder.add_row(&format!("subs{} xmm0, xmm1", s), true);
+                    self.builder
+                        .add_row(&format!("cvtts{}2si rax, xmm0", s), true);
+                    self.builder.add_row("btc rax, 63", true);
+                    self.builder.add_row(&format!(".L.end.{}:", seq), false);
+                } else {
+                    self.builder
+                        .add_row(&format!("cvtts{}2si rax, xmm0", s), true);
+                }
+                self.builder.add_row("push rax", true);
+                // 64ビット整数に変換した値を変換先の幅に切り詰める
+                self.cast(to);
+            }
+        }
+    }
+
+    // long doubleが関わる変換をx87命令で行う
+    fn convert_long_double(&mut self, from: &Type, to: &Type) {
+        if from.kind == to.kind {
+            return;
+        }
+        if to.kind == TypeKind::LongDouble {
+            match from.kind {
+                TypeKind::Float => self.builder.add_row("fld DWORD PTR [rsp]", true),
+                TypeKind::Double => self.builder.add_row("fld QWORD PTR [rsp]", true),
+                _ => {
+                    // 整数は64ビットの符号付き整数として読み込む
+                    // unsigned longで負として読み込まれた値には2^64を足す
+                    self.builder.add_row("fild QWORD PTR [rsp]", true);
+                    if from.kind == TypeKind::ULong {
+                        let seq = self.label_seq;
+                        self.label_seq += 1;
+                        self.builder.add_row("cmp QWORD PTR [rsp], 0", true);
+                        self.builder.add_row(&format!("jge .L.end.{}", seq), true);
+                        self.builder.add_row(
+                            &format!("mov DWORD PTR [rsp], {}", 2f32.powi(64).to_bits()),
+                            true,
+                        );
+                        self.builder.add_row("fadd DWORD PTR [rsp]", true);
+                        self.builder.add_row(&format!(".L.end.{}:", seq), false);
+                    }
+                }
+            }
+            self.builder.add_row("add rsp, 8", true);
+            self.push_st0();
+            return;
+        }
+        // voidへの変換では値を使わないが、他の型の値と同じ8バイトの領域にしておく
+        if to.kind == TypeKind::Void {
+            self.builder.add_row("add rsp, 8", true);
+            return;
+        }
+        self.pop_st0();
+        if to.is_bool() {
+            // 0.0と-0.0は偽、NaNを含むそれ以外の値は真とする
+            self.builder.add_row("fldz", true);
+            self.builder.add_row("fucomip st, st(1)", true);
+            self.builder.add_row("fstp st(0)", true);
+            self.builder.add_row("setne al", true);
+            self.builder.add_row("setp dl", true);
+            self.builder.add_row("or al, dl", true);
+            self.builder.add_row("movzx eax, al", true);
+            self.builder.add_row("push rax", true);
+            return;
+        }
+        self.builder.add_row("sub rsp, 8", true);
+        match to.kind {
+            TypeKind::Float => {
+                self.builder.add_row("fstp DWORD PTR [rsp]", true);
+                self.builder.add_row("mov DWORD PTR [rsp+4], 0", true);
+            }
+            TypeKind::Double => self.builder.add_row("fstp QWORD PTR [rsp]", true),
+            TypeKind::ULong => {
+                // 2^63以上の値は2^63を引いてから変換し、最上位ビットを立てる
+                let seq = self.label_seq;
+                self.label_seq += 1;
+                self.builder.add_row(
+                    &format!("mov DWORD PTR [rsp], {}", 2f32.powi(63).to_bits()),
+                    true,
+                );
+                self.builder.add_row("fld DWORD PTR [rsp]", true);
+                self.builder.add_row("fucomip st, st(1)", true);
+                self.builder.add_row(&format!("ja .L.else.{}", seq), true);
+                self.builder.add_row("fsub DWORD PTR [rsp]", true);
+                self.builder.add_row("fisttp QWORD PTR [rsp]", true);
+                self.builder.add_row("btc QWORD PTR [rsp], 63", true);
+                self.builder.add_row(&format!("jmp .L.end.{}", seq), true);
+                self.builder.add_row(&format!(".L.else.{}:", seq), false);
+                self.builder.add_row("fisttp QWORD PTR [rsp]", true);
+                self.builder.add_row(&format!(".L.end.{}:", seq), false);
+            }
+            _ => {
+                // 0方向に切り捨てて64ビット整数にし、変換先の幅に切り詰める
+                self.builder.add_row("fisttp QWORD PTR [rsp]", true);
+                self.cast(to);
+            }
+        }
+    }
+
+    // スタックトップのlong doubleをx87のスタックに読み込む
+    fn pop_st0(&mut self) {
+        self.builder.add_row("fld TBYTE PTR [rsp]", true);
+        self.builder.add_row("add rsp, 16", true);
+    }
+
+    // x87のスタックトップのlong doubleを取り出し、16バイトの領域に積む
+    fn push_st0(&mut self) {
+        self.builder.add_row("sub rsp, 16", true);
+        self.builder.add_row("fstp TBYTE PTR [rsp]", true);
+    }
+
+    // 型tyの引数を渡す場所を8バイトごとに決め、使ったレジスタとスタックの領域の大きさを進める
+    // 浮動小数点数はXMMレジスタ、それ以外は汎用レジスタに順に割り当て、
+    // 構造体・共用体は8バイトごとの分類に従ってレジスタに割り当てる
+    // レジスタが足りない引数とlong double、メモリに分類される構造体・共用体は全体をスタックで渡す
+    fn arg_locs(ty: &Type, gp: &mut usize, fp: &mut usize, stack_size: &mut usize) -> Vec<ArgLoc> {
+        if let Some(classes) = ty.eightbyte_classes() {
+            let need_gp = classes.iter().filter(|c| **c == ArgClass::Integer).count();
+            let need_fp = classes.len() - need_gp;
+            if *gp + need_gp <= ARG_REGS.len() && *fp + need_fp <= 8 {
+                return classes
+                    .iter()
+                    .map(|class| match class {
+                        ArgClass::Integer => {
+                            *gp += 1;
+                            ArgLoc::Reg(*gp - 1)
+                        }
+                        ArgClass::Sse => {
+                            *fp += 1;
+                            ArgLoc::Xmm(*fp - 1)
+                        }
+                    })
+                    .collect();
+            }
+        }
+        // スタックの引数は8バイト単位で並び、それより大きいアラインメントの型はその境界に揃える
+        let align = Self::stack_arg_align(ty);
+        *stack_size = stack_size.div_ceil(align) * align;
+        let loc = ArgLoc::Stack(*stack_size);
+        *stack_size += ty.size_of().div_ceil(8) * 8;
+        vec![loc]
+    }
+
+    // スタックで渡す引数の境界
+    // typedefに指定されたアラインメントは受け渡しに影響しないため、型本来のアラインメントを使う
+    fn stack_arg_align(ty: &Type) -> usize {
+        Type::from(&ty.kind, false).align_of().max(8)
+    }
+
+    // base+dispの位置からsizeバイト（8バイト以下）を汎用レジスタに読み込み、上位のバイトを0にする
+    // 構造体の末尾を越えて読まないように、半端な大きさは1バイトずつ組み立てる
+    fn load_gp(&mut self, reg: &Reg, (base, disp): (&str, isize), size: usize) {
+        let addr = |i: usize| format!("[{}{:+}]", base, disp + i as isize);
+        match size {
+            8 => self
+                .builder
+                .add_row(&format!("mov {}, QWORD PTR {}", reg.qword(), addr(0)), true),
+            4 => self
+                .builder
+                .add_row(&format!("mov {}, DWORD PTR {}", reg.dword(), addr(0)), true),
+            _ => {
+                self.builder.add_row(
+                    &format!("movzx {}, BYTE PTR {}", reg.dword(), addr(size - 1)),
+                    true,
+                );
+                for i in (0..size - 1).rev() {
+                    self.builder
+                        .add_row(&format!("shl {}, 8", reg.qword()), true);
+                    self.builder
+                        .add_row(&format!("mov {}, BYTE PTR {}", reg.byte(), addr(i)), true);
+                }
+            }
+        }
+    }
+
+    // 汎用レジスタの下位sizeバイト（8バイト以下）をbase+dispの位置に書き込む
+    // 半端な大きさは4, 2, 1バイトの順に書き込み、そのたびにレジスタの値をずらす
+    fn store_gp(&mut self, reg: &Reg, (base, disp): (&str, isize), size: usize) {
+        let mut i = 0;
+        for chunk in [8, 4, 2, 1] {
+            if size - i < chunk {
+                continue;
+            }
+            self.builder.add_row(
+                &format!(
+                    "mov {} PTR [{}{:+}], {}",
+                    ptr_name(chunk),
+                    base,
+                    disp + i as isize,
+                    reg.by_size(chunk)
+                ),
+                true,
+            );
+            i += chunk;
+            if i < size {
+                self.builder
+                    .add_row(&format!("shr {}, {}", reg.qword(), chunk * 8), true);
+            }
+        }
+    }
+
+    // base+dispの位置からsizeバイト（floatの4バイトか8バイト）をXMMレジスタに読み込む
+    fn load_xmm(&mut self, xmm: usize, (base, disp): (&str, isize), size: usize) {
+        let op = if size == 4 { "movss" } else { "movsd" };
+        self.builder.add_row(
+            &format!(
+                "{} xmm{}, {} PTR [{}{:+}]",
+                op,
+                xmm,
+                ptr_name(size),
+                base,
+                disp
+            ),
+            true,
+        );
+    }
+
+    // XMMレジスタの下位sizeバイト（floatの4バイトか8バイト）をbase+dispの位置に書き込む
+    fn store_xmm(&mut self, xmm: usize, (base, disp): (&str, isize), size: usize) {
+        let op = if size == 4 { "movss" } else { "movsd" };
+        self.builder.add_row(
+            &format!(
+                "{} {} PTR [{}{:+}], xmm{}",
+                op,
+                ptr_name(size),
+                base,
+                disp,
+                xmm
+            ),
+            true,
+        );
+    }
+
+    // srcの位置からdstの位置へsizeバイトをrcx経由でコピーする
+    fn copy_mem(
+        &mut self,
+        (src, src_disp): (&str, isize),
+        (dst, dst_disp): (&str, isize),
+        size: usize,
+    ) {
+        let mut i = 0;
+        while i < size {
+            let chunk = [8, 4, 2, 1]
+                .into_iter()
+                .find(|chunk| size - i >= *chunk)
+                .unwrap_or(1);
+            let ptr = ptr_name(chunk);
+            let reg = Reg::Rcx.by_size(chunk);
+            self.builder.add_row(
+                &format!(
+                    "mov {}, {} PTR [{}{:+}]",
+                    reg,
+                    ptr,
+                    src,
+                    src_disp + i as isize
+                ),
+                true,
+            );
+            self.builder.add_row(
+                &format!(
+                    "mov {} PTR [{}{:+}], {}",
+                    ptr,
+                    dst,
+                    dst_disp + i as isize,
+                    reg
+                ),
+                true,
+            );
+            i += chunk;
+        }
+    }
+
+    // スタックトップのva_listから型tyの次の引数を取り出し、そのアドレスをスタックに積む
+    // レジスタで渡された引数は保存領域から、そうでない引数はスタックの領域から取り出す
+    // レジスタで渡された構造体は、tempの位置のローカル変数に8バイトずつコピーする
+    fn gen_va_arg(&mut self, ty: &Type, temp: Option<usize>) {
+        let seq = self.label_seq;
+        self.label_seq += 1;
+        self.builder.add_row("pop rax", true);
+        if let Some(classes) = ty.eightbyte_classes() {
+            // 必要な数のレジスタが残っていなければ、すべてスタックの領域から取り出す
+            let gp = classes.iter().filter(|c| **c == ArgClass::Integer).count();
+            let fp = classes.len() - gp;
+            if gp > 0 {
+                self.builder
+                    .add_row(&format!("cmp DWORD PTR [rax], {}", 48 - gp * 8), true);
+                self.builder.add_row(&format!("ja .L.else.{}", seq), true);
+            }
+            if fp > 0 {
+                self.builder
+                    .add_row(&format!("cmp DWORD PTR [rax+4], {}", 176 - fp * 16), true);
+                self.builder.add_row(&format!("ja .L.else.{}", seq), true);
+            }
+            for (i, class) in classes.iter().enumerate() {
+                let (field, step) = match class {
+                    ArgClass::Integer => (0, 8),
+                    ArgClass::Sse => (4, 16),
+                };
+                self.builder
+                    .add_row(&format!("mov edx, DWORD PTR [rax+{}]", field), true);
+                self.builder.add_row("add rdx, QWORD PTR [rax+16]", true);
+                self.builder
+                    .add_row(&format!("add DWORD PTR [rax+{}], {}", field, step), true);
+                if let Some(offset) = temp {
+                    self.builder.add_row("mov rcx, QWORD PTR [rdx]", true);
+                    self.builder
+                        .add_row(&format!("mov [rbp-{}], rcx", offset - i * 8), true);
+                }
+            }
+            if let Some(offset) = temp {
+                self.builder
+                    .add_row(&format!("lea rdx, [rbp-{}]", offset), true);
+            }
+            self.builder.add_row(&format!("jmp .L.end.{}", seq), true);
+        }
+        // スタックで渡された引数は8バイト境界（それより大きいアラインメントの型はその境界）に並ぶ
+        self.builder.add_row(&format!(".L.else.{}:", seq), false);
+        self.builder.add_row("mov rdx, QWORD PTR [rax+8]", true);
+        let align = Self::stack_arg_align(ty);
+        if align > 8 {
+            self.builder
+                .add_row(&format!("add rdx, {}", align - 1), true);
+            self.builder.add_row(&format!("and rdx, -{}", align), true);
+        }
+        self.builder.add_row(
+            &format!("lea rcx, [rdx+{}]", ty.size_of().div_ceil(8) * 8),
+            true,
+        );
+        self.builder.add_row("mov QWORD PTR [rax+8], rcx", true);
+        self.builder.add_row(&format!(".L.end.{}:", seq), false);
+        self.builder.add_row("push rdx", true);
+    }
+
+    // 式文の値をスタックから捨てる
+    fn discard(&mut self, n: &Option<Box<Node>>) {
+        match n.as_ref().and_then(|node| node.ty.as_ref()) {
+            Some(ty) if ty.kind == TypeKind::LongDouble => {
+                self.builder.add_row("add rsp, 16", true);
+            }
+            _ => self.builder.add_row("pop rax", true),
+        }
+    }
+
+    // 浮動小数点型の演算命令の接尾辞の2文字目（floatはss、doubleはsd）
+    fn sse_suffix(ty: &Type) -> &'static str {
+        match ty.kind {
+            TypeKind::Float => "s",
+            _ => "d",
+        }
+    }
+
+    // スタックトップの浮動小数点数をXMMレジスタに取り出す
+    fn pop_xmm(&mut self, xmm: usize) {
+        self.builder.add_row("pop rax", true);
+        self.builder.add_row(&format!("movq xmm{}, rax", xmm), true);
+    }
+
+    // XMMレジスタの浮動小数点数をスタックに積む
+    // floatは下位32ビットに置き、上位32ビットはゼロクリアする
+    fn push_xmm(&mut self, xmm: usize, ty: &Type) {
+        match ty.kind {
+            TypeKind::Float => self.builder.add_row(&format!("movd eax, xmm{}", xmm), true),
+            _ => self.builder.add_row(&format!("movq rax, xmm{}", xmm), true),
+        }
+        self.builder.add_row("push rax", true);
     }
 
-    // int を 1 減算
-    fn dec(&mut self) {
-        self.builder.add_row("dec QWORD PTR [rsp]", true);
+    // 条件式を評価し、その値をスタックに積む
+    // 浮動小数点数はビット表現ではなく値が0かどうかで判定するため、0か1に変換する
+    fn gen_cond(&mut self, n: &Option<Box<Node>>) {
+        self.gen_expr(n);
+        if let Some(ty) = n.as_ref().and_then(|node| node.ty.as_ref())
+            && ty.is_floating_point()
+        {
+            self.convert(ty, &Type::from(&TypeKind::Bool, false));
+        }
+    }
+
+    // スタックトップの値に1を加算する（浮動小数点数は1.0を加算する）
+    fn inc(&mut self, ty: &Option<Box<Type>>) {
+        match ty {
+            Some(ty) if ty.is_floating_point() => self.float_step(ty, "add"),
+            _ => self.builder.add_row("inc QWORD PTR [rsp]", true),
+        }
+    }
+
+    // スタックトップの値から1を減算する（浮動小数点数は1.0を減算する）
+    fn dec(&mut self, ty: &Option<Box<Type>>) {
+        match ty {
+            Some(ty) if ty.is_floating_point() => self.float_step(ty, "sub"),
+            _ => self.builder.add_row("dec QWORD PTR [rsp]", true),
+        }
+    }
+
+    // スタックトップの浮動小数点数と1.0の演算を行う
+    fn float_step(&mut self, ty: &Type, op: &str) {
+        if ty.kind == TypeKind::LongDouble {
+            self.builder.add_row("fld1", true);
+            self.builder.add_row("fld TBYTE PTR [rsp]", true);
+            self.builder.add_row(&format!("f{} st(0), st(1)", op), true);
+            self.builder.add_row("fstp TBYTE PTR [rsp]", true);
+            self.builder.add_row("fstp st(0)", true);
+            return;
+        }
+        let s = Self::sse_suffix(ty);
+        self.pop_xmm(0);
+        match ty.kind {
+            TypeKind::Float => self
+                .builder
+                .add_row(&format!("mov eax, {}", 1f32.to_bits()), true),
+            _ => self
+                .builder
+                .add_row(&format!("movabs rax, {}", 1f64.to_bits()), true),
+        }
+        self.builder.add_row("movq xmm1, rax", true);
+        self.builder
+            .add_row(&format!("{}s{} xmm0, xmm1", op, s), true);
+        self.push_xmm(0, ty);
     }
 
     // 文のコード生成
@@ -389,7 +1413,7 @@ impl Generator {
                     self.label_seq += 1;
                     if els.is_some() {
                         // else節あり
-                        self.gen_expr(cond);
+                        self.gen_cond(cond);
                         self.builder.add_row("pop rax", true);
                         self.builder.add_row("cmp rax, 0", true);
                         self.builder.add_row(&format!("je .L.else.{}", seq), true);
@@ -400,7 +1424,7 @@ impl Generator {
                         self.builder.add_row(&format!(".L.end.{}:", seq), false);
                     } else {
                         // else節なし
-                        self.gen_expr(cond);
+                        self.gen_cond(cond);
                         self.builder.add_row("pop rax", true);
                         self.builder.add_row("cmp rax, 0", true);
                         self.builder.add_row(&format!("je .L.end.{}", seq), true);
@@ -413,12 +1437,14 @@ impl Generator {
                     self.label_seq += 1;
                     let current_break_seq = self.break_seq;
                     let current_continue_seq = self.continue_seq;
+                    let current_loop_vla_depth = self.loop_vla_depth;
                     self.break_seq = seq;
                     self.continue_seq = seq;
+                    self.loop_vla_depth = self.vla_stack.len();
 
                     self.builder
                         .add_row(&format!(".L.continue.{}:", seq), false);
-                    self.gen_expr(cond);
+                    self.gen_cond(cond);
                     self.builder.add_row("pop rax", true);
                     self.builder.add_row("cmp rax, 0", true);
                     self.builder.add_row(&format!("je .L.break.{}", seq), true);
@@ -429,6 +1455,7 @@ impl Generator {
 
                     self.break_seq = current_break_seq;
                     self.continue_seq = current_continue_seq;
+                    self.loop_vla_depth = current_loop_vla_depth;
                 }
                 NodeKind::For {
                     init,
@@ -440,20 +1467,22 @@ impl Generator {
                     self.label_seq += 1;
                     let current_break_seq = self.break_seq;
                     let current_continue_seq = self.continue_seq;
+                    let current_loop_vla_depth = self.loop_vla_depth;
                     self.break_seq = seq;
                     self.continue_seq = seq;
+                    self.loop_vla_depth = self.vla_stack.len();
 
                     if let Some(init) = init.as_ref() {
                         if init.is_expr() {
                             self.gen_expr(&Some(init.clone()));
-                            self.builder.add_row("pop rax", true); // 初期化式の結果を捨てる
+                            self.discard(&Some(init.clone())); // 初期化式の結果を捨てる
                         } else {
                             self.gen_stmt(&Some(init.clone()));
                         }
                     }
                     self.builder.add_row(&format!(".L.begin.{}:", seq), false);
                     if cond.is_some() {
-                        self.gen_expr(cond);
+                        self.gen_cond(cond);
                         self.builder.add_row("pop rax", true);
                         self.builder.add_row("cmp rax, 0", true);
                         self.builder.add_row(&format!("je .L.break.{}", seq), true);
@@ -464,7 +1493,7 @@ impl Generator {
                     if let Some(inc) = inc.as_ref() {
                         if inc.is_expr() {
                             self.gen_expr(&Some(inc.clone()));
-                            self.builder.add_row("pop rax", true); // 増分式の結果を捨てる
+                            self.discard(&Some(inc.clone())); // 増分式の結果を捨てる
                         } else {
                             self.gen_stmt(&Some(inc.clone()));
                         }
@@ -474,20 +1503,23 @@ impl Generator {
 
                     self.break_seq = current_break_seq;
                     self.continue_seq = current_continue_seq;
+                    self.loop_vla_depth = current_loop_vla_depth;
                 }
                 NodeKind::Do { cond, then } => {
                     let seq = self.label_seq;
                     self.label_seq += 1;
                     let current_break_seq = self.break_seq;
                     let current_continue_seq = self.continue_seq;
+                    let current_loop_vla_depth = self.loop_vla_depth;
                     self.break_seq = seq;
                     self.continue_seq = seq;
+                    self.loop_vla_depth = self.vla_stack.len();
 
                     self.builder.add_row(&format!(".L.begin.{}:", seq), false);
                     self.gen_stmt(then);
                     self.builder
                         .add_row(&format!(".L.continue.{}:", seq), false);
-                    self.gen_expr(cond);
+                    self.gen_cond(cond);
                     self.builder.add_row("pop rax", true);
                     self.builder.add_row("cmp rax, 0", true);
                     self.builder.add_row(&format!("jne .L.begin.{}", seq), true);
@@ -495,51 +1527,124 @@ impl Generator {
 
                     self.break_seq = current_break_seq;
                     self.continue_seq = current_continue_seq;
+                    self.loop_vla_depth = current_loop_vla_depth;
                 }
                 NodeKind::Block { body } => {
+                    let depth = self.vla_stack.len();
                     for node in body.iter() {
                         if node.is_expr() {
                             self.gen_expr(&Some(node.clone()));
-                            self.builder.add_row("pop rax", true); // ブロック内の各文の結果を捨てる
+                            self.discard(&Some(node.clone())); // ブロック内の各文の結果を捨てる
                         } else {
                             self.gen_stmt(&Some(node.clone()));
                         }
                     }
+                    // ブロック内で確保した可変長配列の領域を解放する
+                    self.restore_vla_stack(depth);
+                    self.vla_stack.truncate(depth);
                 }
                 NodeKind::Break => {
+                    self.restore_vla_stack(self.loop_vla_depth);
                     self.builder
                         .add_row(&format!("jmp .L.break.{}", self.break_seq), true);
                 }
                 NodeKind::Continue => {
+                    self.restore_vla_stack(self.loop_vla_depth);
                     self.builder
                         .add_row(&format!("jmp .L.continue.{}", self.continue_seq), true);
                 }
                 NodeKind::Goto { name } => {
+                    if let Some((_, depth)) = self.label_vla_depths.iter().find(|(n, _)| n == name)
+                    {
+                        self.restore_vla_stack(*depth);
+                    }
                     self.builder
                         .add_row(&format!("jmp .L.label.{}.{}", self.func_name, name), true);
                 }
+                NodeKind::VlaAlloc { offset } => {
+                    // 16バイト境界を保つように切り上げたサイズをスタックから確保する
+                    self.gen_expr(&node.lhs);
+                    self.builder.add_row("pop rax", true);
+                    self.builder.add_row("add rax, 15", true);
+                    self.builder.add_row("and rax, -16", true);
+                    self.builder.add_row("sub rsp, rax", true);
+                    self.builder
+                        .add_row(&format!("mov [rbp-{}], rsp", offset), true);
+                    self.vla_stack.push(*offset);
+                }
                 NodeKind::Label { name } => {
                     self.builder
                         .add_row(&format!(".L.label.{}.{}:", self.func_name, name), false);
                     if node.lhs.as_ref().unwrap().is_expr() {
                         self.gen_expr(&node.lhs);
-                        self.builder.add_row("pop rax", true); // ラベル付き文の結果を捨てる
+                        self.discard(&node.lhs); // ラベル付き文の結果を捨てる
                     } else {
                         self.gen_stmt(&node.lhs);
                     }
                 }
                 NodeKind::Return => {
-                    if node.lhs.is_some() {
+                    if let Some(lhs) = &node.lhs {
                         self.gen_expr(&node.lhs);
-                        self.builder.add_row("pop rax", true);
+                        // 浮動小数点数の戻り値はxmm0、long doubleはst(0)で返す
+                        // 構造体・共用体は8バイトごとの分類に従ってレジスタで返すか、
+                        // 呼び出し元が用意した領域に書き込んでそのアドレスをraxで返す
+                        // long doubleのみからなる構造体・共用体はst(0)で返す
+                        if let Some(ty) = lhs.ty.as_ref().filter(|ty| ty.is_struct()) {
+                            self.builder.add_row("pop rax", true);
+                            if let Some(sret) = self.sret {
+                                self.builder
+                                    .add_row(&format!("mov rdx, [rbp-{}]", sret), true);
+                                self.copy_mem(("rax", 0), ("rdx", 0), ty.size_of());
+                                self.builder.add_row("mov rax, rdx", true);
+                            } else if ty.is_x87_aggregate() {
+                                self.builder.add_row("fld TBYTE PTR [rax]", true);
+                            } else {
+                                self.builder.add_row("mov rsi, rax", true);
+                                let classes = ty.eightbyte_classes().unwrap_or_default();
+                                let (mut gp, mut fp) = (0, 0);
+                                for (i, class) in classes.iter().enumerate() {
+                                    let src = ("rsi", i as isize * 8);
+                                    let size = (ty.size_of() - i * 8).min(8);
+                                    match class {
+                                        ArgClass::Integer => {
+                                            self.load_gp(&RET_REGS[gp], src, size);
+                                            gp += 1;
+                                        }
+                                        ArgClass::Sse => {
+                                            self.load_xmm(fp, src, size);
+                                            fp += 1;
+                                        }
+                                    }
+                                }
+                            }
+                        } else if lhs
+                            .ty
+                            .as_ref()
+                            .is_some_and(|ty| ty.kind == TypeKind::LongDouble)
+                        {
+                            self.pop_st0();
+                        } else {
+                            self.builder.add_row("pop rax", true);
+                            if lhs.is_floating_point() {
+                                self.builder.add_row("movq xmm0, rax", true);
+                            }
+                        }
                     }
                     self.builder
                         .add_row(&format!("jmp .L.return.{}", self.func_name), true);
                 }
+                NodeKind::ZeroFill => {
+                    let size = node.lhs.as_ref().unwrap().ty.as_ref().unwrap().size_of();
+                    self.gen_addr(&node.lhs);
+                    self.builder.add_row("pop rdi", true);
+                    self.builder.add_row("xor eax, eax", true);
+                    self.builder.add_row(&format!("mov rcx, {}", size), true);
+                    self.builder.add_row("rep stosb", true);
+                }
                 NodeKind::Nop => {}
                 _ => {
                     self.gen_expr(&Some(node.clone()));
-                    self.builder.add_row("pop rax", true); // 式の結果を捨てる
+                    self.discard(&Some(node.clone())); // 式の結果を捨てる
                 }
             }
         }
@@ -556,55 +1661,156 @@ impl Generator {
             }
             match &node.kind {
                 NodeKind::Number { val } => {
-                    self.builder.add_row(&format!("push {}", val), true);
+                    if i32::try_from(*val).is_ok() {
+                        self.builder.add_row(&format!("push {}", val), true);
+                    } else {
+                        // pushの即値は32ビットまでなのでレジスタを経由する
+                        self.builder.add_row(&format!("movabs rax, {}", val), true);
+                        self.builder.add_row("push rax", true);
+                    }
+                }
+                NodeKind::FloatNumber { ext, .. }
+                    if node
+                        .ty
+                        .as_ref()
+                        .is_some_and(|ty| ty.kind == TypeKind::LongDouble) =>
+                {
+                    // 指数部、仮数部の順に積み、拡張倍精度の10バイトを並べる
+                    self.builder
+                        .add_row(&format!("push {}", (ext >> 64) as u16), true);
+                    self.builder
+                        .add_row(&format!("movabs rax, {}", *ext as u64), true);
+                    self.builder.add_row("push rax", true);
+                }
+                NodeKind::FloatNumber { bits, .. } => {
+                    // floatの値は単精度のビット表現にする
+                    if node
+                        .ty
+                        .as_ref()
+                        .is_some_and(|ty| ty.kind == TypeKind::Float)
+                    {
+                        let bits = (f64::from_bits(*bits) as f32).to_bits();
+                        self.builder.add_row(&format!("mov eax, {}", bits), true);
+                    } else {
+                        self.builder.add_row(&format!("movabs rax, {}", bits), true);
+                    }
+                    self.builder.add_row("push rax", true);
+                }
+                NodeKind::Neg => {
+                    // 符号ビットを反転する
+                    self.gen_expr(&node.lhs);
+                    if node
+                        .ty
+                        .as_ref()
+                        .is_some_and(|ty| ty.kind == TypeKind::Float)
+                    {
+                        self.builder.add_row("btc DWORD PTR [rsp], 31", true);
+                    } else if node
+                        .ty
+                        .as_ref()
+                        .is_some_and(|ty| ty.kind == TypeKind::LongDouble)
+                    {
+                        self.builder.add_row("btc WORD PTR [rsp+8], 15", true);
+                    } else {
+                        self.builder.add_row("btc QWORD PTR [rsp], 63", true);
+                    }
                 }
                 NodeKind::String { index, .. } => {
                     self.builder
                         .add_row(&format!("lea rax, .L.str.{}[rip]", index), true); // RIP相対アドレッシング
                     self.builder.add_row("push rax", true); // 文字列リテラルのアドレスをスタックに積む
                 }
-                NodeKind::Var { .. } => {
+                NodeKind::Var { .. } | NodeKind::Member { .. } => {
                     self.gen_addr(&Some(node.clone()));
+                    // 配列や構造体・共用体、関数はアドレスをそのまま値として扱う
                     if let Some(ty) = &node.ty
                         && !ty.is_array()
+                        && !ty.is_struct()
+                        && !ty.is_func()
                     {
-                        self.load(&Some(ty.clone()));
+                        self.load_lvalue(&Some(node.clone()));
                     }
                 }
                 NodeKind::Assign => {
                     self.gen_addr(&node.lhs);
                     self.gen_expr(&node.rhs);
-                    self.store(&node.lhs.as_ref().unwrap().ty);
+                    self.store_lvalue(&node.lhs);
                 }
                 NodeKind::PreInc => {
                     self.gen_addr(&node.lhs);
                     self.builder.add_row("push [rsp]", true);
-                    self.load(&node.lhs.as_ref().unwrap().ty);
-                    self.inc();
-                    self.store(&node.lhs.as_ref().unwrap().ty);
+                    self.load_lvalue(&node.lhs);
+                    self.inc(&node.ty);
+                    self.cast_to(&node.ty);
+                    self.store_lvalue(&node.lhs);
                 }
                 NodeKind::PreDec => {
                     self.gen_addr(&node.lhs);
                     self.builder.add_row("push [rsp]", true);
-                    self.load(&node.lhs.as_ref().unwrap().ty);
-                    self.dec();
-                    self.store(&node.lhs.as_ref().unwrap().ty);
+                    self.load_lvalue(&node.lhs);
+                    self.dec(&node.ty);
+                    self.cast_to(&node.ty);
+                    self.store_lvalue(&node.lhs);
+                }
+                NodeKind::PostInc | NodeKind::PostDec
+                    if node
+                        .ty
+                        .as_ref()
+                        .is_some_and(|ty| ty.kind == TypeKind::LongDouble) =>
+                {
+                    // x87のスタックに元の値を残しておき、1.0を加減算した値をストアする
+                    let ty = node.ty.as_ref().unwrap();
+                    let op = if node.kind == NodeKind::PostInc {
+                        "add"
+                    } else {
+                        "sub"
+                    };
+                    self.gen_addr(&node.lhs);
+                    self.builder.add_row("push [rsp]", true);
+                    self.load_lvalue(&node.lhs);
+                    self.builder.add_row("fld TBYTE PTR [rsp]", true);
+                    self.builder.add_row("mov rax, [rsp+16]", true);
+                    self.builder.add_row("add rsp, 24", true);
+                    self.builder.add_row("fld1", true);
+                    self.builder.add_row("fld st(1)", true);
+                    self.builder.add_row(&format!("f{} st(0), st(1)", op), true);
+                    self.add_access_row("fstp TBYTE PTR [rax]", ty);
+                    self.builder.add_row("fstp st(0)", true);
+                    self.push_st0();
+                }
+                NodeKind::PostInc | NodeKind::PostDec if node.is_floating_point() => {
+                    // 1.0の加減算を元に戻すと誤差が出るため、元の値を複製して残す
+                    self.gen_addr(&node.lhs);
+                    self.builder.add_row("push [rsp]", true);
+                    self.load_lvalue(&node.lhs);
+                    self.builder.add_row("pop rax", true);
+                    self.builder.add_row("pop rdi", true);
+                    self.builder.add_row("push rax", true);
+                    self.builder.add_row("push rdi", true);
+                    self.builder.add_row("push rax", true);
+                    if node.kind == NodeKind::PostInc {
+                        self.inc(&node.ty);
+                    } else {
+                        self.dec(&node.ty);
+                    }
+                    self.store_lvalue(&node.lhs);
+                    self.builder.add_row("pop rax", true);
                 }
                 NodeKind::PostInc => {
                     self.gen_addr(&node.lhs);
                     self.builder.add_row("push [rsp]", true);
-                    self.load(&node.lhs.as_ref().unwrap().ty);
-                    self.inc();
-                    self.store(&node.lhs.as_ref().unwrap().ty);
-                    self.dec();
+                    self.load_lvalue(&node.lhs);
+                    self.inc(&node.ty);
+                    self.store_lvalue(&node.lhs);
+                    self.dec(&node.ty);
                 }
                 NodeKind::PostDec => {
                     self.gen_addr(&node.lhs);
                     self.builder.add_row("push [rsp]", true);
-                    self.load(&node.lhs.as_ref().unwrap().ty);
-                    self.dec();
-                    self.store(&node.lhs.as_ref().unwrap().ty);
-                    self.inc();
+                    self.load_lvalue(&node.lhs);
+                    self.dec(&node.ty);
+                    self.store_lvalue(&node.lhs);
+                    self.inc(&node.ty);
                 }
                 NodeKind::AddAssign
                 | NodeKind::SubAssign
@@ -618,15 +1824,24 @@ impl Generator {
                 | NodeKind::ShrAssign => {
                     self.gen_addr(&node.lhs);
                     self.builder.add_row("push [rsp]", true);
-                    self.load(&node.lhs.as_ref().unwrap().ty);
+                    self.load_lvalue(&node.lhs);
+                    // 左辺の値を演算を行う型に変換し、演算結果を左辺の型に戻す
+                    let lhs_ty = node.lhs.as_ref().and_then(|lhs| lhs.ty.as_deref());
+                    let op_ty = node.compound_op_type();
+                    if let (Some(lhs_ty), Some(op_ty)) = (lhs_ty, &op_ty) {
+                        self.convert(lhs_ty, op_ty);
+                    }
                     self.gen_expr(&node.rhs);
                     self.gen_binary(&Some(node.clone()));
-                    self.store(&node.lhs.as_ref().unwrap().ty);
+                    if let (Some(op_ty), Some(ty)) = (&op_ty, &node.ty) {
+                        self.convert(op_ty, ty);
+                    }
+                    self.store_lvalue(&node.lhs);
                 }
                 NodeKind::Ternary { cond, then, els } => {
                     let seq = self.label_seq;
                     self.label_seq += 1;
-                    self.gen_expr(cond);
+                    self.gen_cond(cond);
                     self.builder.add_row("pop rax", true);
                     self.builder.add_row("cmp rax, 0", true);
                     self.builder.add_row(&format!("je .L.else.{}", seq), true);
@@ -637,7 +1852,7 @@ impl Generator {
                     self.builder.add_row(&format!(".L.end.{}:", seq), false);
                 }
                 NodeKind::LogicalNot => {
-                    self.gen_expr(&node.lhs);
+                    self.gen_cond(&node.lhs);
                     self.builder.add_row("pop rax", true);
                     self.builder.add_row("cmp rax, 0", true);
                     self.builder.add_row("sete al", true);
@@ -647,28 +1862,67 @@ impl Generator {
                 NodeKind::BitNot => {
                     self.gen_expr(&node.lhs);
                     self.builder.add_row("not QWORD PTR [rsp]", true);
+                    self.cast_to(&node.ty);
                 }
                 NodeKind::Addr => {
                     self.gen_addr(&node.lhs);
                 }
+                NodeKind::Cast => {
+                    self.gen_expr(&node.lhs);
+                    match (node.lhs.as_ref().and_then(|lhs| lhs.ty.as_ref()), &node.ty) {
+                        (Some(from), Some(to)) => self.convert(from, to),
+                        (None, Some(to)) => self.cast(to),
+                        _ => {}
+                    }
+                }
                 NodeKind::Deref => {
                     self.gen_expr(&node.lhs);
-                    // 型が配列でない場合にロード
-                    // 配列型の場合、アドレスをスタックに積むだけ
+                    // 型が配列や構造体・共用体、関数でない場合にロード
+                    // それらの型の場合、アドレスをスタックに積むだけ
                     if let Some(ty) = &node.ty
                         && !ty.is_array()
+                        && !ty.is_struct()
+                        && !ty.is_func()
                     {
                         self.load(&Some(ty.clone()));
                     }
                 }
+                NodeKind::VaStart => {
+                    let Some(va) = self.va_info else {
+                        panic!("可変長引数の関数の外でva_startが使われました");
+                    };
+                    // 次に取り出す引数の位置を、名前付きの仮引数の後ろに設定する
+                    self.gen_expr(&node.lhs);
+                    self.builder.add_row("pop rax", true);
+                    self.builder.add_row(
+                        &format!("mov DWORD PTR [rax], {}", va.gp.min(ARG_REGS.len()) * 8),
+                        true,
+                    );
+                    self.builder.add_row(
+                        &format!("mov DWORD PTR [rax+4], {}", 48 + va.fp.min(8) * 16),
+                        true,
+                    );
+                    self.builder
+                        .add_row(&format!("lea rdx, [rbp+{}]", 16 + va.stack), true);
+                    self.builder.add_row("mov QWORD PTR [rax+8], rdx", true);
+                    self.builder
+                        .add_row(&format!("lea rdx, [rbp-{}]", va.area), true);
+                    self.builder.add_row("mov QWORD PTR [rax+16], rdx", true);
+                    self.builder.add_row("push rax", true); // voidの値の代わり
+                }
+                NodeKind::VaArg { temp } => {
+                    self.gen_expr(&node.lhs);
+                    let ty = node.ty.as_ref().unwrap().base_type().clone();
+                    self.gen_va_arg(&ty, *temp);
+                }
                 NodeKind::LogicalAnd => {
                     let seq = self.label_seq;
                     self.label_seq += 1;
-                    self.gen_expr(&node.lhs);
+                    self.gen_cond(&node.lhs);
                     self.builder.add_row("pop rax", true);
                     self.builder.add_row("cmp rax, 0", true);
                     self.builder.add_row(&format!("je .L.false.{}", seq), true);
-                    self.gen_expr(&node.rhs);
+                    self.gen_cond(&node.rhs);
                     self.builder.add_row("pop rax", true);
                     self.builder.add_row("cmp rax, 0", true);
                     self.builder.add_row(&format!("je .L.false.{}", seq), true);
@@ -681,11 +1935,11 @@ impl Generator {
                 NodeKind::LogicalOr => {
                     let seq = self.label_seq;
                     self.label_seq += 1;
-                    self.gen_expr(&node.lhs);
+                    self.gen_cond(&node.lhs);
                     self.builder.add_row("pop rax", true);
                     self.builder.add_row("cmp rax, 0", true);
                     self.builder.add_row(&format!("jne .L.true.{}", seq), true);
-                    self.gen_expr(&node.rhs);
+                    self.gen_cond(&node.rhs);
                     self.builder.add_row("pop rax", true);
                     self.builder.add_row("cmp rax, 0", true);
                     self.builder.add_row(&format!("jne .L.true.{}", seq), true);
@@ -695,54 +1949,304 @@ impl Generator {
                     self.builder.add_row("push 1", true);
                     self.builder.add_row(&format!(".L.end.{}:", seq), false);
                 }
-                NodeKind::Call { name, args } => {
-                    let arg_count = args.len();
-
-                    if arg_count > 6 {
-                        panic!("6個を超える引数の関数呼び出しには対応していません");
-                    }
-
+                NodeKind::Call { args, ret_buf } => {
                     // 引数をスタックに積む（逆順）
                     for arg in args.iter().rev() {
                         self.gen_expr(&Some(arg.clone()));
                     }
 
-                    // 引数をレジスタに移動
-                    for reg in ARG_REGS.iter().take(arg_count) {
-                        self.builder.add_row(&format!("pop {}", reg.qword()), true);
+                    // 関数名で直接呼び出せない場合は、関数のアドレスを求めてr11に入れる
+                    let callee = node.lhs.as_ref().unwrap();
+                    let direct = match &callee.kind {
+                        NodeKind::Var {
+                            name,
+                            is_local: false,
+                            ..
+                        } if callee.ty.as_ref().is_some_and(|ty| ty.is_func()) => Some(name),
+                        _ => None,
+                    };
+                    if direct.is_none() {
+                        self.gen_expr(&node.lhs);
+                        self.builder.add_row("pop r11", true);
                     }
 
-                    // 関数呼び出し（アラインメントは揃っているはず）
-                    self.builder.add_row("mov al, 0", true); // 浮動小数点は使わないので0に設定
-                    self.builder.add_row(&format!("call {}", name), true); // 関数呼び出し
-                    self.builder.add_row("push rax", true); // 戻り値をスタックに積む
+                    // 構造体・共用体の戻り値の分類（メモリかst(0)で返す場合はNone）
+                    let ret_struct = node.ty.as_ref().filter(|ty| ty.is_struct());
+                    let ret_x87 = ret_struct.is_some_and(|ty| ty.is_x87_aggregate());
+                    let ret_classes = ret_struct.map(|ty| ty.eightbyte_classes());
+                    let sret = matches!(ret_classes, Some(None)) && !ret_x87;
+
+                    // 引数の渡し方を決める
+                    // 戻り値をメモリで返す場合は、書き込み先のアドレスを隠れた第1引数にする
+                    let (mut gp, mut fp) = (sret as usize, 0);
+                    let mut offset = 0; // 評価した引数の領域での位置
+                    let mut stack_size: usize = 0; // スタックで渡す引数の領域の大きさ
+                    let mut locs = Vec::new();
+                    for arg in args.iter() {
+                        let ty = arg.ty.as_ref().unwrap().lvalue_converted();
+                        let loc = Self::arg_locs(&ty, &mut gp, &mut fp, &mut stack_size);
+                        // 評価した値はlong double以外は8バイト（構造体・共用体はアドレス）
+                        let size = if ty.kind == TypeKind::LongDouble {
+                            16
+                        } else {
+                            8
+                        };
+                        locs.push((offset, ty, loc));
+                        offset += size;
+                    }
+
+                    // 式の評価途中で積んだ値があるため、スタックで渡す引数の領域を16バイト境界に揃えて確保する
+                    // 評価した引数の領域の先頭をr10に入れ、元のスタックポインタとして確保した領域の直後に保存しておく
+                    // 16バイトより大きいアラインメントの引数があれば、領域をその境界に揃える
+                    let stack_align = locs
+                        .iter()
+                        .filter(|(.., loc)| matches!(loc.as_slice(), [ArgLoc::Stack(_)]))
+                        .map(|(_, ty, _)| Self::stack_arg_align(ty))
+                        .fold(16, usize::max);
+                    self.builder.add_row("mov r10, rsp", true);
+                    self.builder
+                        .add_row(&format!("sub rsp, {}", stack_size + 8), true);
+                    self.builder
+                        .add_row(&format!("and rsp, -{}", stack_align), true);
+                    self.builder
+                        .add_row(&format!("mov [rsp+{}], r10", stack_size), true);
+                    // スタックで渡す引数のコピーにはrcxを使うため、レジスタに入れる前に行う
+                    for (offset, ty, loc) in locs.iter() {
+                        let [ArgLoc::Stack(pos)] = loc.as_slice() else {
+                            continue;
+                        };
+                        if ty.is_struct() {
+                            self.builder
+                                .add_row(&format!("mov rax, [r10+{}]", offset), true);
+                            self.copy_mem(("rax", 0), ("rsp", *pos as isize), ty.size_of());
+                        } else {
+                            let size = ty.size_of().div_ceil(8) * 8;
+                            self.copy_mem(("r10", *offset as isize), ("rsp", *pos as isize), size);
+                        }
+                    }
+                    for (offset, ty, loc) in locs.iter() {
+                        if ty.is_struct() {
+                            // 構造体・共用体は8バイトずつレジスタに読み込む
+                            if let [ArgLoc::Stack(_)] = loc.as_slice() {
+                                continue;
+                            }
+                            self.builder
+                                .add_row(&format!("mov rax, [r10+{}]", offset), true);
+                            for (i, loc) in loc.iter().enumerate() {
+                                let src = ("rax", i as isize * 8);
+                                let size = (ty.size_of() - i * 8).min(8);
+                                match loc {
+                                    ArgLoc::Reg(r) => self.load_gp(&ARG_REGS[*r], src, size),
+                                    ArgLoc::Xmm(x) => self.load_xmm(*x, src, size),
+                                    ArgLoc::Stack(_) => unreachable!(),
+                                }
+                            }
+                            continue;
+                        }
+                        match loc.as_slice() {
+                            [ArgLoc::Reg(i)] => self.builder.add_row(
+                                &format!("mov {}, [r10+{}]", ARG_REGS[*i].qword(), offset),
+                                true,
+                            ),
+                            [ArgLoc::Xmm(i)] => self
+                                .builder
+                                .add_row(&format!("movq xmm{}, [r10+{}]", i, offset), true),
+                            _ => {}
+                        }
+                    }
+                    if sret && let Some(buf) = ret_buf {
+                        self.builder
+                            .add_row(&format!("lea rdi, [rbp-{}]", buf), true);
+                    }
+                    // 可変長引数の関数のために、使用したXMMレジスタの数をalに設定する
+                    self.builder.add_row(&format!("mov al, {}", fp), true);
+                    match direct {
+                        Some(name) => self.builder.add_row(&format!("call {}", name), true),
+                        None => self.builder.add_row("call r11", true),
+                    }
+                    // スタックポインタを戻し、評価した引数を捨てる
+                    self.builder
+                        .add_row(&format!("mov rsp, [rsp+{}]", stack_size), true);
+                    if offset > 0 {
+                        self.builder.add_row(&format!("add rsp, {}", offset), true);
+                    }
+                    // 戻り値をスタックに積む（浮動小数点数はxmm0、long doubleはst(0)で返される）
+                    // 構造体・共用体はレジスタやst(0)で返された値を領域に書き出し、そのアドレスを積む
+                    // メモリで返された場合は、raxが渡した領域のアドレスになっている
+                    match &node.ty {
+                        Some(ty) if ty.is_struct() => {
+                            if ret_x87 && let Some(buf) = ret_buf {
+                                self.builder
+                                    .add_row(&format!("fstp TBYTE PTR [rbp-{}]", buf), true);
+                                self.builder
+                                    .add_row(&format!("lea rax, [rbp-{}]", buf), true);
+                            } else if let (Some(Some(classes)), Some(buf)) = (&ret_classes, ret_buf)
+                            {
+                                let (mut gp, mut fp) = (0, 0);
+                                for (i, class) in classes.iter().enumerate() {
+                                    let dst = buf - i * 8;
+                                    match class {
+                                        ArgClass::Integer => {
+                                            self.builder.add_row(
+                                                &format!(
+                                                    "mov [rbp-{}], {}",
+                                                    dst,
+                                                    RET_REGS[gp].qword()
+                                                ),
+                                                true,
+                                            );
+                                            gp += 1;
+                                        }
+                                        ArgClass::Sse => {
+                                            self.builder.add_row(
+                                                &format!(
+                                                    "movsd QWORD PTR [rbp-{}], xmm{}",
+                                                    dst, fp
+                                                ),
+                                                true,
+                                            );
+                                            fp += 1;
+                                        }
+                                    }
+                                }
+                                self.builder
+                                    .add_row(&format!("lea rax, [rbp-{}]", buf), true);
+                            }
+                            self.builder.add_row("push rax", true);
+                        }
+                        Some(ty) if ty.kind == TypeKind::LongDouble => self.push_st0(),
+                        Some(ty) if ty.is_floating_point() => self.push_xmm(0, ty),
+                        _ => {
+                            self.builder.add_row("push rax", true);
+                            self.cast_to(&node.ty);
+                        }
+                    }
                 }
                 _ => {
                     // 二項演算子
                     self.gen_expr(&node.lhs);
                     self.gen_expr(&node.rhs);
                     self.gen_binary(&Some(node.clone()));
+                    if !matches!(
+                        node.kind,
+                        NodeKind::Eq | NodeKind::Ne | NodeKind::Lt | NodeKind::Le
+                    ) {
+                        // 64ビットで演算した結果を結果型の幅に切り詰める
+                        self.cast_to(&node.ty);
+                    }
                 }
             }
         }
     }
 
+    // raxをrdiで割り、商をrax、余りをrdxに格納する
+    fn gen_div(&mut self, unsigned: bool) {
+        if unsigned {
+            self.builder.add_row("xor edx, edx", true);
+            self.builder.add_row("div rdi", true);
+        } else {
+            self.builder.add_row("cqo", true);
+            self.builder.add_row("idiv rdi", true);
+        }
+    }
+
+    // 浮動小数点数として演算を行う場合はその型を返す
+    fn float_op_type(node: &Node) -> Option<Type> {
+        let ty = match node.kind {
+            // 比較演算はオペランドの型で決まる
+            NodeKind::Eq | NodeKind::Ne | NodeKind::Lt | NodeKind::Le => {
+                node.lhs.as_ref()?.ty.as_deref()?.clone()
+            }
+            NodeKind::AddAssign
+            | NodeKind::SubAssign
+            | NodeKind::MulAssign
+            | NodeKind::DivAssign => node.compound_op_type()?,
+            _ => node.ty.as_deref()?.clone(),
+        };
+        ty.is_floating_point().then_some(ty)
+    }
+
+    // 浮動小数点数の二項演算（long doubleはx87命令、それ以外はSSE命令で行う）
+    // NaNとの比較は!=以外が偽になるよう、フラグの組み合わせを選ぶ
+    fn gen_float_binary(&mut self, node: &Node, ty: &Type) {
+        let x87 = ty.kind == TypeKind::LongDouble;
+        let s = Self::sse_suffix(ty);
+        let op = match node.kind {
+            NodeKind::Add | NodeKind::AddAssign => "add",
+            NodeKind::Sub | NodeKind::SubAssign => "sub",
+            NodeKind::Mul | NodeKind::MulAssign => "mul",
+            NodeKind::Div | NodeKind::DivAssign => "div",
+            NodeKind::Eq | NodeKind::Ne | NodeKind::Lt | NodeKind::Le => {
+                // 右オペランドと左オペランドを比べ、a < b は b > a として判定する
+                // 順序付けられない場合はCFが立つので偽になる
+                if x87 {
+                    self.builder.add_row("fld TBYTE PTR [rsp+16]", true);
+                    self.builder.add_row("fld TBYTE PTR [rsp]", true);
+                    self.builder.add_row("add rsp, 32", true);
+                    self.builder.add_row("fucomip st, st(1)", true);
+                    self.builder.add_row("fstp st(0)", true);
+                } else {
+                    self.pop_xmm(1); // 右オペランド
+                    self.pop_xmm(0); // 左オペランド
+                    self.builder
+                        .add_row(&format!("ucomis{} xmm1, xmm0", s), true);
+                }
+                match node.kind {
+                    NodeKind::Eq => {
+                        self.builder.add_row("sete al", true);
+                        self.builder.add_row("setnp dl", true);
+                        self.builder.add_row("and al, dl", true);
+                    }
+                    NodeKind::Ne => {
+                        self.builder.add_row("setne al", true);
+                        self.builder.add_row("setp dl", true);
+                        self.builder.add_row("or al, dl", true);
+                    }
+                    NodeKind::Lt => self.builder.add_row("seta al", true),
+                    _ => self.builder.add_row("setae al", true),
+                }
+                self.builder.add_row("movzx eax, al", true);
+                self.builder.add_row("push rax", true);
+                return;
+            }
+            _ => panic!("浮動小数点数に未対応の演算です: {:?}", node.kind),
+        };
+        if x87 {
+            // st(0)に左オペランド、st(1)に右オペランドを置いて演算する
+            self.builder.add_row("fld TBYTE PTR [rsp]", true);
+            self.builder.add_row("fld TBYTE PTR [rsp+16]", true);
+            self.builder.add_row("add rsp, 32", true);
+            self.builder.add_row(&format!("f{} st(0), st(1)", op), true);
+            self.builder.add_row("fstp st(1)", true);
+            self.push_st0();
+        } else {
+            self.pop_xmm(1); // 右オペランド
+            self.pop_xmm(0); // 左オペランド
+            self.builder
+                .add_row(&format!("{}s{} xmm0, xmm1", op, s), true);
+            self.push_xmm(0, ty);
+        }
+    }
+
     fn gen_binary(&mut self, n: &Option<Box<Node>>) {
         if let Some(node) = n {
+            if let Some(ty) = Self::float_op_type(node) {
+                self.gen_float_binary(node, &ty);
+                return;
+            }
             self.builder.add_row("pop rdi", true); // 右オペランド
             self.builder.add_row("pop rax", true); // 左オペランド
 
+            let unsigned = node.is_unsigned_op();
             match node.kind {
                 NodeKind::Add | NodeKind::AddAssign => self.builder.add_row("add rax, rdi", true),
                 NodeKind::Sub | NodeKind::SubAssign => self.builder.add_row("sub rax, rdi", true),
                 NodeKind::Mul | NodeKind::MulAssign => self.builder.add_row("imul rax, rdi", true),
                 NodeKind::Div | NodeKind::DivAssign => {
-                    self.builder.add_row("cqo", true);
-                    self.builder.add_row("idiv rdi", true);
+                    self.gen_div(unsigned);
                 }
                 NodeKind::Rem | NodeKind::RemAssign => {
-                    self.builder.add_row("cqo", true);
-                    self.builder.add_row("idiv rdi", true);
+                    self.gen_div(unsigned);
                     self.builder.add_row("mov rax, rdx", true);
                 }
                 NodeKind::BitAnd | NodeKind::BitAndAssign => {
@@ -760,7 +2264,11 @@ impl Generator {
                 }
                 NodeKind::Shr | NodeKind::ShrAssign => {
                     self.builder.add_row("mov cl, dil", true);
-                    self.builder.add_row("shr rax, cl", true);
+                    if unsigned {
+                        self.builder.add_row("shr rax, cl", true); // 論理シフト
+                    } else {
+                        self.builder.add_row("sar rax, cl", true); // 算術シフト
+                    }
                 }
                 NodeKind::Eq => {
                     self.builder.add_row("cmp rax, rdi", true);
@@ -774,12 +2282,20 @@ impl Generator {
                 }
                 NodeKind::Lt => {
                     self.builder.add_row("cmp rax, rdi", true);
-                    self.builder.add_row("setl al", true);
+                    if unsigned {
+                        self.builder.add_row("setb al", true);
+                    } else {
+                        self.builder.add_row("setl al", true);
+                    }
                     self.builder.add_row("movzx eax, al", true);
                 }
                 NodeKind::Le => {
                     self.builder.add_row("cmp rax, rdi", true);
-                    self.builder.add_row("setle al", true);
+                    if unsigned {
+                        self.builder.add_row("setbe al", true);
+                    } else {
+                        self.builder.add_row("setle al", true);
+                    }
                     self.builder.add_row("movzx eax, al", true);
                 }
                 _ => {}
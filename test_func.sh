@@ -11,8 +11,9 @@ cc -c ./bin/func.c -o ./bin/func.o
 assert() {
   expected="$1"
   input="$2"
+  flags="$3" # 追加のコンパイラオプション
 
-  ./target/debug/c-compiler -i "$input" > ./bin/tmp.s || {
+  ./target/debug/c-compiler $flags -i "$input" > ./bin/tmp.s || {
     echo -e "\033[31m( ERROR )\033[0m Compilation failed: $input"
     exit 1
   }
@@ -35,6 +36,37 @@ assert() {
   fi
 }
 
+# ソースをinput_charsetに変換したファイルからコンパイルする
+assert_charset() {
+  expected="$1"
+  input_charset="$2"
+  exec_charset="$3"
+  input="$4"
+
+  echo "$input" | iconv -f UTF-8 -t "$input_charset" > ./bin/tmp.c
+  ./target/debug/c-compiler -f ./bin/tmp.c -finput-charset="$input_charset" -fexec-charset="$exec_charset" > ./bin/tmp.s || {
+    echo -e "\033[31m( ERROR )\033[0m Compilation failed: [$input_charset -> $exec_charset] $input"
+    exit 1
+  }
+
+  cc -g -o ./bin/tmp ./bin/tmp.s ./bin/func.o || {
+    echo -e "\033[31m( ERROR )\033[0m Linking failed: [$input_charset -> $exec_charset] $input"
+    exit 1
+  }
+
+  set +e
+  ./bin/tmp
+  actual="$?"
+  set -e
+
+  if [ "$actual" = "$expected" ]; then
+    echo -e "\033[32m( OK )\033[0m [$input_charset -> $exec_charset] $input => $actual"
+  else
+    echo -e "\033[31m( NG )\033[0m [$input_charset -> $exec_charset] $input => $expected expected, but got $actual"
+    exit 1
+  fi
+}
+
 assert 3 '
 int main() {
     return foo();
@@ -173,4 +205,706 @@ int main() {
     return **f;
 }'
 
+echo + _Bool and bool
+assert 15 '
+_Bool f(int x) {
+    return x;
+}
+int main() {
+    _Bool b = 256;
+    _Bool c;
+    c = 0;
+    c += 5;
+    return b + c * 2 + f(7) * 4 + sizeof(b) * 8;
+}'
+assert 6 '
+#include <stdbool.h>
+int main() {
+    bool b = 2;
+    return b + true + false + sizeof(true);
+}'
+assert 3 '
+bool is_positive(int x) {
+    return x > 0;
+}
+int main() {
+    bool b = 2;
+    return b + true + is_positive(5) + false + sizeof(true) - 1;
+}' -std=c23
+
+echo + enumerations
+assert 21 '
+enum Color;
+int paint(enum Color c);
+enum Color { RED, GREEN = 10, BLUE };
+enum Color favorite = BLUE;
+int paint(enum Color c) {
+    return c + 10;
+}
+int main() {
+    return paint(favorite) - RED - sizeof(enum Color) + 4;
+}'
+assert 7 '
+enum Small : unsigned char;
+enum Small : unsigned char { ONE = 1, SIX = 6 };
+int main() {
+    enum Small s;
+    s = SIX;
+    return s + ONE + sizeof(s) - 1;
+}' -std=c23
+
+echo + unions and global initializers
+assert 30 '
+int g[2][2] = {1, 2, 3};
+union { int i; char c; } gu = { .c = 5 };
+struct { char a; int b; } gsv = { 1, 2 };
+int main() {
+    int *pu = &gu, *pb = &gsv;
+    return g[0][1] + g[1][0] + g[1][1] + *pu + pb[1] * 10;
+}'
+assert 42 '
+struct { int kind; union { long l; char s[8]; } as; } msgs[2] = {
+    { 0, { .l = 40 } },
+    [1] = { .kind = 1, .as.s = { 1, 1 } },
+};
+int main() {
+    long *p = &msgs[0];
+    char *q = &msgs[1];
+    return p[1] + q[8] + q[0];
+}'
+
+echo + struct member access
+assert 42 '
+struct { int kind; union { long l; char s[8]; } as; } msgs[2] = {
+    { 0, { .l = 40 } },
+    [1] = { .kind = 1, .as.s = { 1, 1 } },
+};
+int main() {
+    return msgs[0].as.l + msgs[1].as.s[0] + msgs[1].kind;
+}'
+
+echo + struct tags and incomplete types
+assert 129 '
+struct node;
+struct node *push(struct node *head, int v);
+void *calloc(long n, long size);
+struct node {
+    int val;
+    struct node *next;
+};
+struct node *push(struct node *head, int v) {
+    struct node *n = calloc(1, sizeof(struct node));
+    n->val = v;
+    n->next = head;
+    return n;
+}
+int main() {
+    struct node *list = 0;
+    int i = 1;
+    while (i <= 5) {
+        list = push(list, i);
+        i = i + 1;
+    }
+    int sum = 0;
+    while (list) {
+        sum = sum * 2 + list->val;
+        list = list->next;
+    }
+    return sum;
+}'
+assert 12 '
+struct S;
+struct S *gp;
+struct S { int x; int y; };
+struct S g;
+int main() {
+    gp = &g;
+    gp->y = 4;
+    return g.y + sizeof(*gp);
+}'
+
+echo + typedef names
+assert 52 '
+typedef int myint;
+typedef struct { int x; int y; } Point;
+typedef struct node Node;
+struct node { int v; Node *next; };
+typedef int (*binop)(int, int);
+typedef unsigned char u8, *u8p;
+typedef int myint;
+int unused(int, char *) { return 0; }
+int add(int a, int b) { return a + b; }
+myint sum(Point *p) { return p->x + p->y; }
+int shadow(myint myint) { return myint + 1; }
+int main() {
+    Point p;
+    p.x = 3; p.y = 4;
+    Node a, b;
+    a.v = 1; a.next = &b; b.v = 2;
+    binop f;
+    u8 c = 255;
+    u8p cp = &c;
+    int r = sum(&p) + a.next->v + sizeof(binop) + sizeof(Point) + sizeof(u8p) + *cp;
+    {
+        int Point = 5;
+        r = r + Point;
+        { typedef long Point; r = r + sizeof(Point); }
+    }
+    myint m = sizeof(myint);
+    return r + m + shadow(1) - 255;
+}'
+
+echo + bit-fields
+assert 42 '
+struct Reg { unsigned a : 3; unsigned b : 5; int c : 4; unsigned : 0; unsigned d : 12; };
+struct Mixed { char x; int y : 20; char z; long w : 40; };
+struct Z { char a; int : 0; char b; };
+struct U { char a : 4; char b : 4; };
+struct Reg gr = { 5, 17, -3, 1000 };
+struct Mixed gm = { 1, -100000, 2, 123456789012 };
+int check_layout() {
+    return sizeof(struct Reg) * 1000 + sizeof(struct Mixed) * 10 + sizeof(struct Z) + sizeof(struct U) * 100;
+}
+int main() {
+    struct Reg r;
+    r.a = 7; r.b = 31; r.c = -8; r.d = 4095;
+    if (r.a != 7 || r.b != 31 || r.c != -8 || r.d != 4095) return 1;
+    r.a = 9;
+    if (r.a != 1) return 2;
+    r.c = 7; r.c += 1;
+    if (r.c != -8) return 3;
+    r.b++;
+    if (r.b != 0 || r.a != 1 || r.d != 4095) return 4;
+    if (gr.a != 5 || gr.b != 17 || gr.c != -3 || gr.d != 1000) return 5;
+    if (gm.x != 1 || gm.y != -100000 || gm.z != 2 || gm.w != 123456789012) return 6;
+    struct Mixed m = { 3, 4, 5, 6 };
+    if (m.x + m.y + m.z + m.w != 18) return 7;
+    struct U u = { .b = 3, .a = -1 };
+    if (u.a != -1 || u.b != 3) return 8;
+    int v = (r.a = 12);
+    if (v != 4) return 9;
+    return check_layout() - 8265 + 42;
+}'
+
+echo + function pointers
+assert 42 '
+int add(int a, int b) { return a + b; }
+int sub(int a, int b) { return a - b; }
+int mul(int a, int b) { return a * b; }
+typedef int (*binop)(int, int);
+binop gops[3] = { add, sub, &mul };
+int (*gfp)(int, int) = sub;
+int apply(int (*f)(int, int), int a, int b) { return f(a, b); }
+int (*pick(int i))(int, int) { return gops[i]; }
+struct vtable { int (*op)(int, int); int bias; };
+int fact(int n) { if (n <= 1) return 1; return n * fact(n - 1); }
+int main() {
+    int (*fp)(int, int) = add;
+    int (*tbl[2])(int, int);
+    tbl[0] = mul; tbl[1] = sub;
+    struct vtable vt; vt.op = mul; vt.bias = 1;
+    struct vtable *pv = &vt;
+    int r = fp(2, 3);
+    r = r + (*fp)(1, 1);
+    r = r + tbl[0](2, 4);
+    r = r + gops[1](10, 3);
+    r = r + apply(add, 1, 2);
+    r = r + pick(2)(2, 5);
+    r = r + pv->op(3, 3) + vt.bias;
+    r = r + gfp(10, 8);
+    r = r + (fp == add) + (fp != sub);
+    int (*f1)(int) = fact;
+    r = r + f1(3) - 6;
+    r = r + sizeof(fp) + sizeof(tbl) - 24;
+    int (*ap)[3];
+    int arr[3];
+    ap = &arr;
+    (*ap)[2] = 2;
+    return r + arr[2] - 9 + sizeof(*ap) - 12 + sizeof(int (*)[3]) - 8;
+}'
+assert 15 '
+int inc(int x) { return x + 1; }
+int dbl(int x) { return x * 2; }
+typedef int (*unop)(int);
+int (*choose(int i))(int) { if (i) return dbl; return inc; }
+unop choose_addr(int i) { if (i) return &dbl; return &inc; }
+int main() {
+    return choose(0)(2) + choose(1)(2) + choose_addr(0)(1) + choose_addr(1)(3);
+}'
+
+echo + casts
+assert 15 '
+typedef int (*cmp_fn)(const void *, const void *);
+void qsort(void *base, long n, long size, cmp_fn cmp);
+int cmp(const void *a, const void *b) { return *(const int *)a - *(const int *)b; }
+int main() {
+    int a[5];
+    a[0] = 4; a[1] = 1; a[2] = 3; a[3] = 5; a[4] = 2;
+    qsort((void *)a, 5, sizeof(int), cmp);
+    return a[0] * 10 + a[4] + (unsigned char)(a[1] + 254) + (short)65536;
+}'
+
+echo + alignment
+assert 7 '
+#include <stdalign.h>
+#pragma pack(push, 1)
+struct P { char c; int i; short s; };
+#pragma pack(push, 2)
+struct Q { char c; int i; };
+#pragma pack(pop)
+struct R { char c; long l; };
+#pragma pack(pop)
+struct S { char c; int i; };
+alignas(32) char g[3];
+struct P gp = {1, 0x01020304, -3};
+int main() {
+    struct P p;
+    p.i = 1234567;
+    p.s = -3;
+    int r = sizeof(struct P) + sizeof(struct Q) + sizeof(struct R) + sizeof(struct S) - 30;
+    r = r + (p.i == 1234567) + (p.s == -3) + (gp.i == 0x01020304) + (gp.s == -3);
+    return r + ((long)g % 32 == 0) + alignof(struct R) * 2;
+}'
+
+echo + over-aligned locals
+assert 127 '
+struct __attribute__((aligned(64))) S { int a; char b; };
+typedef int aint __attribute__((aligned(32)));
+int aligned(void *p, long a) { return (long)p % a == 0; }
+int f(int a1, int a2, int a3, int a4, int a5, int a6, aint x, struct S s, int y) {
+    _Alignas(32) int z = x + y;
+    return aligned(&x, 32) + aligned(&s, 64) * 2 + aligned(&z, 32) * 4 + (s.a == 7 && s.b == 3) * 8 + (z == 14) * 16;
+}
+int main() {
+    struct S s;
+    int i;
+    int r = 0;
+    s.a = 7;
+    s.b = 3;
+    for (i = 0; i < 3; i++) {
+        _Alignas(128) int t = i;
+        r += aligned(&t, 128) && t == i;
+    }
+    return f(1, 2, 3, 4, 5, 6, 5, s, 9) + (r == 3) * 32 + (sizeof(struct S) == 64) * 64;
+}'
+
+echo + packed bit-fields
+assert 55 '
+struct __attribute__((packed)) A { char a; int b : 30; char c; };
+#pragma pack(1)
+struct B { char a; int b : 4; int : 0; char c; };
+#pragma pack()
+#pragma pack(2)
+struct C { char a; int b : 20; int d : 20; char c; };
+#pragma pack()
+#pragma pack(4)
+struct F { char a; long b : 40; long c : 40; char e; };
+#pragma pack()
+struct __attribute__((packed)) E { char a; long b : 60; char c : 4; short d : 15; unsigned long e : 63; };
+struct E gpe = {1, -123456789012345, -3, -1234, 0x7edcba9876543210};
+int main() {
+    struct A a;
+    struct C c;
+    struct E e;
+    a.a = 1; a.b = -123456789; a.c = 2;
+    a.b += 1000;
+    e.a = 5; e.b = 0x7ffffffffffffff; e.c = 7; e.d = -1234; e.e = 0x7edcba9876543210;
+    int r = (sizeof(struct A) == 6) + ((long)&a.c - (long)&a == 5) + (sizeof(struct B) == 5);
+    r += (sizeof(struct C) == 8) * 2 + ((long)&c.c - (long)&c == 6) * 2;
+    r += (sizeof(struct F) == 12 && _Alignof(struct F) == 4) * 4 + (sizeof(struct E) == 19) * 4;
+    r += (a.a == 1 && a.b == -123455789 && a.c == 2) * 8;
+    r += (e.a == 5 && e.b == 0x7ffffffffffffff && e.c == 7 && e.d == -1234 && e.e == 0x7edcba9876543210) * 16;
+    r += (gpe.b == -123456789012345 && gpe.c == -3 && gpe.d == -1234 && gpe.e == 0x7edcba9876543210) * 16;
+    return r;
+}'
+
+echo + static assertions
+assert 24 '
+struct hdr {
+    int type;
+    int len;
+    long id;
+    _Static_assert(sizeof(int) == 4, "int must be 32 bits");
+};
+_Static_assert(sizeof(struct hdr) == 16, "struct hdr must match the wire format");
+static_assert((unsigned char)-1 == 255);
+int main() {
+    struct hdr h;
+    static_assert(_Alignof(struct hdr) == 8);
+    return sizeof(h) + sizeof(h.id);
+}' -std=c23
+
+echo + generic selections
+assert 64 '
+int twice(int x) { return x * 2; }
+int inc(int x) { return x + 1; }
+int main() {
+    int x = 3;
+    int y = _Generic(x, int: twice, default: inc)(x);
+    int (*fp)(int) = _Generic(y, long: inc, default: twice);
+    return y * 10 + fp(2);
+}'
+
+echo + variable length arrays
+assert 56 '
+int sum(int n) {
+    int a[n];
+    int i;
+    for (i = 0; i < n; i++) a[i] = i;
+    int s = 0;
+    for (i = 0; i < n; i++) s += a[i];
+    return s;
+}
+int find(int n, int key) {
+    int i = 0;
+    {
+        int t[n][2];
+        for (i = 0; i < n; i++) {
+            t[i][1] = i * 2;
+            if (t[i][1] == key) goto found;
+        }
+        return -1;
+    }
+found:
+    return i;
+}
+int main() {
+    int n = 10;
+    int b[n];
+    b[9] = 3;
+    return sum(10) + b[9] + find(n, 14) + sizeof(b) - 40 + (find(3, 7) == -1);
+}'
+assert 50 '
+int weighted(int rows, int cols, int a[rows][cols]) {
+    int i, j;
+    int s = 0;
+    for (i = 0; i < rows; i++)
+        for (j = 0; j < cols; j++)
+            s += a[i][j] * (i + 1);
+    return s + sizeof(a[0]);
+}
+int total(int n, int v[n]);
+int apply(int n, int (*fn)(int k, int v[k]), int a[n]) { return fn(n, a); }
+int total(int k, int v[k]) {
+    int i;
+    int s = 0;
+    for (i = 0; i < k; i++) s += v[i];
+    return s;
+}
+int main() {
+    int b[2][4];
+    int i;
+    for (i = 0; i < 8; i++) b[i / 4][i % 4] = i;
+    return weighted(2, 4, b) - 16 + apply(3, total, b[1]) - 15;
+}'
+
+echo + incomplete arrays
+assert 47 '
+extern int table[];
+extern int n;
+int get(int i) { return table[i]; }
+int table[] = {10, 20, 30};
+char names[][6] = {"alpha", "beta"};
+int main() {
+    extern int n;
+    return get(2) + n + sizeof(table) + sizeof(names) - 12 + (names[1][3] == 97);
+}
+int n = 4;'
+
+echo + parameter adjustment
+assert 56 '
+int sum(int a[10], int n) {
+    int s = 0;
+    int i;
+    for (i = 0; i < n; i++) s += a[i];
+    return s + sizeof a;
+}
+int row(int m[][4]) { return m[1][2] + sizeof(m) + sizeof(*m); }
+int apply(int f(int), int x) { return f(x); }
+int twice(int x) { return x * 2; }
+int last(int a[static 3], int b[const], int c[restrict static 1]) { return a[2] + b[0] + c[0]; }
+int main() {
+    int a[3] = {1, 2, 3};
+    int m[2][4];
+    m[1][2] = 5;
+    return sum(a, 3) + row(m) + apply(twice, 4) + last(a, a, a);
+}'
+assert 12 '
+int second(int a[*]);
+int second(int *a) { return a[1]; }
+int add(char c, short s, long l, _Bool b) { return c + s + l + b; }
+int main() {
+    int a[] = {4, 5};
+    return second(a) + add(1, 2, 3, 1);
+}'
+
+echo + floating point
+assert 1 '
+int sprintf();
+int strcmp(char *a, char *b);
+double g = 1.5 * 2;
+float gf = 1.0f / 4;
+int gi = 7.9;
+struct P { float x; double y; };
+double add(double a, float b, int c) { return a + b + c; }
+float half(float v) { return v / 2; }
+int main() {
+    char buf[64];
+    struct P p = {1.5f, 2.5};
+    double (*fp)(double, float, int) = add;
+    sprintf(buf, "%.2f %d %.3f %.1f %.2f", g + gf, gi, fp(0.5, 0.25f, 1), p.x + p.y, half(5));
+    return strcmp(buf, "3.25 7 1.750 4.0 2.50") == 0;
+}'
+
+echo + long double
+assert 1 '
+int sprintf();
+int strcmp(char *a, char *b);
+long double strtold(char *s, char **end);
+long double g = -2.5L;
+long double mix(int a, long double x, double y, long double z, int b) {
+    return a + x * 10 + y * 100 + z * 1000 + b * 10000;
+}
+int main() {
+    char buf[128];
+    long double third = 1.0L / 3;
+    long double (*fp)(int, long double, double, long double, int) = mix;
+    sprintf(buf, "%.20Lf %Lf %Lf %.1Lf", 3.14159265358979323846L, g, fp(1, 2, 3, 4, 5), strtold("0.25", 0) * 6);
+    return strcmp(buf, "3.14159265358979323851 -2.500000 54321.000000 1.5") == 0 && third * 3 == 1;
+}'
+assert 1 '
+int sprintf();
+int strcmp(char *a, char *b);
+long double third = 1.0L / 3;
+long double big = 9007199254740993;
+long double ubig = 18446744073709551615UL;
+long double mixed = (1.0L - 0.1L) * 3 + (long double)(1.0 / 3);
+long double tiny = 1e-4940L * 1e-10L;
+int main() {
+    char buf[128];
+    long double x = 1.0L;
+    sprintf(buf, "%.20Lf %.0Lf %.0Lf %.5Le", third, big, ubig, tiny);
+    return strcmp(buf, "0.33333333333333333334 9007199254740993 18446744073709551615 1.09356e-4950") == 0
+        && mixed == (x - 0.1L) * 3 + (long double)(1.0 / 3) && third == x / 3;
+}'
+
+echo + variadic functions
+assert 15 '
+#include <stdarg.h>
+int sum(int n, ...) {
+    va_list ap;
+    int s = 0;
+    va_start(ap, n);
+    while (n--)
+        s += va_arg(ap, int);
+    va_end(ap);
+    return s;
+}
+int main() { return sum(5, 1, 2, 3, 4, 5); }'
+assert 1 '
+#include <stdarg.h>
+int vsprintf(char *buf, char *fmt, va_list ap);
+int strcmp(char *a, char *b);
+char buf[64];
+int log_to(char *fmt, ...) {
+    va_list ap, aq;
+    va_start(ap, fmt);
+    va_copy(aq, ap);
+    int first = va_arg(aq, int);
+    va_end(aq);
+    vsprintf(buf, fmt, ap);
+    va_end(ap);
+    return first;
+}
+long double ld_sum(int n, ...) {
+    va_list ap;
+    long double s = 0;
+    va_start(ap, n);
+    while (n--)
+        s += va_arg(ap, long double);
+    va_end(ap);
+    return s;
+}
+int main() {
+    int first = log_to("%d %s %.2f %ld", 42, "hi", 3.14159, 1234567890123);
+    return first == 42 && strcmp(buf, "42 hi 3.14 1234567890123") == 0 && ld_sum(3, 1.0L, 2.5L, 3.25L) == 6.75;
+}'
+assert 1 '
+#include <stdarg.h>
+struct va_mix { long l; double d; };
+struct va_big { long a, b, c; };
+struct va_flt { float x, y, z; };
+struct va_pair { long a, b; };
+long call_va_longs(long (*f)(int, ...));
+double call_va_doubles(double (*f)(int, ...));
+long call_va_structs(long (*f)(int, ...));
+long call_va_spill(long (*f)(int, ...));
+long longs(int n, ...) {
+    va_list ap;
+    long s = 0;
+    int i;
+    va_start(ap, n);
+    for (i = 0; i < n; i++)
+        s = s * 2 + va_arg(ap, long);
+    va_end(ap);
+    return s;
+}
+double doubles(int n, ...) {
+    va_list ap;
+    double s = 0;
+    va_start(ap, n);
+    while (n--)
+        s += va_arg(ap, double);
+    va_end(ap);
+    return s;
+}
+long structs(int n, ...) {
+    va_list ap;
+    va_start(ap, n);
+    struct va_mix m = va_arg(ap, struct va_mix);
+    struct va_big b = va_arg(ap, struct va_big);
+    struct va_flt v = va_arg(ap, struct va_flt);
+    struct va_pair p = va_arg(ap, struct va_pair);
+    va_end(ap);
+    return m.l + m.d * 2 + b.a + b.b + b.c + (v.x + v.y + v.z) * 2 + p.b - p.a;
+}
+long spill(int n, ...) {
+    va_list ap;
+    va_start(ap, n);
+    long a = va_arg(ap, long) + va_arg(ap, long) + va_arg(ap, long) + va_arg(ap, long);
+    struct va_pair p = va_arg(ap, struct va_pair);
+    long b = va_arg(ap, long);
+    va_end(ap);
+    return a + p.a + p.b + b;
+}
+int main() {
+    return call_va_longs(longs) == 2036 && call_va_doubles(doubles) == 55.5
+        && call_va_structs(structs) == 182 && call_va_spill(spill) == 315;
+}'
+
+echo + stack arguments
+assert 1 '
+long sum_many(long a, long b, long c, long d, long e, long f, long g, long h, int i);
+double fsum_many(double a, double b, double c, double d, double e, double f, double g, double h, double i, float j, int k);
+long call_many(long (*f)(long, long, long, long, long, long, long, char, double, double, double, double, double, double, double, double, float, double));
+long many(long a, long b, long c, long d, long e, long f, long g, char h, double i, double j, double k, double l, double m, double n, double o, double p, float q, double r) {
+    return a + b + c + d + e + f + g * 10 + h * 100 + (i + j + k + l + m + n + o + p) * 1000 + q * 100000 + r * 1000000;
+}
+int main() {
+    return sum_many(1, 2, 3, 4, 5, 6, 7, 8, 9) == 285 && fsum_many(1, 2, 3, 4, 5, 6, 7, 8, 9, 1.5f, 3) == 3276
+        && call_many(many) == 10129391 && many(1, 2, 3, 4, 5, 6, 7, 8, 0.5, 1, 2, 3, 4, 5, 6, 7, 8.5f, 9.25) == 10129391;
+}'
+assert 1 '
+#include <stdarg.h>
+int sprintf();
+int strcmp(char *a, char *b);
+long longs(int n, ...) {
+    va_list ap;
+    long s = 0;
+    va_start(ap, n);
+    while (n--)
+        s = s * 2 + va_arg(ap, long);
+    va_end(ap);
+    return s;
+}
+double named(long a, long b, long c, long d, long e, long f, long g, double x, ...) {
+    va_list ap;
+    va_start(ap, x);
+    long h = va_arg(ap, long);
+    double y = va_arg(ap, double);
+    long double z = va_arg(ap, long double);
+    int i = va_arg(ap, int);
+    va_end(ap);
+    return a + b + c + d + e + f + g * 10 + x * 100 + h * 1000 + y * 10000 + z * 100000 + i * 1000000;
+}
+int main() {
+    char buf[128];
+    sprintf(buf, "%d %d %d %d %d %d %d %.1f %.1f %.1f %.1f %.1f %.1f %.1f %.1f %.1f %s", 1, 2, 3, 4, 5, 6, 7, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, "end");
+    return longs(10, 1L, 2L, 3L, 4L, 5L, 6L, 7L, 8L, 9L, 10L) == 2036 && named(1, 2, 3, 4, 5, 6, 7, 0.5, 8L, 0.25, 3.0L, 2) == 2310641
+        && strcmp(buf, "1 2 3 4 5 6 7 1.0 2.0 3.0 4.0 5.0 6.0 7.0 8.0 9.0 end") == 0;
+}'
+
+echo + struct passing
+assert 1 '
+struct s_ll { long a, b; };
+struct s_dd { double x, y; };
+struct s_mix { int i; float f; double d; };
+struct s_big { long a, b, c; };
+struct s_chars { char c[3]; };
+struct s_fff { float x, y, z; };
+struct s_ld { long double v; };
+struct s_ll make_ll(long a, long b);
+struct s_dd make_dd(double x, double y);
+struct s_mix make_mix(int i, float f, double d);
+struct s_big make_big(long a, long b, long c);
+struct s_chars make_chars(char a, char b, char c);
+struct s_fff make_fff(float x, float y, float z);
+struct s_ld make_ld(long double v);
+double sum_structs(struct s_ll ll, struct s_dd dd, struct s_mix mix, struct s_big big, struct s_chars ch, struct s_fff fff, struct s_ld ld, struct s_ll ll2);
+double call_struct_funcs(struct s_ll (*ll)(struct s_ll, long), struct s_big (*big)(struct s_big, int), struct s_mix (*mix)(struct s_fff, struct s_chars, struct s_dd));
+struct s_ll ll_cb(struct s_ll s, long n) { s.a += n; s.b += n; return s; }
+struct s_big big_cb(struct s_big s, int n) { struct s_big r = s; r.c *= n; return r; }
+struct s_mix mix_cb(struct s_fff f, struct s_chars c, struct s_dd d) {
+    struct s_mix m;
+    m.i = c.c[0] + c.c[1] + c.c[2];
+    m.f = f.x + f.y + f.z;
+    m.d = d.x + d.y;
+    return m;
+}
+double local_sum(struct s_ll ll, struct s_dd dd, struct s_mix mix, struct s_big big, struct s_chars ch, struct s_fff fff, struct s_ld ld, struct s_ll ll2) {
+    return ll.a + ll.b * 2 + dd.x * 3 + dd.y * 4 + mix.i * 5 + mix.f * 6 + mix.d * 7 +
+           big.a * 8 + big.b * 9 + big.c * 10 + ch.c[0] * 11 + ch.c[1] * 12 + ch.c[2] * 13 +
+           fff.x * 14 + fff.y * 15 + fff.z * 16 + (double)ld.v * 17 + ll2.a * 18 + ll2.b * 19;
+}
+int main() {
+    struct s_ll ll = make_ll(1, 2);
+    struct s_dd dd = make_dd(0.5, 1.5);
+    struct s_mix mix = make_mix(3, 2.5f, 4.25);
+    struct s_big big = make_big(5, 6, 7);
+    struct s_chars ch = make_chars(8, 9, 10);
+    struct s_fff fff = make_fff(1.5f, 2.5f, 3.5f);
+    struct s_ld ld = make_ld(2.75L);
+    return ll.b == 2 && dd.y == 1.5 && mix.i == 3 && mix.f == 2.5f && mix.d == 4.25 && big.c == 7 && ch.c[2] == 10
+        && fff.z == 3.5f && ld.v == 2.75L && sum_structs(ll, dd, mix, big, ch, fff, ld, ll) == 779.5
+        && local_sum(ll, dd, mix, big, ch, fff, ld, ll) == 779.5 && call_struct_funcs(ll_cb, big_cb, mix_cb) == 1585
+        && ll_cb(make_ll(1, 2), 5).b == 7 && big_cb(make_big(1, 2, 3), 4).c == 12;
+}'
+assert 1 '
+struct s_ll { long a, b; };
+struct s_ld { long double v; };
+struct s_int3 { int a, b, c; };
+struct __attribute__((packed)) s_odd { char c; int i; short s; };
+struct s_int3 twice_int3(struct s_int3 s);
+struct s_odd twice_odd(struct s_odd s);
+long spill_struct(long a, long b, long c, long d, long e, struct s_ll s, long f);
+long double call_ld_ret(struct s_ld (*f)(long double));
+struct s_ld ld_ret(long double v) { struct s_ld r; r.v = v * 4; return r; }
+struct s_odd my_odd(struct s_odd s) { return twice_odd(s); }
+long my_spill(long a, long b, long c, long d, long e, struct s_ll s, long f) { return a + b + c + d + e + s.a * 10 + s.b * 100 + f * 1000; }
+struct s_ll fib(int n) {
+    struct s_ll r;
+    if (n == 0) { r.a = 0; r.b = 1; return r; }
+    struct s_ll p = fib(n - 1);
+    r.a = p.b; r.b = p.a + p.b;
+    return r;
+}
+int main() {
+    struct s_int3 t = {1, 2, 3};
+    struct s_odd o = {4, 5, 6};
+    struct s_ll s = {7, 8};
+    t = twice_int3(t);
+    struct s_odd o2 = my_odd(o);
+    return t.a == 2 && t.c == 6 && o2.c == 8 && o2.i == 10 && o2.s == 12 && spill_struct(1, 2, 3, 4, 5, s, 9) == 9885
+        && my_spill(1, 2, 3, 4, 5, s, 9) == 9885 && call_ld_ret(ld_ret) == 5 && ld_ret(2).v == 8 && fib(10).a == 55;
+}'
+
+echo + source and execution character sets
+assert_charset 130 SHIFT_JIS SHIFT_JIS 'char main() { char *s = "あ"; return s[0]; }'
+assert_charset 162 SHIFT_JIS EUC-JP 'char main() { char *s = "あ"; return s[1]; }'
+assert_charset 92 SHIFT_JIS SHIFT_JIS 'int main() { char *s = "表"; return s[1] + s[2]; }'
+assert_charset 97 UTF-8 SHIFT_JIS 'int main() { char *s = "ソa"; return (s[0] & 255) == 131 && s[1] == 92 ? s[2] : 0; }'
+assert_charset 227 EUC-JP UTF-8 'char main() { char *s = "あ"; return s[0]; }'
+assert_charset 233 LATIN1 LATIN1 'int main() { return '"'"'é'"'"' & 255; }'
+assert_charset 162 UTF-8 EUC-JP 'int main() { return '"'"'あ'"'"' & 255; }'
+
 echo OK
@@ -9,8 +9,9 @@ cargo build
 assert() {
   expected="$1"
   input="$2"
+  flags="$3" # 追加のコンパイラオプション
 
-  ./target/debug/c-compiler -i "int main() { $input }" > ./bin/tmp.s || {
+  ./target/debug/c-compiler $flags -i "int main() { $input }" > ./bin/tmp.s || {
     echo -e "\033[31m( ERROR )\033[0m Compilation failed: $input"
     exit 1
   }
@@ -212,6 +213,245 @@ assert 5 'int a[2][3]; *(*(a + 1) + 2) = 5; return a[1][2];'
 assert 2 'int i = 2; int a[2][3]; a[1][i - 1] = 2; return a[1][1];'
 assert 2 'int a[2][3]; int *p; p = a[1]; a[1][1] = 2; return *(++p);'
 
+echo + unsigned integers
+assert 255 'unsigned char c; c = 255; return c;'
+assert 1 'unsigned short s; s = 65535; return s == 65535;'
+assert 1 'char c; c = 200; return c < 0;'
+assert 0 'signed char c; c = 200; return c > 0;'
+assert 15 'unsigned int x; x = 4294967280; return x >> 28;'
+assert 252 'int x; x = -16; return x >> 2;'
+assert 1 'int x; x = 0x80000000; x >>= 31; return x == -1;'
+assert 1 'unsigned x; x = 0x80000000; x >>= 31; return x;'
+assert 255 'unsigned int x; x = 4294967295; return x / 16777216;'
+assert 2 'unsigned long x; x = 10; return x % 4;'
+assert 1 'unsigned x; x = 7; x /= 2; x %= 2; return x;'
+assert 0 'unsigned int a; int b; a = 1; b = -1; return b < a;'
+assert 1 'unsigned int a; int b; a = 1; b = -1; return b >= a;'
+assert 1 'unsigned char a; char b; a = 1; b = -1; return b < a;'
+assert 0 'return -1 < 0u;'
+assert 1 'return -1 < 0;'
+assert 255 'unsigned long x; x = 0xffffffffffffffff; return x / 0x100000000000000;'
+assert 1 'char c; c = 200; return c > 0;' -funsigned-char
+assert 1 'signed char c; c = 200; return c < 0;' -funsigned-char
+assert 1 "return '\xff' == 255;" -funsigned-char
+assert 1 "return '\xff' == -1;"
+
+echo + _Bool
+assert 1 '_Bool b; b = 256; return b;'
+assert 0 '_Bool b; b = 0; return b;'
+assert 1 'int x; int *p; p = &x; _Bool b; b = p; return b;'
+assert 1 '_Bool b; b = 0; b++; b++; return b;'
+assert 0 '_Bool b; b = 1; b--; return b;'
+assert 1 '_Bool b; b = 0; b--; return b;'
+assert 1 'return sizeof(_Bool);'
+assert 2 'bool b; b = 5; return b + true;' -std=c23
+
+echo + type specifier combinations
+assert 8 'long long a; return sizeof(a);'
+assert 8 'long long int a; return sizeof(a);'
+assert 8 'unsigned long int a; return sizeof(a);'
+assert 2 'short int a; return sizeof(a);'
+assert 2 'signed short int a; return sizeof(a);'
+assert 16 'long double a; return sizeof(a);'
+assert 1 'signed char a; return sizeof(a);'
+assert 4 'signed a; return sizeof(a);'
+assert 10 'return sizeof(unsigned short int) + sizeof(long long int);'
+assert 1 'unsigned long long x; x = -1; return x > 0;'
+assert 1 'int long signed a; a = -1; return a < 0 && sizeof(a) == 8;'
+assert 28 'int a = 1; int b = 2; int c = 3; int d = 4; int e = 5; int f = 6; int g = 7; return a + b + c + d + e + f + g;'
+
+echo + integer constant types
+assert 4 'return sizeof(1);'
+assert 4 'return sizeof(1u);'
+assert 8 'return sizeof(1l);'
+assert 8 'return sizeof(1ull);'
+assert 8 'return sizeof(4294967295);'
+assert 4 'return sizeof(0xffffffff);'
+assert 8 'return sizeof(0x100000000);'
+
+echo + enumerations
+assert 6 'enum { A, B = 5, C }; return A + C;'
+assert 3 'enum E { X = 1, Y = X + 2 }; enum E e; e = Y; return e;'
+assert 4 'enum E { X = -1 }; return sizeof(enum E);'
+assert 1 'enum E : unsigned char { X = 255 }; enum E e; e = X; return sizeof(e) == 1 && e == 255;'
+assert 12 'enum { N = 3 }; int a[N * 1]; return sizeof(a);'
+assert 2 'enum { A, B }; int r; r = 0; { enum { A = 2 }; r = A; } return r + B - 1;'
+assert 1 'enum { A = 1 << 4, B = A | 1, C = B > 16 ? 1 : 0 }; return C;'
+
+echo + struct member access
+assert 3 'struct { int a; int b; } s; s.a = 1; s.b = 2; return s.a + s.b;'
+assert 8 'struct { char a; int b; } s; return sizeof(s);'
+assert 5 'struct { char a; int b; } s, *p; p = &s; p->b = 5; return s.b;'
+assert 8 'struct { int a[3]; struct { char c; } in[2]; } s[2], *p; p = s; p[1].in[1].c = 4; s[1].a[2] = 3; int *q = &p[1].a[2]; (s + 1)->in[0].c = 1; return *q + s[1].in[1].c + p[1].in[0].c;'
+assert 6 'struct { int x; struct { int y; int z; } in; } s; s.in.z = 4; s.in.z += 2; return s.in.z;'
+assert 3 'struct { int a; int b; } s, t; s.a = 1; s.b = 2; return (t = s).b + (1 ? s : t).a;'
+assert 24 'struct { int tag; union { int i; char c; }; struct { int x, y; }; } s; s.tag = 1; s.i = 2; s.y = 5; return s.tag + s.c + s.y + sizeof(s);'
+assert 81 'union { int i; char c[8]; long l; } u; u.l = 0; u.i = 258; return sizeof(u) * 10 + u.c[1];'
+assert 7 'union { int i; char c; } u, *p; p = &u; p->i = 0; p->c = 7; return u.i;'
+assert 3 'struct { int tag; union { int i; char c; } val; } s = { 1, { .c = 2 } }; return s.tag + s.val.c;'
+assert 100 'struct { int a; struct { char b; long c; } in; } s = { 1, 2, 3 }, t; t = s; t.in.c = 7; return s.a + s.in.b + s.in.c + t.in.c * 10 + sizeof(s);'
+
+echo + struct tags
+assert 52 'struct node { int v; struct node *next; } a, b, c; a.v = 1; b.v = 2; c.v = 3; a.next = &b; b.next = &c; c.next = 0; int s = 0; struct node *p = &a; while (p) { s = s + p->v; p = p->next; } return s + a.next->next->v * 10 + sizeof(*a.next);'
+assert 18 'struct S { char c[5]; } s; int r = sizeof(s); { struct S; struct S *p; struct S { long l; } t; p = &t; r = r + sizeof(*p); } struct S u; return r + sizeof(u);'
+assert 7 'union U { int i; char c; } u; union U *p = &u; p->i = 7; return u.c;'
+
+echo + typedef names
+assert 12 'typedef int T; T a = 4; T *p = &a; return sizeof(T) + *p + sizeof(p) - 4;'
+assert 10 'typedef struct { int x; int y; } P; P p; p.x = 3; p.y = 7; P *q = &p; return q->x + q->y;'
+assert 9 'typedef char C, *CP, A3[3]; C c = 1; CP cp = &c; A3 a; return sizeof(a) + sizeof(cp) - *cp - 1;'
+assert 6 'typedef int T; int r = sizeof(T); { int T = 2; r = r + T; } return r;'
+assert 8 'typedef int T; { typedef long T; return sizeof(T); }'
+assert 4 'typedef int T; typedef int T; T T2 = sizeof(T); return T2;'
+
+echo + bit-fields
+assert 8 'struct { unsigned a : 3; unsigned b : 5; int c : 4; unsigned : 0; unsigned d : 12; } r; return sizeof(r);'
+assert 5 'struct { char a; int : 0; char b; } z; return sizeof(z);'
+assert 16 'struct { char x; int y : 20; char z; long w : 40; } m; return sizeof(m);'
+assert 1 'union { char a : 4; unsigned char b : 7; } u; return sizeof(u);'
+assert 3 'struct { unsigned a : 3; unsigned b : 5; } s; s.a = 7; s.b = 30; s.a = s.a + 4; return s.a;'
+assert 8 'struct { int a : 4; int b : 4; } s; s.a = 7; s.b = 5; s.a += 1; return -s.a;'
+assert 31 'struct { unsigned a : 3; unsigned b : 5; } s; s.a = 0; s.b = 0; s.b--; return s.b;'
+assert 4 'struct { unsigned a : 3; } s; int v = (s.a = 12); return v;'
+assert 1 'struct { _Bool f : 1; int n : 7; } s; s.n = 63; s.f = 10; return s.f;'
+assert 21 'struct { int a : 4; int : 6; unsigned b : 5; } s = { -3, 24 }; return s.a + s.b;'
+assert 9 'struct { char x; unsigned a : 4, b : 4; } s = { .a = 1, 9 }; return s.b;'
+
+echo + function pointers
+assert 16 'int (*fp)(int, int); int *(*tbl[3])(void); return sizeof(fp) + sizeof(tbl) - 24 + sizeof(int (*)[3]);'
+assert 12 'int (*ap)[3]; int a[3]; ap = &a; (*ap)[1] = 12; return a[1] + sizeof(*ap) - 12;'
+assert 1 'int (*fp)(int, int) = 0; return !fp;'
+
+echo + casts
+assert 44 'return (char)300;'
+assert 255 'return (unsigned char)-1;'
+assert 1 'int x = 65537; return (short)x;'
+assert 1 'long l = 4294967297; return (int)l;'
+assert 1 'return (_Bool)256;'
+assert 1 'long x = (int)4294967295; return x < 0;'
+assert 7 'int a[3]; a[1] = 7; long p = (long)a; return *(int *)(p + 4);'
+assert 3 '(void)5; return 3;'
+assert 5 'int x; return sizeof((char)x) + sizeof(int);'
+assert 1 'enum { A = (char)257 }; return A;'
+assert 2 'typedef unsigned char byte; int v = 258; return (byte)v;'
+
+echo + integer promotions and usual arithmetic conversions
+assert 1 'char a = 100, b = 100; return a + b == 200;'
+assert 1 'unsigned char uc = 255; return uc + 1 == 256;'
+assert 16 'char a; long l; return sizeof(a + a) + sizeof(+a) + sizeof(~a) + sizeof(l << 1) - sizeof(1 << l);'
+assert 1 'unsigned u = 4294967295u; return u + 1 == 0;'
+assert 0 'return -1 < 1u;'
+assert 1 'return -1 < (unsigned char)1;'
+assert 1 'long m = -1; unsigned n = 1; return m < n;'
+assert 0 'unsigned long ul = 1; return -1 < ul;'
+assert 1 'short sh = -1; unsigned short us = 65535; return sh != us;'
+assert 1 'long l = 1; return (l << 40) == 1099511627776;'
+assert 1 'return (1 << 31) < 0;'
+assert 1 'return (unsigned)-1 / 2 == 2147483647;'
+assert 1 'return -1 % 3u == 0;'
+assert 1 'unsigned x = 1; x = ~x; return x == 4294967294u;'
+assert 1 'unsigned ux = 3000000000u; int mul = 2; return ux * mul == 1705032704;'
+assert 1 'return (1 ? 1 : 2u) - 2 > 0;'
+assert 3 'struct { unsigned a : 3; unsigned b : 32; } s; s.a = 0; s.b = 0; return (s.a - 1 < 0) + (s.b - 1 > 0) * 2;'
+assert 1 'int i = -4; unsigned u = 2; i /= u; return i == 2147483646;'
+assert 1 'long l = 1; l <<= 33u; return l == 8589934592;'
+assert 1 'char c = 127; c++; return c == -128;'
+assert 1 'unsigned char uc = 255; int v = uc++; return v == 255 && uc == 0;'
+assert 44 'char d; int w = (d = 300); return w;'
+assert 1 'enum { E = (unsigned char)200 + (unsigned char)100 }; return E == 300;'
+
+echo + type qualifiers
+assert 5 'const int x = 5; return x;'
+assert 3 'int a = 1; int * const p = &a; *p = 3; return a;'
+assert 2 'int a = 1, b = 2; const int *p = &a; p = &b; return *p;'
+assert 15 'int a = 7; int *q = &a; int * const * pp = &q; return **pp + sizeof(pp);'
+assert 6 'typedef const int T; T a[2] = {2, 4}; const volatile int *p = a; return p[0] + p[1];'
+assert 9 'volatile int x = 4; x = x + 1; volatile struct { int a; } s; s.a = x; return s.a + x - 1;'
+assert 3 'struct { const int a; int b; } s = {1, 2}; s.b = s.b + s.a; return s.b;'
+
+echo + alignment
+assert 85 'return _Alignof(char) + _Alignof(short) * 2 + _Alignof(int) * 3 + _Alignof(long) * 4 + _Alignof(int[3]) * 5 + _Alignof(long double);'
+assert 3 'char c; _Alignas(16) int x; _Alignas(long) char y; return ((long)&x % 16 == 0) + ((long)&y % 8 == 0) * 2;'
+assert 16 'int a[3]; _Alignas(16) char b; return _Alignof a + _Alignof(b) - 4;'
+assert 55 'struct __attribute__((packed)) { char c; int i; } p; struct { char c; int i; } __attribute__((__packed__)) q; q.i = 300; return sizeof(p) * 10 + (q.i == 300) + sizeof(q) - 1;'
+assert 24 'struct { char c; } __attribute__((aligned(8))) a; struct { char c; } __attribute__((aligned)) b; return sizeof(a) + sizeof(b);'
+assert 48 'struct { char c; _Alignas(8) char d; int e __attribute__((aligned(16))); } s; return sizeof(s) + ((long)&s.e - (long)&s);'
+assert 36 'typedef int I16 __attribute__((aligned(16))); char c; I16 b; return _Alignof(I16) + ((long)&b % 16 == 0) * 20;'
+assert 16 'alignas(16) int x; return alignof(x);' -std=c23
+assert 7 'char c; _Alignas(32) int y = 5; char d; y += c = d = 1; return ((long)&y % 32 == 0) + (y == 6) * 2 + (c + d == 2) * 4;'
+assert 7 'struct { int a; } __attribute__((aligned(64))) s; _Alignas(128) char b[3]; s.a = 3; b[2] = 4; return ((long)&s % 64 == 0) + ((long)b % 128 == 0) * 2 + (s.a + b[2] == 7) * 4;'
+
+echo + static assertions
+assert 5 'enum { N = 3 }; _Static_assert(N * 2 == 6, "N"); int x = 5; _Static_assert(sizeof(x) == 4, "int " "size"); return x;'
+assert 8 'struct { int a; _Static_assert(_Alignof(long) == 8, "long"); long b; } s; static_assert(sizeof(s) == 16); return _Alignof(s);' -std=c23
+
+echo + generic selections
+assert 7 'const int ci = 1; int a[3]; return _Generic(ci, int: 1, const int: 50, default: 100) + _Generic(a, int *: 2, default: 100) + _Generic(a[0], long: 100, default: 4);'
+assert 15 'char c; unsigned u; long l; return _Generic(c, char: 1, int: 100) + _Generic(c + 1, int: 2, char: 100) + _Generic(u, unsigned int: 4, default: 100) + _Generic(l, long: 8, default: 100);'
+assert 3 'struct S { int x; } s; int *p; const int *q; return _Generic(s, struct S: 1, default: 100) + _Generic(p, const int *: 100, int *: 1) + _Generic(q, const int *: 1, int *: 100);'
+assert 5 'int n = 0; int r = _Generic(n++, int: 5, default: n++); return r + n;'
+assert 123 'char c; signed char s; unsigned char u; return _Generic(c, char: 1, signed char: 2, unsigned char: 3) * 100 + _Generic(s, char: 1, signed char: 2, unsigned char: 3) * 10 + _Generic(u, char: 1, signed char: 2, unsigned char: 3);'
+assert 123 'char c; signed char s; unsigned char u; return _Generic(c, char: 1, signed char: 2, unsigned char: 3) * 100 + _Generic(s, char: 1, signed char: 2, unsigned char: 3) * 10 + _Generic(u, char: 1, signed char: 2, unsigned char: 3);' -funsigned-char
+assert 3 'char *p; return _Generic(p, char *: 1, signed char *: 10, unsigned char *: 10) + _Generic("a", char *: 2, default: 10);'
+
+echo + variable length arrays
+assert 36 'int n = 5; int a[n]; for (n = 0; n < 5; n++) a[n] = n * n; return a[4] + sizeof(a);'
+assert 87 'int n = 3, m = 4; int a[n][m]; int i, j; for (i = 0; i < n; i++) for (j = 0; j < m; j++) a[i][j] = i * 10 + j; return a[2][3] + sizeof(a) + sizeof(a[1]);'
+assert 55 'int m = 4; int a[3][m]; a[2][1] = 7; int *p = &a[0][0]; return p[9] + sizeof(a);'
+assert 53 'int n = 4; int (*p)[n]; int a[3][n]; p = a; p[1][2] = 5; p++; return (*p)[2] + sizeof(*p) + sizeof(int[n][2]);'
+assert 16 'int n = 3; char c = 1; long a[n]; a[0] = 1; a[1] = 2; a[2] = 3; n = 100; int x = 9; return a[0] + a[1] + a[2] + x + c + sizeof(a) - 24;'
+assert 3 'int i; int n = 1000; long before, after; { int x[n]; before = (long)x; } for (i = 0; i < 100000; i++) { int a[n]; a[0] = i; if (i == 5000) continue; if (i == 99998) break; } { int y[n]; after = (long)y; } return (before == after) + 2 * (i == 99998);'
+assert 3 'int i = 0; int n = 1000; long p0, p1; again: { int a[n]; if (i == 0) p0 = (long)a; p1 = (long)a; i++; if (i < 100000) goto again; } return (p0 == p1) + (i == 100000) * 2;'
+assert 72 'int n = 3; typedef int row[n]; n = 10; row r; typedef row mat[n]; n = 1; mat m; r[2] = 5; m[9][2] = 4; return sizeof(r) + sizeof(row) * 2 + (sizeof(m) == 120) + r[2] + m[9][2] + 26;'
+
+echo + incomplete arrays
+assert 23 'int primes[] = {2, 3, 5, 7}; return sizeof(primes) + primes[3];'
+assert 107 'char msg[] = "hello"; return sizeof(msg) + msg[1] + msg[5];'
+assert 31 'int a[][2] = {{1, 2}, {3, 4}, {5}}; int d[] = {[4] = 1, 2}; return sizeof(a) + sizeof(d) / 4 - a[2][1] + a[1][1] - 3;'
+assert 107 'char s[8] = "ab"; char t[3] = "xyz"; char w[][4] = {"ab", {"cd"}}; return s[1] + s[5] + sizeof(t) + sizeof(w) + w[1][1] - 100 - t[2] + 120;'
+assert 9 'char s[] = "a\tb\"c"; return s[1] + sizeof(s) - 6;'
+assert 2 'unsigned char u[] = "\377"; return u[0] - 253 + u[1];'
+assert 3 'extern int g; extern int h[]; int a[] = {1, 2, 3}; return sizeof a / sizeof a[0];'
+
+echo + floating point
+assert 37 'double x = 1.5; float f = 2.25f; x += f; return x * 10;'
+assert 14 'double a = 1e1, b = .5, c = 0x1p2; return a + b * c + 2.;'
+assert 10 'float f = 0.1f; double d = f; return (f == 0.1) + (d == f) * 2 + (0.1f == 0.1f) * 8;'
+assert 10 'double n = 0.0 / 0.0; return (n == n) + (n != n) * 2 + (n < 1) * 4 + (1 <= 1.0) * 8;'
+assert 2 'return (int)-2.7 + (unsigned char)4.9 + (_Bool)0.5 - 1;'
+assert 6 'unsigned long u = 1e19; double d = u; return (u == 10000000000000000000UL) + (d == 1e19) * 2 + ((unsigned long)d == u) * 4 - 1;'
+assert 7 'double x = 1.5; x++; ++x; float y = x--; return x + y - (x == 2.5) + !0.0 + (0.0 || -0.0) + (0.5 && 1);'
+assert 8 'int i = 3; i *= 2.5; double d[2] = {1, -2}; return i + -d[1] - (sizeof(1.0f) + sizeof(1.0)) / 12;'
+assert 3 'if (0.1) return 3; return 0;'
+
+echo + long double
+assert 64 'long double x = 1.5L; struct { char c; long double v; } s; return sizeof(x) + _Alignof(long double) + sizeof(s);'
+assert 1 'long double a = 1.0L / 3; double d = 1.0 / 3; return a != d;'
+assert 7 'long double c = 2.5L; c++; c += 1; long double old = c--; return (old == 4.5) + (c == 3.5) * 2 + (c > 3) * 4;'
+assert 10 'long double n = 0.0L / 0; return (n == n) + (n != n) * 2 + (n < 1) * 4 + (2.0L <= 2) * 8;'
+assert 6 'unsigned long u = (unsigned long)1e19L; long double x = u; return (u == 10000000000000000000UL) * 2 + (x == 1e19L) * 4 + (int)-0.5L;'
+assert 5 'long double x = -2.5L; float f = -x; int i = x; if (0.5L) return f + i + !0.0L * 4 + 0.5;'
+
+echo + variadic functions
+assert 32 '__builtin_va_list ap; return sizeof(ap) + _Alignof(ap);'
+assert 6 '__builtin_va_list ap, aq; ap->gp_offset = 6; __builtin_va_copy(aq, ap); __builtin_va_end(ap); return aq[0].gp_offset;'
+assert 7 'char b[16]; sprintf(b, "%d%d%d%d%d%d%d", 1, 2, 3, 4, 5, 6, 7); return b[6] - 48;'
+
+echo + unions
+assert 8 'union { int i; char c; long l; } u; return sizeof(u);'
+assert 12 'union { char c[9]; int i; } u; return sizeof(u);'
+assert 81 'union { int i; char c[8]; long l; } u; long *l = &u; int *i = &u; char *c = &u; *l = 0; *i = 258; return sizeof(u) * 10 + c[1];'
+assert 51 'union { int i; char c; } u = { 300 }, v = { .c = 7 }, w; w = u; char *pw = &w; int *pv = &v; return *pw + *pv;'
+assert 3 'struct { int tag; union { int i; char c; } val; } s = { 1, { .c = 2 } }; int *p = &s; return p[0] + p[1];'
+
+echo + initializers
+assert 9 'int a[3] = {1, 2, 3}; int x = {5}; return a[0] + a[2] + x;'
+assert 3 'int a[4] = {1, 2}; return a[0] + a[1] + a[2] + a[3];'
+assert 10 'int a[2][2] = {1, 2, 3, 4}; return a[0][0] + a[0][1] + a[1][0] + a[1][1];'
+assert 11 'struct { int x; int y; } a[3] = { {1, 2}, [2] = { .y = 9 }, }; int *p = a; return p[1] + p[5] + p[2] + p[4];'
+assert 100 'struct { int a; struct { char b; long c; } in; } s = { 1, 2, 3 }, t; long *ps = &s, *pt = &t; char *cs = &s; t = s; pt[2] = 7; return cs[0] + cs[8] + ps[2] + pt[2] * 10 + sizeof(s);'
+assert 14 'int x = 3; int y = x + 1; x = 10; int z = x; return y + z;'
+
 echo + goto and labels
 assert 5 'int a; a = 0; goto skip; a = 10; skip: a = a + 5; return a;'
 